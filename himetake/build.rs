@@ -49,15 +49,12 @@
 use kusabira::KusabiraError;
 use kusabira::builder::{Config as MldBuilderConfig};
 use kusabira::hooks::cc::warnings_into_errors;
-use std::convert::From;
-use std::fmt::{Display, Error as FmtError, Formatter};
-use system_deps::{Config as SystemDepsConfig, Error as SystemDepsError};
 
 ///
 /// Build the library and
 /// [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding file.
 ///
-fn main() -> Result<(), MainError>
+fn main() -> Result<(), KusabiraError>
 {
 	MldBuilderConfig::default()
 		.lib_name("hello_world_c")
@@ -66,51 +63,13 @@ fn main() -> Result<(), MainError>
 			"src/hello_world_c_*.c",
 			"src/unixcw_libcw_demo.c"]
 			.into_iter())
+		.system_library("libcw", ">=7.0")
+		.std("c11")
 		.cc_build_hook(warnings_into_errors)
-		.add_cc_build_hook(|build| {build.std("c11")})
 		.bindgen_builder_hook(|builder| {builder.generate_block(true)})
 		.add_bindgen_builder_hook(|builder| {builder.generate_comments(true)})
 		.add_bindgen_builder_hook(|builder| {builder.newtype_enum("cw_return_values")})
 		.build()?;
 
-	SystemDepsConfig::new().probe()?;
-
 	Ok(())
 }
-
-///
-/// The integrated error data.
-///
-#[derive(Debug)]
-enum MainError
-{
-	KusabiraError(KusabiraError),
-	SystemDepsError(SystemDepsError),
-}
-
-impl From<KusabiraError> for MainError
-{
-	fn from(value: KusabiraError) -> Self
-	{
-		MainError::KusabiraError(value)
-	}
-}
-
-impl From<SystemDepsError> for MainError
-{
-	fn from(value: SystemDepsError) -> Self
-	{
-		MainError::SystemDepsError(value)
-	}
-}
-
-impl Display for MainError
-{
-	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError>
-	{
-		match self {
-			MainError::KusabiraError(err) => write!(f, "MainError: {}", err),
-			MainError::SystemDepsError(err) => write!(f, "MainError: {}", err),
-		}
-	}
-}
@@ -72,4 +72,4 @@ pub mod wrapper;
 #[cfg(doc)]
 use bindgen::builder;
 
-include!(concat!(env!("OUT_DIR"), "/hello_world_export_to_rust.in"));
+kusabira::include_bindings!("hello_world_export_to_rust");
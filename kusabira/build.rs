@@ -0,0 +1,120 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+//!
+//! The build script of [`kusabira`], which tells the `bindgen` version
+//! resolved in `Cargo.lock` to the bindgen cache.
+//!
+
+use std::env;
+use std::fs::read_to_string;
+use std::path::Path;
+
+/// The environment variable of the resolved `bindgen` version, which is
+/// empty if unknown.
+static ENV_KEY_BINDGEN_VERSION: &str = "KUSABIRA_BINDGEN_VERSION";
+
+///
+/// Emit the `bindgen` version that `kusabira` depends on in `Cargo.lock`,
+/// looked up from the output directory and then the package directory
+/// upwards.
+///
+fn main()
+{
+	let lockfile = ["OUT_DIR", "CARGO_MANIFEST_DIR"]
+		.into_iter()
+		.filter_map(|key| {env::var_os(key)})
+		.find_map(|dir| {
+			Path::new(&dir)
+				.ancestors()
+				.map(|dir| {dir.join("Cargo.lock")})
+				.find(|lockfile| {lockfile.is_file()})
+		});
+
+	let version = match &lockfile {
+		Some(lockfile) => {
+			println!("cargo:rerun-if-changed={}", lockfile.display());
+			resolved_bindgen_version(lockfile).unwrap_or_default()
+		},
+		None => {
+			println!("cargo:rerun-if-changed=build.rs");
+			String::new()
+		},
+	};
+	println!("cargo:rustc-env={ENV_KEY_BINDGEN_VERSION}={version}");
+}
+
+///
+/// Look up the version of the `bindgen` dependency of this `kusabira` in
+/// `lockfile`.
+///
+/// A dependency is listed as `bindgen` if only one version of `bindgen` is
+/// locked, or as `bindgen <version>` otherwise.
+///
+fn resolved_bindgen_version(lockfile: &Path) -> Option<String>
+{
+	let lock: toml::Table = read_to_string(lockfile).ok()?.parse().ok()?;
+	let packages = lock.get("package")?.as_array()?;
+	let field = |package: &toml::Value, key: &str| {
+		package.get(key).and_then(toml::Value::as_str).map(str::to_string)
+	};
+
+	let kusabira = packages.iter().find(|package| {
+		field(package, "name").as_deref() == Some(env!("CARGO_PKG_NAME"))
+			&& field(package, "version").as_deref() == Some(env!("CARGO_PKG_VERSION"))
+	})?;
+	let dependency = kusabira
+		.get("dependencies")?
+		.as_array()?
+		.iter()
+		.filter_map(toml::Value::as_str)
+		.find(|dependency| {dependency.split(' ').next() == Some("bindgen")})?;
+	if let Some(version) = dependency.split(' ').nth(1) {
+		return Some(version.to_string());
+	}
+
+	packages
+		.iter()
+		.find(|package| {field(package, "name").as_deref() == Some("bindgen")})
+		.and_then(|package| {field(package, "version")})
+}
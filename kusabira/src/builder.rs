@@ -57,21 +57,52 @@
 //! user-supplied function or closure that receives the backend-dependent
 //! configuration, modifies it and returns it.
 //!
+//! Alternatively, the configuration can be declared in the
+//! [`Cargo`](https://doc.rust-lang.org/cargo/) manifest and read by
+//! [`builder::Config::from_manifest`].  The methods on [`builder::Config`]
+//! still apply on top of the manifest.
+//!
 //! Finally, the builder is executed by [`builder::Config::build`], which
 //! builds the library and
 //! [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding files
 //! altogether.  [`builder::Config::build`] checks the extention of each input
 //! file; the source files are passed together to [`cc::Build::try_compile`] to
-//! build a single library, while the header files are passed in the one-by-one
+//! build a single library, the C++ sources by the separate [`cc::Build`]
+//! configured for C++ and the NASM sources by [`nasm_rs::Build`], while the
+//! header files are passed in the one-by-one
 //! manner to [`bindgen::Builder::generate`] and
 //! [`bindgen::Bindings::write_to_file`].  Both of these behaviors reflect the
 //! usage design of the backends.
 //!
+//! The type and [`builder::Language`] of each input file are looked up by
+//! the extension, following the conventional mapping in
+//! [`builder::SOURCE_EXTS`] and [`builder::HEADER_EXTS`].  The lookup is
+//! case-sensitive, eg `*.C` is C++.  The type of individual input files can
+//! be overridden by [`builder::Config::override_file_type`].  An extension
+//! MUST NOT be configured as both source and header.
+//!
 //! The glob expansion by [`glob`] is supported on the input files.  The glob
 //! expansion happens during the execution of [`builder::Config::build`].
 //!
+//! The relative input file patterns are resolved against the source root
+//! configured by [`builder::Config::source_root`], which defaults to the
+//! package directory (`CARGO_MANIFEST_DIR`) upon the build by
+//! [`Cargo`](https://doc.rust-lang.org/cargo/).  Hence, the build script
+//! works regardless of the current directory.
+//!
+//! On top of [`glob`], the input file patterns support the following syntax:
+//!
+//! * The brace sets, eg `src/{core,io}/*.c`.  A brace set expands into one
+//!   pattern per comma-separated alternative before the glob expansion.
+//! * The exclusion patterns, either prefixed by `!` as the input file or
+//!   configured by [`builder::Config::exclude_input_file`].  Any file matching
+//!   an exclusion pattern is dropped from the input files.
+//! * The ignore files (`.gitignore` and `.ignore`), honored by
+//!   [`builder::Config::respect_ignore_files`].
+//!
 //! All of the input files discovered by [`builder::Config::build`] are
-//! reported to [`Cargo`](https://doc.rust-lang.org/cargo/) by
+//! reported to [`Cargo`](https://doc.rust-lang.org/cargo/), in the absolute
+//! and normalized paths, by
 //! [`cargo:rerun-if-changed`](https://doc.rust-lang.org/cargo/reference/build-scripts.html#rerun-if-changed),
 //! so that an update on any input files trigger the rebuild.  This includes
 //! the recursively included header files found by
@@ -84,6 +115,46 @@
 //! [`Cargo`](https://doc.rust-lang.org/cargo/) by [`builder::Config::build`]
 //! for linking.
 //!
+//! If some of the source files require a different [`cc::Build`]
+//! configuration or linkage, eg the optional plugins, configure them as a
+//! separate [`builder::Library`] by [`builder::Config::add_library`].  Each
+//! library group is compiled into its own library.
+//!
+//! A library is a static archive by default.  Configure
+//! [`builder::LinkKind::Shared`] to link a versioned shared library instead,
+//! eg to share the library with the processes other than Rust, or
+//! [`builder::LinkKind::Objects`] to link the objects directly, eg to keep
+//! the constructors not referenced from Rust.
+//!
+//! ## Configuration headers
+//! Generate the configuration header, eg `config.h`, by
+//! [`builder::Config::add_config_header`] rather than maintaining it by
+//! hand.  The output directory is then an include directory of both of the
+//! backends.
+//!
+//! ## Feature probes
+//! Check the headers, functions, type sizes and compiler flags of the target
+//! by [`builder::Config::add_probe`] rather than assuming them.  The results
+//! are available both to C by the configuration headers and to Rust by
+//! `cfg`.
+//!
+//! ## Generated files
+//! The source and header files generated during the build, either by a code
+//! generator compiled and run by [`builder::Config::add_host_tool`] or by a
+//! closure configured by [`builder::Config::generate_source`], are fed
+//! into the default library in the output directory.  They are compiled and
+//! bound in the same way as the configured input files.
+//!
+//! ## Headers for the dependent crates
+//! For a `-sys` crate with the
+//! [`links`](https://doc.rust-lang.org/cargo/reference/build-scripts.html#the-links-manifest-key)
+//! key, install the public headers by [`builder::Config::install_header`] and
+//! pass any other information by [`builder::Config::metadata`].  The
+//! dependent crates find them as `DEP_<LINKS>_INCLUDE` and so on.  In turn,
+//! [`builder::Config::build`] adds the `DEP_<LINKS>_INCLUDE` directories of
+//! the dependencies to both of the backends, unless disabled by
+//! [`builder::Config::include_dep_dirs`].
+//!
 //! ## Header files
 //! Create a single header file that `#include`s all of the header files
 //! exported to Rust.  Configure only this header file to [`builder::Config`].
@@ -91,7 +162,9 @@
 //! into a Rust source file.  Each binding file has the same filename as the
 //! configured header file except that the extention is replaced by the one
 //! configured by [`builder::Config::binding_ext`], or
-//! [`builder::RUST_FFI_BINDING_EXT`] by default.
+//! [`builder::RUST_FFI_BINDING_EXT`] by default.  The
+//! [`include_bindings`](crate::include_bindings) macro saves writing the path
+//! by hand.
 //!
 //! This configuration is recommended because [`bindgen::Builder::generate`]
 //! requires a header file completely pre-processable and compilable on its
@@ -114,12 +187,19 @@
 //! the binding file into a dedicated module, and `use` the required items only
 //! to avoid flooding a module by many unused identifiers.  As of version
 //! 0.68.1, [`bindgen`] adds `pub` to every bound identifier.
+//! [`builder::Config::restrict_bindings`] also keeps the items of the system
+//! headers out of the binding files.
 //!
 //! Alternatively, you can also create multiple header files and configure all
 //! of them, as long as each header file pre-processes and compiles on its own.
 //! This is a good option if you have multiple features to import and each Rust
 //! module does not require all imported features.
 //!
+//! If the header files share some types and you need them in one module,
+//! configure [`builder::Config::combined_binding`] instead.  The header files
+//! are then bound together into a single binding file, by the wrapper header
+//! generated in place of the hand-written one above.
+//!
 //! # Internal Design Notes
 //! ## Path Storage
 //! The output directory is stored as [`std::path::PathBuf`] because it MUST
@@ -130,15 +210,28 @@
 //! valid path, so [`std::path::PathBuf`] is too restrictive.  [`glob`] deals
 //! with a pattern in the same way.
 //!
+//! The source root is stored as [`std::path::PathBuf`] as it is a path rather
+//! than a pattern.  It is escaped by [`glob::Pattern::escape`] when the
+//! relative patterns are joined to it.
+//!
 //! ## Hook Addition
 //!
 //! The following methods on [`builder::Config`] add a new hook to the existing
 //! one in the configuration:
 //!
 //! * [`builder::Config::add_cc_build_hook`]
+//! * [`builder::Config::add_cxx_build_hook`]
+//! * [`builder::Config::add_nasm_build_hook`]
 //! * [`builder::Config::add_bindgen_builder_hook`]
+//! * [`builder::Config::add_header_bindgen_builder_hook`]
+//! * [`builder::Config::add_sync_bindgen_builder_hook`]
 //! * [`builder::Config::add_glob_matchoptions_hook`]
 //!
+//! [`builder::Library::add_cc_build_hook`],
+//! [`builder::Library::add_cxx_build_hook`] and
+//! [`builder::Library::add_nasm_build_hook`] work in the same way on a library
+//! group.
+//!
 //! Internally, these methods create a new hook closure in which the input
 //! parameter is first passed to the existing hook, and its result is then
 //! passed to the new hook.  Below is the pseudocode of the internal hook
@@ -178,67 +271,175 @@
 use bindgen::Builder;
 #[cfg(test)]
 use tests::busshi::bindgen_builder::Builder;
+#[cfg(not(test))]
+use bindgen::clang_version;
+#[cfg(test)]
+use tests::busshi::bindgen_builder::clang_version;
 use bindgen::CargoCallbacks;
 #[cfg(not(test))]
 use cc::Build;
 #[cfg(test)]
 use tests::busshi::cc_build::Build;
 #[cfg(not(test))]
+use nasm_rs::Build as NasmBuild;
+#[cfg(test)]
+use tests::busshi::nasm_build::Build as NasmBuild;
+#[cfg(not(test))]
 use glob::glob_with;
 #[cfg(test)]
 use tests::busshi::glob::glob_with;
-use glob::MatchOptions;
+use glob::{MatchOptions, Pattern};
 use std::boxed::Box;
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::convert::AsRef;
 use std::env;
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
+use std::fs::{create_dir_all, read, read_to_string, write};
 use std::fmt::{Display, Error as FmtError, Formatter};
 use std::iter::Iterator;
-use std::path::Path;
+use std::mem::{replace, take};
+use std::panic::{catch_unwind, resume_unwind};
+use std::path::{Component, MAIN_SEPARATOR_STR, Path};
 #[cfg(not(test))]
 use std::path::PathBuf;
 #[cfg(test)]
 use tests::busshi::std_path_path_buf::PathBuf;
 use std::path::PathBuf as StdPathBuf;
+use std::env::consts::EXE_SUFFIX;
 use std::process::{ExitCode, Termination};
+use std::sync::{Arc, Mutex};
+#[cfg(not(test))]
+use std::process::Command;
+#[cfg(test)]
+use tests::busshi::std_process_command::Command;
 
 use super::error::Error as MldError;
 use super::hooks::bindgen::reflect as reflect_bindgen;
+use super::hooks::bindgen::reflect_header as reflect_header_bindgen;
 use super::hooks::cc::reflect as reflect_cc;
 use super::hooks::glob::reflect as reflect_glob;
+use super::hooks::nasm::reflect as reflect_nasm;
 
-// This `use` is required for the document to link to
-// `system_deps::Config::probe`.
-#[cfg(doc)]
-use system_deps::Config as SystemDepsConfig;
+use abi_layout::{TypeLayout, parse_binding, read_headers, render_checker, render_tests};
+use bindgen_cache::{BindgenCache, Dependencies, DependencyRecorder};
+use pattern::{
+	IgnoreFiles,
+	expand_braces,
+	normalize_path,
+	resolve_pattern,
+	split_patterns,
+};
+use probe::run_probes;
+use system_library::find_system_library;
 
+/// The ABI layout checks of the binding files against the C compiler.
+mod abi_layout;
+/// The content-addressed cache of the binding files.
+mod bindgen_cache;
+/// The Rust module of the binding files generated in the output directory.
+mod bindings_mod;
+/// The configuration headers generated in the output directory.
+mod config_header;
+/// The host tools run before the input file expansion.
+mod host_tool;
+/// The input file language model.
+mod language;
+/// The library groups on top of the default library.
+mod library;
+/// The declarative configuration out of the Cargo manifest.
+mod manifest;
+/// The worker threads limited by the jobserver.
+mod parallel;
+/// The input file pattern handling.
+mod pattern;
+/// The feature probes run before the configuration header generation.
+mod probe;
+/// The system libraries found by `pkg-config`.
+mod system_library;
+/// The vendored binding files committed to the source tree.
+mod vendored_bindings;
 #[cfg(test)]
 /// The tests for [`builder`].
 pub mod tests;
 
+pub use config_header::ConfigHeader;
+pub use host_tool::HostTool;
+pub use language::{FileType, Language};
+pub use library::{Library, LinkKind};
+pub use probe::Probe;
+pub use vendored_bindings::VendoredBindingsMode;
+
 /// The environment variable name [`Cargo`](https://doc.rust-lang.org/cargo/)
 /// configures the output directory.
 pub static ENV_KEY_OUT_DIR: &str = "OUT_DIR";
 
-/// The default path extensions for the source files passed to [`cc::Build`].
-pub static SOURCE_EXTS: [&str; 5] =
+/// The environment variable name [`Cargo`](https://doc.rust-lang.org/cargo/)
+/// configures the package directory.
+pub static ENV_KEY_CARGO_MANIFEST_DIR: &str = "CARGO_MANIFEST_DIR";
+
+/// The environment variable name [`Cargo`](https://doc.rust-lang.org/cargo/)
+/// configures the host triple.
+pub static ENV_KEY_HOST: &str = "HOST";
+
+/// The environment variable name [`Cargo`](https://doc.rust-lang.org/cargo/)
+/// configures the target triple.
+pub static ENV_KEY_TARGET: &str = "TARGET";
+
+/// The environment variable name to override [`VendoredBindingsMode`] of the
+/// vendored bindings configured by [`Config::vendored_bindings`].
+///
+/// `verify` and `update` select [`VendoredBindingsMode::Verify`] and
+/// [`VendoredBindingsMode::Update`] respectively, so that the continuous
+/// integration can check the vendored binding files.  `1` and `force` select
+/// [`VendoredBindingsMode::Force`], and `fallback` selects
+/// [`VendoredBindingsMode::Fallback`].  Empty and `0` leave the configured
+/// mode as is.  The value is case-sensitive, and [`Config::build`] fails on
+/// any other value.
+pub static ENV_KEY_VENDORED_BINDINGS: &str = "KUSABIRA_VENDORED_BINDINGS";
+
+/// The environment variable name to override the cache directory of
+/// [`Config::bindgen_cache`], so that multiple packages and builds can share
+/// the cached binding files.
+pub static ENV_KEY_BINDGEN_CACHE_DIR: &str = "KUSABIRA_BINDGEN_CACHE_DIR";
+
+/// The default path extensions for the source files passed to [`cc::Build`],
+/// and their languages.
+///
+/// The extensions follow the conventional mapping of GCC and Clang.  They are
+/// case-sensitive, eg `C` is C++ while `c` is C.
+pub static SOURCE_EXTS: [(&str, Language); 15] =
 [
-	"c",
-	"cc",
-	"cpp",
-	"cxx",
-	"s",
+	("c", Language::C),
+	("C", Language::Cxx),
+	("cc", Language::Cxx),
+	("cp", Language::Cxx),
+	("cpp", Language::Cxx),
+	("CPP", Language::Cxx),
+	("cxx", Language::Cxx),
+	("c++", Language::Cxx),
+	("i", Language::PreprocessedC),
+	("ii", Language::PreprocessedCxx),
+	("s", Language::Assembly),
+	("S", Language::PreprocessedAssembly),
+	("sx", Language::PreprocessedAssembly),
+	("asm", Language::Nasm),
+	("nasm", Language::Nasm),
 ];
 
 /// The default path extensions for the header files passed to
-/// [`bindgen::Builder::generate`].
-pub static HEADER_EXTS: [&str; 4] =
+/// [`bindgen::Builder::generate`], and their languages.
+///
+/// As in [`SOURCE_EXTS`], the extensions are case-sensitive.
+pub static HEADER_EXTS: [(&str, Language); 7] =
 [
-	"h",
-	"hh",
-	"hpp",
-	"hxx",
+	("h", Language::C),
+	("H", Language::Cxx),
+	("hh", Language::Cxx),
+	("hp", Language::Cxx),
+	("hpp", Language::Cxx),
+	("hxx", Language::Cxx),
+	("h++", Language::Cxx),
 ];
 
 /// The default extension of the
@@ -248,6 +449,53 @@ pub static HEADER_EXTS: [&str; 4] =
 /// the true Rust source files.
 pub static RUST_FFI_BINDING_EXT: &str = "in";
 
+/// The filename of the Rust module generated in the output directory, which
+/// declares one submodule per
+/// [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding file.
+///
+/// [`crate::include_bindings`] `include!`s this module.
+pub static BINDINGS_MOD_FILENAME: &str = "bindings_mod.rs";
+
+/// The directory in the output directory to install the public headers into,
+/// reported to [`Cargo`](https://doc.rust-lang.org/cargo/) as
+/// `cargo:include`.
+pub static INSTALL_INCLUDE_DIR: &str = "include";
+
+/// The default cache directory of [`Config::bindgen_cache`] in the output
+/// directory.
+pub static BINDGEN_CACHE_DIR: &str = "bindgen-cache";
+
+/// The directory in the output directory to generate the C programs and the
+/// Rust tests of [`Config::abi_layout_tests`] into.
+pub static ABI_LAYOUT_DIR: &str = "abi_layout";
+
+/// The prefixes of the [`Cargo`](https://doc.rust-lang.org/cargo/)
+/// instructions, which MUST NOT be used as the metadata keys.
+static CARGO_INSTRUCTION_PREFIXES: [&str; 5] =
+[
+	"rerun-if-",
+	"rustc-",
+	"warning",
+	"error",
+	"metadata",
+];
+
+/// The boxed hook to configure [`cc::Build`].
+type CcBuildHookFn<'a> = Box<dyn FnOnce(&mut Build) -> &mut Build + 'a>;
+
+/// The boxed hook to configure [`nasm_rs::Build`].
+type NasmBuildHookFn<'a> = Box<dyn FnOnce(&mut NasmBuild) -> &mut NasmBuild + 'a>;
+
+/// The boxed hook to configure [`bindgen::Builder`] for a header file.
+type HeaderBindgenBuilderHookFn<'a> = Box<dyn FnMut(Builder, &HeaderBinding) -> Builder + 'a>;
+
+/// The boxed thread-safe hook to configure [`bindgen::Builder`] for a header
+/// file.
+type SyncBindgenBuilderHookFn<'a> = Box<dyn Fn(Builder, &HeaderBinding) -> Builder + Send + Sync + 'a>;
+
+/// The boxed closure generating a file in the output directory.
+type GenerateSourceFn<'a> = Box<dyn FnOnce(&Path) -> Result<StdPathBuf, MldError> + 'a>;
+
 ///
 /// The configuration parameters, as well as the entry to the builder engine.
 ///
@@ -257,13 +505,46 @@ pub static RUST_FFI_BINDING_EXT: &str = "in";
 pub struct Config<'a>
 {
 	out_dir: PathBuf,
-	input_files: Vec<&'a str>,
-	lib_name: Option<&'a str>,
-	cc_exts: Vec<String>,
-	bindgen_exts: Vec<String>,
-	binding_ext: &'a str,
-	cc_build_hook: RefCell<Box<dyn FnOnce(&mut Build) -> &mut Build + 'a>>,
+	source_root: Option<StdPathBuf>,
+	input_files: Vec<String>,
+	exclude_input_files: Vec<String>,
+	respect_ignore_files: bool,
+	lib_name: Option<String>,
+	link_kind: LinkKind,
+	libraries: Vec<Library<'a>>,
+	pre_build_steps: Vec<PreBuildStep<'a>>,
+	config_headers: Vec<ConfigHeader>,
+	probes: Vec<Probe>,
+	include_dirs: Vec<StdPathBuf>,
+	defines: Vec<(String, Option<String>)>,
+	std: Option<String>,
+	cxx_std: Option<String>,
+	include_input_dirs: bool,
+	include_dep_dirs: bool,
+	restrict_bindings: bool,
+	restrict_bindings_dirs: Vec<StdPathBuf>,
+	system_libraries: Vec<(String, String)>,
+	installed_headers: Vec<(String, String)>,
+	metadata: Vec<(String, String)>,
+	cc_exts: Vec<(String, Language)>,
+	bindgen_exts: Vec<(String, Language)>,
+	file_type_overrides: Vec<(String, FileType)>,
+	binding_ext: String,
+	combined_binding: Option<String>,
+	vendored_bindings_dir: Option<StdPathBuf>,
+	vendored_bindings_mode: VendoredBindingsMode,
+	bindgen_cache: bool,
+	bindgen_cache_dir: Option<StdPathBuf>,
+	parallel_bindgen: bool,
+	abi_layout_tests: bool,
+	cc_build_hook: RefCell<CcBuildHookFn<'a>>,
+	cxx_build_hook: RefCell<CcBuildHookFn<'a>>,
+	nasm_build_hook: RefCell<NasmBuildHookFn<'a>>,
+	nasm_object_format: Option<String>,
 	bindgen_builder_hook: RefCell<Box<dyn FnMut(Builder) -> Builder + 'a>>,
+	header_bindgen_builder_hook: RefCell<HeaderBindgenBuilderHookFn<'a>>,
+	unsync_bindgen_builder_hooks: bool,
+	sync_bindgen_builder_hook: SyncBindgenBuilderHookFn<'a>,
 	glob_matchoptions_hook: RefCell<Box<dyn FnOnce(MatchOptions) -> MatchOptions + 'a>>,
 }
 
@@ -275,13 +556,32 @@ impl<'a> Default for Config<'a>
 	/// # Default Parameters
 	/// * *Output Directory*: The value of environment variable `OUT_DIR` if
 	///    defined, the current directory (`.`) otherwise.
+	/// * *Source Root*: The value of environment variable
+	///   `CARGO_MANIFEST_DIR` if defined, the current directory otherwise.
 	/// * *Input Files*: None.
+	/// * *Exclusion Input File Patterns*: None.
+	/// * *Respect Ignore Files*: No.
 	/// * *Library Name*: None.
+	/// * *Library Linkage*: [`LinkKind::Static`].
+	/// * *Library Groups*: None.
 	/// * *Source File Extensions*: As defined in [`SOURCE_EXTS`].
 	/// * *Header File Extensions*: As defined in [`HEADER_EXTS`].
+	/// * *File Type Overrides*: None.
 	/// * *Binding File Extension*: As defined in [`RUST_FFI_BINDING_EXT`].
+	/// * *Combined Binding File*: None, ie one binding file per header file.
+	/// * *Vendored Binding Directory*: None.
+	/// * *Vendored Binding Mode*: [`VendoredBindingsMode::Fallback`], unless
+	///   overridden by [`ENV_KEY_VENDORED_BINDINGS`].
+	/// * *Binding Cache*: No.
+	/// * *Parallel Binding Generation*: No.
+	/// * *ABI Layout Tests*: No.
 	/// * *[`cc::Build`] Configuration Hook*: [`super::hooks::cc::reflect`].
+	/// * *[`cc::Build`] Configuration Hook for C++*: [`super::hooks::cc::reflect`].
+	/// * *[`nasm_rs::Build`] Configuration Hook*: [`super::hooks::nasm::reflect`].
+	/// * *NASM Object Format*: Selected by [`nasm_rs`] for the target.
 	/// * *[`bindgen::Builder`] Configuration Hook*: [`super::hooks::bindgen::reflect`].
+	/// * *[`bindgen::Builder`] Configuration Hook per Header*: [`super::hooks::bindgen::reflect_header`].
+	/// * *Thread-Safe [`bindgen::Builder`] Configuration Hook*: [`super::hooks::bindgen::reflect_header`].
 	/// * *[`glob::MatchOptions`] Configuration Hook*: [`super::hooks::glob::reflect`].
 	///
 	/// # Example
@@ -296,15 +596,49 @@ impl<'a> Default for Config<'a>
 		Config {
 			out_dir: PathBuf::from(
 				env::var(&ENV_KEY_OUT_DIR).unwrap_or(".".to_string())),
+			source_root: env::var_os(ENV_KEY_CARGO_MANIFEST_DIR)
+				.map(StdPathBuf::from),
 			input_files: Vec::new(),
+			exclude_input_files: Vec::new(),
+			respect_ignore_files: false,
 			lib_name: None,
+			link_kind: LinkKind::default(),
+			libraries: Vec::new(),
+			pre_build_steps: Vec::new(),
+			config_headers: Vec::new(),
+			probes: Vec::new(),
+			include_dirs: Vec::new(),
+			defines: Vec::new(),
+			std: None,
+			cxx_std: None,
+			include_input_dirs: true,
+			include_dep_dirs: true,
+			restrict_bindings: false,
+			restrict_bindings_dirs: Vec::new(),
+			system_libraries: Vec::new(),
+			installed_headers: Vec::new(),
+			metadata: Vec::new(),
 			cc_exts: SOURCE_EXTS
-				.iter().map(|&x| {String::from(x)}).collect(),
+				.iter().map(|&(x, language)| {(String::from(x), language)}).collect(),
 			bindgen_exts: HEADER_EXTS
-				.iter().map(|&x| {String::from(x)}).collect(),
-			binding_ext: RUST_FFI_BINDING_EXT,
+				.iter().map(|&(x, language)| {(String::from(x), language)}).collect(),
+			file_type_overrides: Vec::new(),
+			binding_ext: RUST_FFI_BINDING_EXT.to_string(),
+			combined_binding: None,
+			vendored_bindings_dir: None,
+			vendored_bindings_mode: VendoredBindingsMode::default(),
+			bindgen_cache: false,
+			bindgen_cache_dir: None,
+			parallel_bindgen: false,
+			abi_layout_tests: false,
 			cc_build_hook: RefCell::new(Box::new(reflect_cc)),
+			cxx_build_hook: RefCell::new(Box::new(reflect_cc)),
+			nasm_build_hook: RefCell::new(Box::new(reflect_nasm)),
+			nasm_object_format: None,
 			bindgen_builder_hook: RefCell::new(Box::new(reflect_bindgen)),
+			header_bindgen_builder_hook: RefCell::new(Box::new(reflect_header_bindgen)),
+			unsync_bindgen_builder_hooks: false,
+			sync_bindgen_builder_hook: Box::new(reflect_header_bindgen),
 			glob_matchoptions_hook: RefCell::new(Box::new(reflect_glob)),
 		}
 	}
@@ -344,10 +678,40 @@ impl<'a> Config<'a>
 		self
 	}
 
+	///
+	/// Set the source root directory, against which the relative input file
+	/// patterns are resolved.
+	///
+	/// A relative source root is resolved against the current directory upon
+	/// [`Config::build`].
+	///
+	/// This is primarily for the non-[`Cargo`](https://doc.rust-lang.org/cargo/)
+	/// usages and the input files out of the package directory.  Upon the
+	/// build by [`Cargo`](https://doc.rust-lang.org/cargo/), the package
+	/// directory is the source root by default.
+	///
+	/// # Example
+	/// ```
+	/// use std::path::Path;
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.source_root(Path::new("vendor/libfoo"))
+	/// 	.input_file("src/*.c");
+	/// ```
+	///
+	pub fn source_root(mut self, source_root: &Path) -> Self
+	{
+		self.source_root = Some(source_root.to_path_buf());
+		self
+	}
+
 	///
 	/// Set a single input file.
 	///
-	/// The filename MAY be a [`glob`] pattern.
+	/// The filename MAY be a [`glob`] pattern, optionally with the brace sets.
+	/// A filename prefixed by `!` is an exclusion pattern.  Refer to
+	/// [the module document](super::builder) for the detail.
 	///
 	/// Any existing input files are removed from the configuration.
 	///
@@ -368,7 +732,9 @@ impl<'a> Config<'a>
 	///
 	/// Set either a single or multiple input files via an iterator.
 	///
-	/// The filename MAY be a [`glob`] pattern.
+	/// The filename MAY be a [`glob`] pattern, optionally with the brace sets.
+	/// A filename prefixed by `!` is an exclusion pattern.  Refer to
+	/// [the module document](super::builder) for the detail.
 	///
 	/// Any existing input files are removed from the configuration.
 	///
@@ -392,7 +758,9 @@ impl<'a> Config<'a>
 	///
 	/// Add a single input file.
 	///
-	/// The filename MAY be a [`glob`] pattern.
+	/// The filename MAY be a [`glob`] pattern, optionally with the brace sets.
+	/// A filename prefixed by `!` is an exclusion pattern.  Refer to
+	/// [the module document](super::builder) for the detail.
 	///
 	/// Any existing input files are preserved in the configuration.
 	///
@@ -407,14 +775,16 @@ impl<'a> Config<'a>
 	///
 	pub fn add_input_file(mut self, filename: &'a str) -> Self
 	{
-		self.input_files.push(filename);
+		self.input_files.push(filename.to_string());
 		self
 	}
 
 	///
 	/// Add either a single or multiple input files via an iterator.
 	///
-	/// The filename MAY be a [`glob`] pattern.
+	/// The filename MAY be a [`glob`] pattern, optionally with the brace sets.
+	/// A filename prefixed by `!` is an exclusion pattern.  Refer to
+	/// [the module document](super::builder) for the detail.
 	///
 	/// Any existing input files are preserved in the configuration.
 	///
@@ -433,357 +803,1661 @@ impl<'a> Config<'a>
 		where IT: Iterator<Item = &'a str>
 	{
 		for filename in filename_iter {
-			self.input_files.push(filename);
+			self.input_files.push(filename.to_string());
 		}
 		self
 	}
 
 	///
-	/// Set the output library name.
+	/// Set a single exclusion input file pattern.
 	///
-	/// This library named is passed to [`cc::Build::try_compile`].
+	/// The pattern is a [`glob`] pattern, optionally with the brace sets.  Any
+	/// input file matching the pattern is not built.  The pattern MUST NOT be
+	/// prefixed by `!`.
 	///
-	/// Refer to [`cc::Build::compile`] for the convention upon the library
-	/// name.
+	/// Any existing exclusion patterns are removed from the configuration.
+	/// The input files prefixed by `!` are not affected.
 	///
 	/// # Example
 	/// ```
 	/// use kusabira::builder::Config;
 	///
-	/// let lib_name = "test_lib";
 	/// let config = Config::default()
-	/// 	.lib_name(lib_name);
+	/// 	.input_file("src/**/*.c")
+	/// 	.exclude_input_file("src/legacy/**");
 	/// ```
 	///
-	pub fn lib_name(mut self, lib_name: &'a str) -> Self
+	pub fn exclude_input_file(mut self, pattern: &'a str) -> Self
 	{
-		self.lib_name = Some(lib_name);
-		self
+		self.exclude_input_files.clear();
+		self.add_exclude_input_file(pattern)
 	}
 
 	///
-	/// Set the [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding
-	/// file extension.
+	/// Set either a single or multiple exclusion input file patterns via an
+	/// iterator.
 	///
-	/// As of Oct 2023, there is no common extention for the Rust include file.
-	/// The example of [std::include] uses `in`, which is also the default,
-	/// while `rs` also makes a sense because an `include!`d file is a valid
-	/// Rust source file.
+	/// Refer to [`Config::exclude_input_file`] for the pattern syntax.
+	///
+	/// Any existing exclusion patterns are removed from the configuration.
 	///
 	/// # Example
 	/// ```
 	/// use kusabira::builder::Config;
 	///
-	/// let binding_ext = "rs";
 	/// let config = Config::default()
-	/// 	.binding_ext(binding_ext);
+	/// 	.input_file("src/**/*.c")
+	/// 	.exclude_input_files(
+	/// 		["src/**/test_*.c", "src/{examples,legacy}/**"]
+	/// 		.into_iter());
 	/// ```
 	///
-	pub fn binding_ext(mut self, binding_ext: &'a str) -> Self
+	pub fn exclude_input_files<IT>(mut self, pattern_iter: IT) -> Self
+		where IT: Iterator<Item = &'a str>
 	{
-		self.binding_ext = binding_ext;
-		self
+		self.exclude_input_files.clear();
+		self.add_exclude_input_files(pattern_iter)
 	}
 
 	///
-	/// Add a source file extention regarded as the input to [`cc::Build`].
+	/// Add a single exclusion input file pattern.
 	///
-	/// If the extension is already added, it is not added again.
+	/// Refer to [`Config::exclude_input_file`] for the pattern syntax.
+	///
+	/// Any existing exclusion patterns are preserved in the configuration.
 	///
 	/// # Example
 	/// ```
 	/// use kusabira::builder::Config;
 	///
-	/// let source_ext = "i";
 	/// let config = Config::default()
-	/// 	.add_source_ext(source_ext);
+	/// 	.input_file("src/**/*.c")
+	/// 	.exclude_input_file("src/**/test_*.c")
+	/// 	.add_exclude_input_file("src/legacy/**");
 	/// ```
 	///
-	pub fn add_source_ext(mut self, ext: &str) -> Self
+	pub fn add_exclude_input_file(mut self, pattern: &'a str) -> Self
 	{
-		if self.cc_exts.iter().find(|&x| {x == ext}).is_none() {
-			self.cc_exts.push(ext.to_string());
-		}
+		self.exclude_input_files.push(pattern.to_string());
 		self
 	}
 
 	///
-	/// Delete a source file extention regarded as the input to [`cc::Build`].
+	/// Add either a single or multiple exclusion input file patterns via an
+	/// iterator.
 	///
-	/// If the extension is not added, this method does nothing.
+	/// Refer to [`Config::exclude_input_file`] for the pattern syntax.
+	///
+	/// Any existing exclusion patterns are preserved in the configuration.
 	///
 	/// # Example
 	/// ```
 	/// use kusabira::builder::Config;
 	///
-	/// let source_ext = "cc";
 	/// let config = Config::default()
-	/// 	.delete_source_ext(source_ext);
+	/// 	.input_file("src/**/*.c")
+	/// 	.exclude_input_file("src/**/test_*.c")
+	/// 	.add_exclude_input_files(
+	/// 		["src/examples/**", "src/legacy/**"]
+	/// 		.into_iter());
 	/// ```
 	///
-	pub fn delete_source_ext(mut self, ext: &str) -> Self
+	pub fn add_exclude_input_files<IT>(mut self, pattern_iter: IT) -> Self
+		where IT: Iterator<Item = &'a str>
 	{
-		if let Some(i) = self.cc_exts.iter().position(|x| {x == ext}) {
-			self.cc_exts.remove(i);
+		for pattern in pattern_iter {
+			self.exclude_input_files.push(pattern.to_string());
 		}
 		self
 	}
 
 	///
-	/// Add a header file extention regarded as the input to
-	/// [`bindgen::Builder::generate`].
+	/// Set whether the ignore files are respected upon the glob expansion.
 	///
-	/// If the extension is already added, it is not added again.
+	/// If respected, any input file ignored by the `.gitignore` or `.ignore`
+	/// files in its directory or the ancestors, up to the top of the
+	/// repository, is not built.  The ignore files are also reported to
+	/// [`Cargo`](https://doc.rust-lang.org/cargo/) by
+	/// [`cargo:rerun-if-changed`](https://doc.rust-lang.org/cargo/reference/build-scripts.html#rerun-if-changed).
 	///
 	/// # Example
 	/// ```
 	/// use kusabira::builder::Config;
 	///
-	/// let header_ext = "hhh";
 	/// let config = Config::default()
-	/// 	.add_header_ext(header_ext);
+	/// 	.input_file("vendor/**/*.c")
+	/// 	.respect_ignore_files(true);
 	/// ```
 	///
-	pub fn add_header_ext(mut self, ext: &str) -> Self
+	pub fn respect_ignore_files(mut self, respect_ignore_files: bool) -> Self
 	{
-		if self.bindgen_exts.iter().find(|&x| {x == ext}).is_none() {
-			self.bindgen_exts.push(ext.to_string());
-		}
+		self.respect_ignore_files = respect_ignore_files;
 		self
 	}
 
 	///
-	/// Delete a header file extention regarded as the input to
-	/// [`bindgen::Builder::generate`].
+	/// Set the output library name.
 	///
-	/// If the extension is not added, this method does nothing.
+	/// This library named is passed to [`cc::Build::try_compile`].
+	///
+	/// Refer to [`cc::Build::compile`] for the convention upon the library
+	/// name.
 	///
 	/// # Example
 	/// ```
 	/// use kusabira::builder::Config;
 	///
-	/// let header_ext = "hh";
+	/// let lib_name = "test_lib";
 	/// let config = Config::default()
-	/// 	.delete_header_ext(header_ext);
+	/// 	.lib_name(lib_name);
 	/// ```
 	///
-	pub fn delete_header_ext(mut self, ext: &str) -> Self
+	pub fn lib_name(mut self, lib_name: &'a str) -> Self
 	{
-		if let Some(i) = self.bindgen_exts.iter().position(|x| {x == ext}) {
-			self.bindgen_exts.remove(i);
-		}
+		self.lib_name = Some(lib_name.to_string());
 		self
 	}
 
 	///
-	/// Set the hook to configure [`cc::Build`].
-	///
-	/// The configured hook is called only once during the execution of
-	/// [`Config::build`].
-	///
-	/// The configured hook replaces the old one.
+	/// Set the linkage of the default library, ie the one named by
+	/// [`Config::lib_name`].
 	///
-	/// The parameter type of the hook is aligned to the configuration methods
-	/// of [`cc::Build`], which receives and returns `&mut cc::Build`.
+	/// The library is archived by [`cc::Build::try_compile`] unless
+	/// [`LinkKind::Shared`], which links the shared library
+	/// `lib<lib_name>.so.<soversion>` in the output directory instead, or
+	/// [`LinkKind::Objects`], which links the objects directly.
 	///
 	/// # Example
 	/// ```
-	/// use cc::Build;
-	/// use kusabira::builder::Config;
+	/// use kusabira::builder::{Config, LinkKind};
 	///
 	/// let config = Config::default()
-	/// 	.cc_build_hook(|build: &mut Build| {build});
-	/// ```
-	///
-	/// The [`FnOnce`] trait is sufficient on the hook, so it MAY consume any
-	/// external data.
-	/// ```
-	/// use cc::Build;
-	/// use kusabira::builder::Config;
+	/// 	.lib_name("test_lib")
+	/// 	.link_kind(LinkKind::WholeArchive);
 	///
-	/// let consumed = String::from("c11");
-	/// let mut consumer = vec![];
-	/// let _config = Config::default()
-	/// 	.cc_build_hook(
-	/// 		|build: &mut Build|
-	/// 		{
-	/// 			build.std(&consumed);
-	/// 			consumer.push(consumed);
-	/// 			build
-	/// 		});
+	/// let shared_config = Config::default()
+	/// 	.lib_name("test_lib")
+	/// 	.link_kind(LinkKind::Shared(1));
 	/// ```
 	///
-	pub fn cc_build_hook<CcBuildHook>(
-		mut self,
-		cc_build_hook: CcBuildHook)
-		-> Self
-		where CcBuildHook: FnOnce(&mut Build) -> &mut Build + 'a
+	pub fn link_kind(mut self, link_kind: LinkKind) -> Self
 	{
-		self.cc_build_hook = RefCell::new(Box::new(cc_build_hook));
+		self.link_kind = link_kind;
 		self
 	}
 
 	///
-	/// Add a new hook to configure [`cc::Build`].
+	/// Add a library group, built into its own archive on top of the default
+	/// library.
 	///
-	/// The configured hook is called only once during the execution of
-	/// [`Config::build`].
+	/// The default library consists of the input files configured to
+	/// [`Config`] and is configured by [`Config::lib_name`],
+	/// [`Config::link_kind`] and [`Config::cc_build_hook`].  A library group
+	/// has its own input files, [`cc::Build`] configuration hook and
+	/// [`LinkKind`], while the other parameters are shared.  The header files
+	/// in a library group are bound in the same way as the ones in [`Config`].
 	///
-	/// Refer to [the Hook Addition section](super::builder#hook-addition) for
-	/// the detail of the hook generated by this method.
+	/// Each library group MUST have a unique name and at least one source
+	/// file.
 	///
-	/// The parameter type of the hook is aligned to the configuration methods
-	/// of [`cc::Build`], which receives and returns `&mut cc::Build`.
+	/// The library groups are built and reported to
+	/// [`Cargo`](https://doc.rust-lang.org/cargo/) in the order added, followed
+	/// by the default library.  As the linker resolves the symbols in the
+	/// reported order, a library group MAY depend on the ones added after it
+	/// and the default library.
 	///
 	/// # Example
 	/// ```
-	/// use cc::Build;
-	/// use kusabira::builder::Config;
+	/// use kusabira::builder::{Config, Library, LinkKind};
+	/// use kusabira::hooks::cc::warnings_into_errors;
 	///
 	/// let config = Config::default()
-	/// 	.add_cc_build_hook(|build: &mut Build| {build});
+	/// 	.lib_name("foo_core")
+	/// 	.input_file("src/core/*.c")
+	/// 	.cc_build_hook(warnings_into_errors)
+	/// 	.add_library(Library::new("foo_plugins")
+	/// 		.input_file("src/plugins/*.c")
+	/// 		.link_kind(LinkKind::WholeArchive)
+	/// 		.cc_build_hook(|build| {build.define("FOO_PLUGIN", None)}));
 	/// ```
 	///
-	/// Refer to the example on [`Config::cc_build_hook`] for the usage of a
-	/// hook with only the [`FnOnce`] trait.
-	///
-	pub fn add_cc_build_hook<CcBuildHook>(
-		mut self,
-		cc_build_hook: CcBuildHook)
-		-> Self
-		where CcBuildHook: FnOnce(&mut Build) -> &mut Build + 'a
+	pub fn add_library(mut self, library: Library<'a>) -> Self
 	{
-		let cc_build_hook_fn = (self.cc_build_hook)
-			.replace(Box::new(reflect_cc));
-		self.cc_build_hook = RefCell::new(Box::new(move |build: &mut Build| {
-			cc_build_hook(cc_build_hook_fn(build))
-		}));
+		self.libraries.push(library);
 		self
 	}
 
 	///
-	/// Set the hook to configure [`bindgen::Builder`].
+	/// Add a host tool, built and run before the input files are expanded.
 	///
-	/// The configured hook is called for each configured C header file during
-	/// the execution of [`Config::build`].
+	/// The host tools are compiled for the host, even under the
+	/// cross-compilation, and run in the order added.  Refer to [`HostTool`]
+	/// for the detail.
 	///
-	/// The configured hook replaces the old one.
+	/// The output files of the host tools are fed into the input files of the
+	/// default library.  They are classified by the extensions as the other
+	/// input files, but neither the exclusion patterns nor the ignore files
+	/// apply to them.  As with the other input files, the directories of the
+	/// output files are added as the include directories of both of the
+	/// backends, so that a generated header file can be `#include`d from the
+	/// C sources and the bound headers.  Add the output directory by
+	/// [`Config::include_dir`] instead if the directories of the input files
+	/// are not included by [`Config::include_input_dirs`].
 	///
-	/// The parameter type of the hook is aligned to the configuration methods
-	/// of [`bindgen::Builder`], which receives and returns `bindgen::Builder`.
+	/// The host tools require `HOST` configured by
+	/// [`Cargo`](https://doc.rust-lang.org/cargo/).
 	///
 	/// # Example
 	/// ```
-	/// use bindgen::Builder;
-	/// use kusabira::builder::Config;
+	/// use kusabira::builder::{Config, HostTool};
 	///
 	/// let config = Config::default()
-	/// 	.bindgen_builder_hook(|builder: Builder| {builder});
+	/// 	.lib_name("foo")
+	/// 	.input_file("src/*.c")
+	/// 	.add_host_tool(HostTool::new("gen_tables")
+	/// 		.source_file("tools/gen_tables.c")
+	/// 		.args(["tables.c", "tables.h"].into_iter())
+	/// 		.output_files(["tables.c", "tables.h"].into_iter()));
 	/// ```
 	///
-	/// Mind that the configuration methods of [`bindgen::Builder`] tend to
-	/// consume `self`, which is returned as a separate value, as well as
-	/// the parameters.  Implement your hooks so that it does not miss the
-	/// [`FnMut`] trait and do not forget to receive the returned
-	/// [`bindgen::Builder`] data.
+	pub fn add_host_tool(mut self, host_tool: HostTool<'a>) -> Self
+	{
+		self.pre_build_steps.push(PreBuildStep::HostTool(host_tool));
+		self
+	}
+
 	///
-	/// A hook below compiles and works because it does not consume
-	/// `rustified_enum`, so the hook has the [`FnMut`] trait:
-	/// ```
-	/// use bindgen::Builder;
-	/// use kusabira::builder::Config;
+	/// Add a configuration header, generated in the output directory before
+	/// the input files are expanded.
 	///
-	/// let rustified_enum = String::from("rust_style_enum");
-	/// let _config = Config::default()
-	/// 	.bindgen_builder_hook(
-	/// 		|builder: Builder|
-	/// 		{
-	/// 			let builder = builder.rustified_enum(&rustified_enum);
-	/// 			builder
-	/// 		});
-	/// ```
+	/// The output directory is added as an include directory to all of the
+	/// [`cc::Build`]s of the libraries, and to [`bindgen::Builder`] by `-I`,
+	/// before the hooks are called.  The header is written only if the
+	/// content changes, so that it does not trigger the rebuild in vain.
 	///
-	/// The following hook consumes `rustified_enum`, so it does not have the
-	/// [`FnMut`] trait and causes a compilation error:
-	/// ```compile_fail
-	/// use bindgen::Builder;
-	/// use kusabira::builder::Config;
+	/// Refer to [`ConfigHeader`] for the syntax of the template.
 	///
-	/// let rustified_enum = String::from("rust_style_enum");
-	/// let _config = Config::default()
-	/// 	.bindgen_builder_hook(
-	/// 		|builder: Builder|
-	/// 		{
-	/// 			let builder = builder.rustified_enum(rustified_enum);
-	/// 			// rustified_enum is lost!
-	/// 			builder
-	/// 		});
+	/// # Example
 	/// ```
+	/// use kusabira::builder::{Config, ConfigHeader};
 	///
-	/// A hook also fails to compile if the return value from a
-	/// [`bindgen::Builder`] configuration method is not received nor returned
-	/// from the hook:
-	/// ```compile_fail
-	/// use bindgen::Builder;
-	/// use kusabira::builder::Config;
-	///
-	/// let rustified_enum = String::from("rust_style_enum");
-	/// let _config = Config::default()
-	/// 	.bindgen_builder_hook(
-	/// 		|builder: Builder|
-	/// 		{
-	/// 			builder.rustified_enum(&rustified_enum);
-	/// 			// builder is lost!
-	/// 			builder
-	/// 		});
+	/// let config = Config::default()
+	/// 	.lib_name("foo")
+	/// 	.input_file("src/*.[ch]")
+	/// 	.add_config_header(ConfigHeader::new("foo_config.h")
+	/// 		.target_properties()
+	/// 		.set_bool("FOO_ENABLE_THREADS", true));
 	/// ```
 	///
-	pub fn bindgen_builder_hook<BindgenBuildHook>(
-		mut self,
-		bindgen_builder_hook: BindgenBuildHook)
-		-> Self
-		where BindgenBuildHook: FnMut(Builder) -> Builder + 'a
+	pub fn add_config_header(mut self, config_header: ConfigHeader) -> Self
 	{
-		self.bindgen_builder_hook = RefCell::new(Box::new(bindgen_builder_hook));
+		self.config_headers.push(config_header);
 		self
 	}
 
 	///
-	/// Add a new hook to configure [`bindgen::Builder`].
+	/// Add a feature probe, run before the configuration headers are
+	/// generated.
 	///
-	/// The configured hook is called for each configured C header file during
-	/// the execution of [`Config::build`].
+	/// The probes are compiled by the compiler and flags of the
+	/// [`cc::Build`] for C of the default library, configured by
+	/// [`Config::cc_build_hook`].  The results are emitted to
+	/// [`Cargo`](https://doc.rust-lang.org/cargo/) so that the Rust code can
+	/// check them by `cfg`, and can be set to the configuration headers by
+	/// [`ConfigHeader::probes`].
 	///
-	/// Refer to [the Hook Addition section](super::builder#hook-addition) for
-	/// the detail of the hook generated by this method.
+	/// The results are cached in the output directory until the compiler
+	/// command changes.
 	///
-	/// The parameter type of the hook is aligned to the configuration methods
-	/// of [`bindgen::Builder`], which receives and returns `bindgen::Builder`.
+	/// Refer to [`Probe`] for the kinds of the probes and the names of the
+	/// results.
 	///
-	/// Refer to the example on [`Config::bindgen_builder_hook`] for the
-	/// limitation due to the hook being bound to [`FnMut`].
+	/// # Example
+	/// ```
+	/// use kusabira::builder::{Config, ConfigHeader, Probe};
+	///
+	/// let config = Config::default()
+	/// 	.lib_name("foo")
+	/// 	.input_file("src/*.c")
+	/// 	.add_probe(Probe::header("sys/epoll.h"))
+	/// 	.add_probe(Probe::size_of("long double"))
+	/// 	.add_config_header(ConfigHeader::new("config.h")
+	/// 		.probes());
+	/// ```
+	///
+	/// Then in the Rust code:
+	/// ```
+	/// #[cfg(have_sys_epoll_h)]
+	/// mod epoll {}
+	/// ```
+	///
+	pub fn add_probe(mut self, probe: Probe) -> Self
+	{
+		self.probes.push(probe);
+		self
+	}
+
+	///
+	/// Add a closure generating a source or header file, run before the input
+	/// files are expanded.
+	///
+	/// The closure is called with the output directory, and returns the path
+	/// of the generated file, relative to the output directory or absolute.
+	/// The generated file is fed into the input files of the default library
+	/// in the same way as the output files of [`Config::add_host_tool`]; a
+	/// generated source file is compiled and a generated header file is
+	/// bound, as classified by the extension.
+	///
+	/// The closures and the host tools are run in the order added.  An error
+	/// returned by the closure is converted into [`MldError`] and returned
+	/// by [`Config::build`] as it is.  The name identifies the closure in the
+	/// diagnostics.
+	///
+	/// # Example
+	/// ```
+	/// use std::fs::write;
+	/// use std::path::Path;
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.lib_name("foo")
+	/// 	.input_file("src/*.c")
+	/// 	.generate_source("version", |out_dir: &Path|
+	/// 	{
+	/// 		let version_c = out_dir.join("version.c");
+	/// 		write(&version_c, "const char foo_version[] = \"1.0.0\";\n")?;
+	/// 		Ok::<_, std::io::Error>(version_c)
+	/// 	});
+	/// ```
+	///
+	pub fn generate_source<GenerateSource, E>(
+		mut self,
+		name: &str,
+		generate_source: GenerateSource)
+		-> Self
+		where GenerateSource: FnOnce(&Path) -> Result<StdPathBuf, E> + 'a,
+			E: Into<MldError>
+	{
+		self.pre_build_steps.push(PreBuildStep::GenerateSource(
+			name.to_string(),
+			Box::new(move |out_dir: &Path| {generate_source(out_dir).map_err(Into::into)})));
+		self
+	}
+
+	///
+	/// Set the [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding
+	/// file extension.
+	///
+	/// As of Oct 2023, there is no common extention for the Rust include file.
+	/// The example of [std::include] uses `in`, which is also the default,
+	/// while `rs` also makes a sense because an `include!`d file is a valid
+	/// Rust source file.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let binding_ext = "rs";
+	/// let config = Config::default()
+	/// 	.binding_ext(binding_ext);
+	/// ```
+	///
+	pub fn binding_ext(mut self, binding_ext: &'a str) -> Self
+	{
+		self.binding_ext = binding_ext.to_string();
+		self
+	}
+
+	///
+	/// Bind all of the header files together into a single binding file,
+	/// instead of one binding file per header file.
+	///
+	/// [`Config::build`] generates the wrapper header `name.h` in the output
+	/// directory, which `#include`s all of the header files in the configured
+	/// order, and passes it to a single [`bindgen::Builder::generate`].  The
+	/// binding file `name` with the extension configured by
+	/// [`Config::binding_ext`] has no duplicated definitions of the types
+	/// shared among the header files, so that it can be
+	/// [`include!`](std::include)d into a single module.
+	///
+	/// If any of the header files is C++, the wrapper header is bound as C++.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.input_file("include/foo/*.h")
+	/// 	.combined_binding("foo_bindings");
+	/// ```
+	///
+	pub fn combined_binding(mut self, name: &str) -> Self
+	{
+		self.combined_binding = Some(name.to_string());
+		self
+	}
+
+	///
+	/// Set the directory of the pre-generated binding files committed to the
+	/// source tree, resolved against the source root.
+	///
+	/// The binding files are selected by the target triple, eg
+	/// `bindings/x86_64-unknown-linux-gnu/hello_world_export_to_rust.in` for
+	/// the directory `bindings`.  Each of them has the same filename as the
+	/// binding file generated by [`Config::build`], into which it is copied.
+	///
+	/// How the vendored binding files are used is configured by
+	/// [`Config::vendored_bindings_mode`].  By default,
+	/// [`bindgen::Builder::generate`] still runs as long as `libclang` is
+	/// loaded, and the vendored binding files are used instead only if
+	/// `libclang` cannot be loaded.  [`HeaderBinding::vendored_binding_file`]
+	/// tells which binding file is used.
+	///
+	/// # Example
+	/// ```
+	/// use std::path::Path;
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/hello_world_export_to_rust.h")
+	/// 	.vendored_bindings(Path::new("bindings"));
+	/// ```
+	///
+	pub fn vendored_bindings(mut self, vendored_bindings_dir: &Path) -> Self
+	{
+		self.vendored_bindings_dir = Some(vendored_bindings_dir.to_path_buf());
+		self
+	}
+
+	///
+	/// Set how to use the vendored binding files configured by
+	/// [`Config::vendored_bindings`], [`VendoredBindingsMode::Fallback`] by
+	/// default.
+	///
+	/// The environment variable [`ENV_KEY_VENDORED_BINDINGS`] overrides the
+	/// mode, so that a machine without `libclang` can build, and the
+	/// continuous integration can verify the vendored binding files, without
+	/// modifying the build script.  In [`VendoredBindingsMode::Verify`], the
+	/// binding files are generated into the output directory in place of the
+	/// ones used by the build, not into a temporary location, and compared
+	/// with the vendored ones.  The build fails with the difference if any of
+	/// the vendored binding files is stale against the header files, and
+	/// fails if any of them is missing; run the build once in
+	/// [`VendoredBindingsMode::Update`] to create or rewrite them.
+	///
+	/// # Example
+	/// ```
+	/// use std::path::Path;
+	/// use kusabira::builder::{Config, VendoredBindingsMode};
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/hello_world_export_to_rust.h")
+	/// 	.vendored_bindings(Path::new("bindings"))
+	/// 	.vendored_bindings_mode(VendoredBindingsMode::Force);
+	/// ```
+	///
+	pub fn vendored_bindings_mode(mut self, vendored_bindings_mode: VendoredBindingsMode) -> Self
+	{
+		self.vendored_bindings_mode = vendored_bindings_mode;
+		self
+	}
+
+	///
+	/// Reuse the binding files generated by the previous builds if their
+	/// inputs are unchanged.
+	///
+	/// A binding file is cached by the hash of the `bindgen` and `libclang`
+	/// versions, the target, the effective command line flags of
+	/// [`bindgen::Builder`] after the hooks, and the contents of the header
+	/// file and its transitive includes, as well as the environment
+	/// variables read by `bindgen`.  On a hit, [`bindgen::Builder::generate`]
+	/// does not run, although `libclang` is still loaded to tell its version.
+	/// The `bindgen` version is the one resolved in `Cargo.lock`, or read out
+	/// of the bindings of an empty header if `Cargo.lock` is not found above
+	/// the output directory of `kusabira`.  The cache is bypassed if neither
+	/// tells the version.
+	/// [`HeaderBinding::bindgen_cache_hit`] and [`BuildResults`] report the
+	/// hits and the misses.
+	///
+	/// The cache directory is [`BINDGEN_CACHE_DIR`] in the output directory,
+	/// or the value of [`ENV_KEY_BINDGEN_CACHE_DIR`] if defined.
+	///
+	/// The [`bindgen::callbacks::ParseCallbacks`] added by the hooks are not
+	/// hashed.  Disable the cache if they change the bindings.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/hello_world_export_to_rust.h")
+	/// 	.bindgen_cache(true);
+	/// ```
+	///
+	pub fn bindgen_cache(mut self, bindgen_cache: bool) -> Self
+	{
+		self.bindgen_cache = bindgen_cache;
+		self
+	}
+
+	///
+	/// Generate the binding files on multiple threads while the cc sources
+	/// are compiled.
+	///
+	/// [`Config::build`] expands all of the input files first, and then
+	/// generates the binding files of the independent header files on the
+	/// worker threads, while the current thread compiles the libraries and
+	/// then joins the worker threads.  A worker thread is spawned for each
+	/// token available from the
+	/// [jobserver](https://doc.rust-lang.org/cargo/reference/build-scripts.html#jobserver)
+	/// of [`Cargo`](https://doc.rust-lang.org/cargo/), so that the build
+	/// script does not run more jobs than allowed.
+	///
+	/// [`bindgen::Builder`] is bound to the thread on which it is created, so
+	/// only the thread-safe hook configured by
+	/// [`Config::sync_bindgen_builder_hook`] is called.  [`Config::build`]
+	/// fails if [`Config::bindgen_builder_hook`] or
+	/// [`Config::header_bindgen_builder_hook`] is configured.
+	///
+	/// # Example
+	/// ```
+	/// use bindgen::Builder;
+	/// use kusabira::builder::{Config, HeaderBinding};
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/*.[ch]")
+	/// 	.parallel_bindgen(true)
+	/// 	.sync_bindgen_builder_hook(|builder: Builder, _header_binding: &HeaderBinding|
+	/// 	{
+	/// 		builder.derive_default(true)
+	/// 	});
+	/// ```
+	///
+	pub fn parallel_bindgen(mut self, parallel_bindgen: bool) -> Self
+	{
+		self.parallel_bindgen = parallel_bindgen;
+		self
+	}
+
+	///
+	/// Generate the tests checking the sizes, the alignments and the field
+	/// offsets of the bound structs, unions and enums against the C compiler.
+	///
+	/// For each binding file, [`Config::build`] generates a C program
+	/// printing the layouts of the types declared in the header file and the
+	/// ones it includes by `#include "..."`, and compiles it by the
+	/// [`cc::Build`] of the default library, or the one for C++ if the header
+	/// file is C++.  The Rust tests comparing the layouts of the bound types
+	/// with the printed ones are generated into [`ABI_LAYOUT_DIR`] in the
+	/// output directory, and declared in [`BINDINGS_MOD_FILENAME`] under
+	/// `#[cfg(test)]`, so that `cargo test` runs them if the bindings are
+	/// included by `include_bindings!()`.  The single binding file included
+	/// by `include_bindings!("header_name")` does not carry the tests; they
+	/// MAY be included from [`HeaderBinding::abi_layout_test_file`] by hand.
+	///
+	/// The opaque, the empty and the generic types, and the bitfields are not
+	/// checked.  A binding file not parsed as Rust, or a bound struct or
+	/// union without any fields, fails the build rather than skipping the
+	/// checks.  The program is compiled for the target, so the tests fail
+	/// to run the program if the target is not runnable on the host.
+	///
+	/// [`HeaderBinding::abi_layout_checker`] and
+	/// [`HeaderBinding::abi_layout_test_file`] report the generated files.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/hello_world_export_to_rust.h")
+	/// 	.abi_layout_tests(true);
+	/// ```
+	///
+	pub fn abi_layout_tests(mut self, abi_layout_tests: bool) -> Self
+	{
+		self.abi_layout_tests = abi_layout_tests;
+		self
+	}
+
+	///
+	/// Add a source file extention regarded as the input to [`cc::Build`].
+	///
+	/// The language of the extension follows [`SOURCE_EXTS`] if listed, or C
+	/// otherwise.  Use [`Config::add_source_ext_as`] to configure the language
+	/// explicitly.
+	///
+	/// If the extension is already added, it is not added again.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let source_ext = "ci";
+	/// let config = Config::default()
+	/// 	.add_source_ext(source_ext);
+	/// ```
+	///
+	pub fn add_source_ext(self, ext: &str) -> Self
+	{
+		if self.cc_exts.iter().any(|(x, _)| {x == ext}) {
+			return self;
+		}
+		let language = conventional_language(&SOURCE_EXTS, ext);
+		self.add_source_ext_as(ext, language)
+	}
+
+	///
+	/// Add a source file extention regarded as the input to [`cc::Build`] in
+	/// the given language.
+	///
+	/// If the extension is already added, its language is replaced.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::{Config, Language};
+	///
+	/// let config = Config::default()
+	/// 	.add_source_ext_as("ino", Language::Cxx);
+	/// ```
+	///
+	pub fn add_source_ext_as(mut self, ext: &str, language: Language) -> Self
+	{
+		match self.cc_exts.iter_mut().find(|(x, _)| {x == ext}) {
+			Some((_, ext_language)) => *ext_language = language,
+			None => self.cc_exts.push((ext.to_string(), language)),
+		}
+		self
+	}
+
+	///
+	/// Delete a source file extention regarded as the input to [`cc::Build`].
+	///
+	/// If the extension is not added, this method does nothing.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let source_ext = "cc";
+	/// let config = Config::default()
+	/// 	.delete_source_ext(source_ext);
+	/// ```
+	///
+	pub fn delete_source_ext(mut self, ext: &str) -> Self
+	{
+		if let Some(i) = self.cc_exts.iter().position(|(x, _)| {x == ext}) {
+			self.cc_exts.remove(i);
+		}
+		self
+	}
+
+	///
+	/// Add a header file extention regarded as the input to
+	/// [`bindgen::Builder::generate`].
+	///
+	/// The language of the extension follows [`HEADER_EXTS`] if listed, or C
+	/// otherwise.  Use [`Config::add_header_ext_as`] to configure the language
+	/// explicitly.
+	///
+	/// If the extension is already added, it is not added again.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let header_ext = "hhh";
+	/// let config = Config::default()
+	/// 	.add_header_ext(header_ext);
+	/// ```
+	///
+	pub fn add_header_ext(self, ext: &str) -> Self
+	{
+		if self.bindgen_exts.iter().any(|(x, _)| {x == ext}) {
+			return self;
+		}
+		let language = conventional_language(&HEADER_EXTS, ext);
+		self.add_header_ext_as(ext, language)
+	}
+
+	///
+	/// Add a header file extention regarded as the input to
+	/// [`bindgen::Builder::generate`] in the given language.
+	///
+	/// If the extension is already added, its language is replaced.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::{Config, Language};
+	///
+	/// let config = Config::default()
+	/// 	.add_header_ext_as("inl", Language::Cxx);
+	/// ```
+	///
+	pub fn add_header_ext_as(mut self, ext: &str, language: Language) -> Self
+	{
+		match self.bindgen_exts.iter_mut().find(|(x, _)| {x == ext}) {
+			Some((_, ext_language)) => *ext_language = language,
+			None => self.bindgen_exts.push((ext.to_string(), language)),
+		}
+		self
+	}
+
+	///
+	/// Delete a header file extention regarded as the input to
+	/// [`bindgen::Builder::generate`].
+	///
+	/// If the extension is not added, this method does nothing.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let header_ext = "hh";
+	/// let config = Config::default()
+	/// 	.delete_header_ext(header_ext);
+	/// ```
+	///
+	pub fn delete_header_ext(mut self, ext: &str) -> Self
+	{
+		if let Some(i) = self.bindgen_exts.iter().position(|(x, _)| {x == ext}) {
+			self.bindgen_exts.remove(i);
+		}
+		self
+	}
+
+	///
+	/// Override the file type of the input files matching a pattern, instead
+	/// of looking up the extension.
+	///
+	/// The pattern is a [`glob`] pattern, optionally with the brace sets,
+	/// resolved against the source root in the same way as the input files.
+	/// The override does not add any input files by itself.
+	///
+	/// If multiple overrides match an input file, the last configured one
+	/// wins.  [`FileType::Unsupported`] drops the matching input files.
+	///
+	/// A source file whose extension does not tell the compiler driver the
+	/// overridden language by [`SOURCE_EXTS`], eg a `*.cpp` file overridden
+	/// as C, is compiled separately with the explicit language flag, ie `-x`
+	/// for the GNU compatible compilers or `/TC` and `/TP` for MSVC.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::{Config, FileType, Language};
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/**/*.{c,h}")
+	/// 	.override_file_type("src/cxx_compat/*.c", FileType::Source(Language::Cxx))
+	/// 	.override_file_type("src/cxx_compat/*.h", FileType::Header(Language::Cxx));
+	/// ```
+	///
+	pub fn override_file_type(mut self, pattern: &str, file_type: FileType) -> Self
+	{
+		self.file_type_overrides.push((pattern.to_string(), file_type));
+		self
+	}
+
+	///
+	/// Add an include directory, resolved against the source root.
+	///
+	/// The include directory applies to all of the [`cc::Build`]s and
+	/// [`nasm_rs::Build`]s of the libraries, and to [`bindgen::Builder`] by
+	/// `-I`, before the hooks are called.  Hence, the compiled code and the
+	/// bindings see the same headers.
+	///
+	/// # Example
+	/// ```
+	/// use std::path::Path;
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/*.[ch]")
+	/// 	.include_dir(Path::new("include"))
+	/// 	.include_dir(Path::new("third_party/foo/include"));
+	/// ```
+	///
+	pub fn include_dir(mut self, include_dir: &Path) -> Self
+	{
+		self.include_dirs.push(include_dir.to_path_buf());
+		self
+	}
+
+	///
+	/// Add a macro definition, with the value or without it if [`None`].
+	///
+	/// The definition applies to all of the [`cc::Build`]s and
+	/// [`nasm_rs::Build`]s of the libraries, and to [`bindgen::Builder`] by
+	/// `-D`, before the hooks are called.  Hence, the compiled code and the
+	/// bindings see the same definitions.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/*.[ch]")
+	/// 	.define("HAVE_CONFIG_H", None)
+	/// 	.define("FOO_MAX_PLUGINS", Some("16"));
+	/// ```
+	///
+	pub fn define(mut self, name: &str, value: Option<&str>) -> Self
+	{
+		self.defines.push((name.to_string(), value.map(str::to_string)));
+		self
+	}
+
+	///
+	/// Set the C language standard, eg `c11`.
+	///
+	/// The standard applies to the [`cc::Build`]s for C by
+	/// [`cc::Build::std`], and to [`bindgen::Builder`] of the C headers by
+	/// `-std=`, before the hooks are called.  The C++ sources and headers are
+	/// configured by [`Config::cxx_std`] instead.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/*.[ch]")
+	/// 	.std("c11");
+	/// ```
+	///
+	pub fn std(mut self, std: &str) -> Self
+	{
+		self.std = Some(std.to_string());
+		self
+	}
+
+	///
+	/// Set the C++ language standard, eg `c++17`.
+	///
+	/// The standard applies to the [`cc::Build`]s for C++ by
+	/// [`cc::Build::std`], and to [`bindgen::Builder`] of the C++ headers by
+	/// `-std=`, before the hooks are called.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/*.{cc,hh}")
+	/// 	.std("c11")
+	/// 	.cxx_std("c++17");
+	/// ```
+	///
+	pub fn cxx_std(mut self, cxx_std: &str) -> Self
+	{
+		self.cxx_std = Some(cxx_std.to_string());
+		self
+	}
+
+	///
+	/// Configure whether to add the directories of the input files as the
+	/// include directories, enabled by default.
+	///
+	/// The directories of the source and header files of a library apply to
+	/// the [`cc::Build`]s of the library and to [`bindgen::Builder`] of its
+	/// headers, after the hooks are called, so that they are searched after
+	/// the explicitly configured include directories.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/*.[ch]")
+	/// 	.include_input_dirs(false);
+	/// ```
+	///
+	pub fn include_input_dirs(mut self, include_input_dirs: bool) -> Self
+	{
+		self.include_input_dirs = include_input_dirs;
+		self
+	}
+
+	///
+	/// Configure whether to add the include directories of the dependencies,
+	/// enabled by default.
+	///
+	/// The directories are taken from the `DEP_<LINKS>_INCLUDE` environment
+	/// variables, which `Cargo` sets for every dependency with the `links`
+	/// key and the `include` metadata.  Disable this if the headers of a
+	/// dependency conflict with the configured ones; the wanted directories
+	/// MAY still be added by [`Config::include_dir`].
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/*.[ch]")
+	/// 	.include_dep_dirs(false);
+	/// ```
+	///
+	pub fn include_dep_dirs(mut self, include_dep_dirs: bool) -> Self
+	{
+		self.include_dep_dirs = include_dep_dirs;
+		self
+	}
+
+	///
+	/// Configure whether to restrict the bindings to the items declared in
+	/// the configured header files, disabled by default.
+	///
+	/// When enabled, [`bindgen::Builder`] of each header file allows only
+	/// the files by [`bindgen::Builder::allowlist_file`], before the hooks are
+	/// called: the header file itself, or all of the header files bound
+	/// together by [`Config::combined_binding`], and the directories
+	/// configured by [`Config::restrict_bindings_dir`].  The types used by
+	/// the allowed items are still bound transitively, while the rest of the
+	/// included headers, eg the system headers, are not.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/hello_world_export_to_rust.h")
+	/// 	.restrict_bindings(true);
+	/// ```
+	///
+	pub fn restrict_bindings(mut self, restrict_bindings: bool) -> Self
+	{
+		self.restrict_bindings = restrict_bindings;
+		self
+	}
+
+	///
+	/// Restrict the bindings to the items declared in the configured header
+	/// files and the header files under a directory, resolved against the
+	/// source root.
+	///
+	/// This enables [`Config::restrict_bindings`], and accumulates the
+	/// directories.  Use this to bind the headers `#include`d by a configured
+	/// header file, such as the public headers of a vendored library.
+	///
+	/// The directories, the include directories and the input files are all
+	/// normalized lexically, so that the paths reported by libclang through
+	/// the include directories match.  A header `#include`d by a relative
+	/// path with `..` MAY still fall out of the directories.
+	///
+	/// # Example
+	/// ```
+	/// use std::path::Path;
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/foo_sys.h")
+	/// 	.restrict_bindings_dir(Path::new("vendor/foo/include"));
+	/// ```
+	///
+	pub fn restrict_bindings_dir(mut self, restrict_bindings_dir: &Path) -> Self
+	{
+		self.restrict_bindings = true;
+		self.restrict_bindings_dirs.push(restrict_bindings_dir.to_path_buf());
+		self
+	}
+
+	///
+	/// Add a system library found by `pkg-config` before the build.
+	///
+	/// The `version` requirement is a comma-separated list of the
+	/// constraints `>=V`, `>V`, `<=V`, `<V` and `=V`, eg `>=7.0, <8`.  A bare
+	/// `V` is the same as `>=V`, as in the `system-deps` metadata.  An empty
+	/// requirement or `*` accepts any version.
+	///
+	/// The include directories and the macro definitions of the library are
+	/// added in the same way as [`Config::include_dir`] and
+	/// [`Config::define`], so that both of the backends see them.  The
+	/// library is linked after all of the libraries built by
+	/// [`Config::build`], so that it resolves the symbols required by them.
+	/// As in `pkg-config`, the libraries are linked statically if
+	/// `<NAME>_STATIC` or `PKG_CONFIG_ALL_STATIC` is set and the static
+	/// libraries are found out of the system directories.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.lib_name("hello_world_c")
+	/// 	.input_file("src/*.[ch]")
+	/// 	.system_library("libcw", ">=7.0");
+	/// ```
+	///
+	pub fn system_library(mut self, name: &str, version: &str) -> Self
+	{
+		self.system_libraries.push((name.to_string(), version.to_string()));
+		self
+	}
+
+	///
+	/// Add the public headers to install into `include` in the output
+	/// directory, for the downstream crates that compile their own C against
+	/// the libraries.
+	///
+	/// The headers matching the `pattern`, resolved against the source root,
+	/// are copied into the `dest_dir` relative to `include`, eg `foo` for
+	/// `#include <foo/foo.h>`.  An empty `dest_dir` installs them into
+	/// `include` itself.  The installation follows the pre-build steps, so
+	/// that the generated headers can be installed as well.
+	///
+	/// When any header is installed, [`Config::build`] emits
+	/// `cargo:include` and `cargo:root` to
+	/// [`Cargo`](https://doc.rust-lang.org/cargo/), which the downstream
+	/// crates read as `DEP_<LINKS>_INCLUDE` and `DEP_<LINKS>_ROOT` if the
+	/// package has the
+	/// [`links`](https://doc.rust-lang.org/cargo/reference/build-scripts.html#the-links-manifest-key)
+	/// key.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.lib_name("foo")
+	/// 	.input_file("src/*.[ch]")
+	/// 	.install_header("include/foo/*.h", "foo");
+	/// ```
+	///
+	pub fn install_header(mut self, pattern: &str, dest_dir: &str) -> Self
+	{
+		self.installed_headers.push((pattern.to_string(), dest_dir.to_string()));
+		self
+	}
+
+	///
+	/// Add the metadata emitted as `cargo:KEY=VALUE` by [`Config::build`].
+	///
+	/// The dependent crates read the metadata as `DEP_<LINKS>_<KEY>` if the
+	/// package has the
+	/// [`links`](https://doc.rust-lang.org/cargo/reference/build-scripts.html#the-links-manifest-key)
+	/// key.  The key MUST NOT be empty, MUST NOT contain `=` and MUST NOT be
+	/// a [`Cargo`](https://doc.rust-lang.org/cargo/) instruction such as
+	/// `rustc-link-lib`.  Neither the key nor the value may contain a newline.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.lib_name("foo")
+	/// 	.input_file("src/*.[ch]")
+	/// 	.metadata("version", "1.2.3")
+	/// 	.metadata("static", "1");
+	/// ```
+	///
+	pub fn metadata(mut self, key: &str, value: &str) -> Self
+	{
+		self.metadata.push((key.to_string(), value.to_string()));
+		self
+	}
+
+	///
+	/// Set the hook to configure [`cc::Build`] of the C and assembly sources.
+	///
+	/// The configured hook is called only once during the execution of
+	/// [`Config::build`].  The C++ sources are configured by
+	/// [`Config::cxx_build_hook`] instead.
+	///
+	/// The configured hook replaces the old one.
+	///
+	/// The parameter type of the hook is aligned to the configuration methods
+	/// of [`cc::Build`], which receives and returns `&mut cc::Build`.
+	///
+	/// # Example
+	/// ```
+	/// use cc::Build;
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.cc_build_hook(|build: &mut Build| {build});
+	/// ```
+	///
+	/// The [`FnOnce`] trait is sufficient on the hook, so it MAY consume any
+	/// external data.
+	/// ```
+	/// use cc::Build;
+	/// use kusabira::builder::Config;
+	///
+	/// let consumed = String::from("c11");
+	/// let mut consumer = vec![];
+	/// let _config = Config::default()
+	/// 	.cc_build_hook(
+	/// 		|build: &mut Build|
+	/// 		{
+	/// 			build.std(&consumed);
+	/// 			consumer.push(consumed);
+	/// 			build
+	/// 		});
+	/// ```
+	///
+	pub fn cc_build_hook<CcBuildHook>(
+		mut self,
+		cc_build_hook: CcBuildHook)
+		-> Self
+		where CcBuildHook: FnOnce(&mut Build) -> &mut Build + 'a
+	{
+		self.cc_build_hook = RefCell::new(Box::new(cc_build_hook));
+		self
+	}
+
+	///
+	/// Add a new hook to configure [`cc::Build`] of the C and assembly
+	/// sources.
+	///
+	/// The configured hook is called only once during the execution of
+	/// [`Config::build`].
+	///
+	/// Refer to [the Hook Addition section](super::builder#hook-addition) for
+	/// the detail of the hook generated by this method.
+	///
+	/// The parameter type of the hook is aligned to the configuration methods
+	/// of [`cc::Build`], which receives and returns `&mut cc::Build`.
+	///
+	/// # Example
+	/// ```
+	/// use cc::Build;
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.add_cc_build_hook(|build: &mut Build| {build});
+	/// ```
+	///
+	/// Refer to the example on [`Config::cc_build_hook`] for the usage of a
+	/// hook with only the [`FnOnce`] trait.
+	///
+	pub fn add_cc_build_hook<CcBuildHook>(
+		mut self,
+		cc_build_hook: CcBuildHook)
+		-> Self
+		where CcBuildHook: FnOnce(&mut Build) -> &mut Build + 'a
+	{
+		let cc_build_hook_fn = (self.cc_build_hook)
+			.replace(Box::new(reflect_cc));
+		self.cc_build_hook = RefCell::new(Box::new(move |build: &mut Build| {
+			cc_build_hook(cc_build_hook_fn(build))
+		}));
+		self
+	}
+
+	///
+	/// Set the hook to configure [`cc::Build`] of the C++ sources.
+	///
+	/// The C++ sources are the ones in the C++ [`Language`]s, ie
+	/// [`Language::is_cxx`].  They are compiled by [`cc::Build`] separated from
+	/// the C and assembly sources and configured by [`cc::Build::cpp`], so
+	/// that they are compiled with the C++ flags and the C++ standard library
+	/// is linked.  The configured hook is called after [`cc::Build::cpp`].
+	///
+	/// If a library has both of the C and C++ sources, the C sources are
+	/// compiled into the objects by [`cc::Build::try_compile_intermediates`],
+	/// which are then archived into the library together with the C++
+	/// sources.
+	///
+	/// The configured hook is called only once during the execution of
+	/// [`Config::build`].
+	///
+	/// The configured hook replaces the old one.
+	///
+	/// # Example
+	/// ```
+	/// use cc::Build;
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.cc_build_hook(|build: &mut Build| {build.std("c11")})
+	/// 	.cxx_build_hook(|build: &mut Build| {build.std("c++17")});
+	/// ```
+	///
+	pub fn cxx_build_hook<CcBuildHook>(
+		mut self,
+		cxx_build_hook: CcBuildHook)
+		-> Self
+		where CcBuildHook: FnOnce(&mut Build) -> &mut Build + 'a
+	{
+		self.cxx_build_hook = RefCell::new(Box::new(cxx_build_hook));
+		self
+	}
+
+	///
+	/// Add a new hook to configure [`cc::Build`] of the C++ sources.
+	///
+	/// The configured hook is called only once during the execution of
+	/// [`Config::build`].
+	///
+	/// Refer to [the Hook Addition section](super::builder#hook-addition) for
+	/// the detail of the hook generated by this method.
+	///
+	/// # Example
+	/// ```
+	/// use cc::Build;
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.cxx_build_hook(|build: &mut Build| {build.std("c++17")})
+	/// 	.add_cxx_build_hook(|build: &mut Build| {build.flag_if_supported("-fno-rtti")});
+	/// ```
+	///
+	pub fn add_cxx_build_hook<CcBuildHook>(
+		mut self,
+		cxx_build_hook: CcBuildHook)
+		-> Self
+		where CcBuildHook: FnOnce(&mut Build) -> &mut Build + 'a
+	{
+		let cxx_build_hook_fn = (self.cxx_build_hook)
+			.replace(Box::new(reflect_cc));
+		self.cxx_build_hook = RefCell::new(Box::new(move |build: &mut Build| {
+			cxx_build_hook(cxx_build_hook_fn(build))
+		}));
+		self
+	}
+
+	///
+	/// Set the hook to configure [`nasm_rs::Build`] of the NASM sources.
+	///
+	/// The NASM sources are the ones in [`Language::Nasm`], eg `*.asm`.  They
+	/// are assembled into the objects by [`nasm_rs::Build::compile_objects`],
+	/// which are then archived into the library together with the [`cc`]
+	/// outputs.
+	///
+	/// Before the configured hook is called, [`nasm_rs::Build`] is configured
+	/// with the object format by [`Config::nasm_object_format`] if any, and
+	/// the directory of each NASM source as an include path, so that
+	/// `%include` works as in the C preprocessor.
+	///
+	/// [`nasm_rs`] runs NASM in the package directory, so [`Config::build`]
+	/// fails if [`ENV_KEY_CARGO_MANIFEST_DIR`] is not defined, eg outside
+	/// [`Cargo`](https://doc.rust-lang.org/cargo/).
+	///
+	/// The configured hook is called only once during the execution of
+	/// [`Config::build`].
+	///
+	/// The configured hook replaces the old one.
+	///
+	/// # Example
+	/// ```
+	/// use nasm_rs::Build;
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/x86/*.asm")
+	/// 	.nasm_build_hook(|build: &mut Build| {build.include("src/x86/include")});
+	/// ```
+	///
+	pub fn nasm_build_hook<NasmBuildHook>(
+		mut self,
+		nasm_build_hook: NasmBuildHook)
+		-> Self
+		where NasmBuildHook: FnOnce(&mut NasmBuild) -> &mut NasmBuild + 'a
+	{
+		self.nasm_build_hook = RefCell::new(Box::new(nasm_build_hook));
+		self
+	}
+
+	///
+	/// Add a new hook to configure [`nasm_rs::Build`] of the NASM sources.
+	///
+	/// The configured hook is called only once during the execution of
+	/// [`Config::build`].
+	///
+	/// Refer to [the Hook Addition section](super::builder#hook-addition) for
+	/// the detail of the hook generated by this method.
+	///
+	/// # Example
+	/// ```
+	/// use nasm_rs::Build;
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.nasm_build_hook(|build: &mut Build| {build.include("src/x86/include")})
+	/// 	.add_nasm_build_hook(|build: &mut Build| {build.define("PIC", None)});
+	/// ```
+	///
+	pub fn add_nasm_build_hook<NasmBuildHook>(
+		mut self,
+		nasm_build_hook: NasmBuildHook)
+		-> Self
+		where NasmBuildHook: FnOnce(&mut NasmBuild) -> &mut NasmBuild + 'a
+	{
+		let nasm_build_hook_fn = (self.nasm_build_hook)
+			.replace(Box::new(reflect_nasm));
+		self.nasm_build_hook = RefCell::new(Box::new(move |build: &mut NasmBuild| {
+			nasm_build_hook(nasm_build_hook_fn(build))
+		}));
+		self
+	}
+
+	///
+	/// Set the object format of the NASM sources, ie the `-f` option of NASM.
+	///
+	/// By default, [`nasm_rs`] selects the object format for the target, eg
+	/// `elf64` for `x86_64-unknown-linux-gnu` and `win64` for
+	/// `x86_64-pc-windows-msvc`.  Configure the object format explicitly for
+	/// a target not covered by [`nasm_rs`], possibly depending on the target
+	/// given by [`Cargo`](https://doc.rust-lang.org/cargo/).
+	///
+	/// # Example
+	/// ```
+	/// use std::env;
+	/// use kusabira::builder::Config;
+	///
+	/// let mut config = Config::default()
+	/// 	.input_file("src/x86/*.asm");
+	/// if env::var("CARGO_CFG_TARGET_OS").is_ok_and(|os| {os == "none"}) {
+	/// 	config = config.nasm_object_format("elf64");
+	/// }
+	/// ```
+	///
+	pub fn nasm_object_format(mut self, nasm_object_format: &str) -> Self
+	{
+		self.nasm_object_format = Some(nasm_object_format.to_string());
+		self
+	}
+
+	///
+	/// Set the hook to configure [`bindgen::Builder`].
+	///
+	/// The configured hook is called for each configured C header file during
+	/// the execution of [`Config::build`].
+	///
+	/// The configured hook replaces the old one.
+	///
+	/// The parameter type of the hook is aligned to the configuration methods
+	/// of [`bindgen::Builder`], which receives and returns `bindgen::Builder`.
+	///
+	/// # Example
+	/// ```
+	/// use bindgen::Builder;
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.bindgen_builder_hook(|builder: Builder| {builder});
+	/// ```
+	///
+	/// Mind that the configuration methods of [`bindgen::Builder`] tend to
+	/// consume `self`, which is returned as a separate value, as well as
+	/// the parameters.  Implement your hooks so that it does not miss the
+	/// [`FnMut`] trait and do not forget to receive the returned
+	/// [`bindgen::Builder`] data.
+	///
+	/// A hook below compiles and works because it does not consume
+	/// `rustified_enum`, so the hook has the [`FnMut`] trait:
+	/// ```
+	/// use bindgen::Builder;
+	/// use kusabira::builder::Config;
+	///
+	/// let rustified_enum = String::from("rust_style_enum");
+	/// let _config = Config::default()
+	/// 	.bindgen_builder_hook(
+	/// 		|builder: Builder|
+	/// 		{
+	/// 			let builder = builder.rustified_enum(&rustified_enum);
+	/// 			builder
+	/// 		});
+	/// ```
+	///
+	/// The following hook consumes `rustified_enum`, so it does not have the
+	/// [`FnMut`] trait and causes a compilation error:
+	/// ```compile_fail
+	/// use bindgen::Builder;
+	/// use kusabira::builder::Config;
+	///
+	/// let rustified_enum = String::from("rust_style_enum");
+	/// let _config = Config::default()
+	/// 	.bindgen_builder_hook(
+	/// 		|builder: Builder|
+	/// 		{
+	/// 			let builder = builder.rustified_enum(rustified_enum);
+	/// 			// rustified_enum is lost!
+	/// 			builder
+	/// 		});
+	/// ```
+	///
+	/// A hook also fails to compile if the return value from a
+	/// [`bindgen::Builder`] configuration method is not received nor returned
+	/// from the hook:
+	/// ```compile_fail
+	/// use bindgen::Builder;
+	/// use kusabira::builder::Config;
+	///
+	/// let rustified_enum = String::from("rust_style_enum");
+	/// let _config = Config::default()
+	/// 	.bindgen_builder_hook(
+	/// 		|builder: Builder|
+	/// 		{
+	/// 			builder.rustified_enum(&rustified_enum);
+	/// 			// builder is lost!
+	/// 			builder
+	/// 		});
+	/// ```
+	///
+	pub fn bindgen_builder_hook<BindgenBuildHook>(
+		mut self,
+		bindgen_builder_hook: BindgenBuildHook)
+		-> Self
+		where BindgenBuildHook: FnMut(Builder) -> Builder + 'a
+	{
+		self.bindgen_builder_hook = RefCell::new(Box::new(bindgen_builder_hook));
+		self.unsync_bindgen_builder_hooks = true;
+		self
+	}
+
+	///
+	/// Add a new hook to configure [`bindgen::Builder`].
+	///
+	/// The configured hook is called for each configured C header file during
+	/// the execution of [`Config::build`].
+	///
+	/// Refer to [the Hook Addition section](super::builder#hook-addition) for
+	/// the detail of the hook generated by this method.
+	///
+	/// The parameter type of the hook is aligned to the configuration methods
+	/// of [`bindgen::Builder`], which receives and returns `bindgen::Builder`.
+	///
+	/// Refer to the example on [`Config::bindgen_builder_hook`] for the
+	/// limitation due to the hook being bound to [`FnMut`].
+	///
+	/// # Example
+	/// ```
+	/// use bindgen::Builder;
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::default()
+	/// 	.add_bindgen_builder_hook(|builder: Builder| {builder});
+	/// ```
+	///
+	pub fn add_bindgen_builder_hook<BindgenBuildHook>(
+		mut self,
+		bindgen_builder_hook: BindgenBuildHook)
+		-> Self
+		where BindgenBuildHook: FnMut(Builder) -> Builder + 'a
+	{
+		let mut bindgen_builder_hook_ = bindgen_builder_hook;
+		let mut bindgen_builder_hook_fn = self.bindgen_builder_hook
+			.replace(Box::new(reflect_bindgen));
+		self.bindgen_builder_hook = RefCell::new(Box::new(move |builder: Builder| {
+			bindgen_builder_hook_(bindgen_builder_hook_fn(builder))
+		}));
+		self.unsync_bindgen_builder_hooks = true;
+		self
+	}
+
+	///
+	/// Set the hook to configure [`bindgen::Builder`] for each header file,
+	/// knowing which header file it configures.
+	///
+	/// The configured hook is called for each configured C header file during
+	/// the execution of [`Config::build`], after the hook configured by
+	/// [`Config::bindgen_builder_hook`].  It receives the
+	/// [`HeaderBinding`] of the header file and the binding file to
+	/// generate, so that the options such as the allowlists and the enum
+	/// styles apply only to the intended header file.  For
+	/// [`Config::combined_binding`], the header file is the generated
+	/// wrapper header.
+	///
+	/// The configured hook replaces the old one.
+	///
+	/// Refer to the example on [`Config::bindgen_builder_hook`] for the
+	/// limitation due to the hook being bound to [`FnMut`].
+	///
+	/// # Example
+	/// ```
+	/// use bindgen::Builder;
+	/// use kusabira::builder::{Config, HeaderBinding};
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/*.h")
+	/// 	.header_bindgen_builder_hook(|builder: Builder, header_binding: &HeaderBinding|
+	/// 	{
+	/// 		if header_binding.input_header_file.ends_with("unixcw_libcw_demo.h") {
+	/// 			builder.newtype_enum("cw_return_values")
+	/// 		} else {
+	/// 			builder
+	/// 		}
+	/// 	});
+	/// ```
+	///
+	pub fn header_bindgen_builder_hook<HeaderBindgenBuildHook>(
+		mut self,
+		header_bindgen_builder_hook: HeaderBindgenBuildHook)
+		-> Self
+		where HeaderBindgenBuildHook: FnMut(Builder, &HeaderBinding) -> Builder + 'a
+	{
+		self.header_bindgen_builder_hook = RefCell::new(Box::new(header_bindgen_builder_hook));
+		self.unsync_bindgen_builder_hooks = true;
+		self
+	}
+
+	///
+	/// Add a new hook to configure [`bindgen::Builder`] for each header file,
+	/// knowing which header file it configures.
+	///
+	/// Refer to [`Config::header_bindgen_builder_hook`] for the hook, and to
+	/// [the Hook Addition section](super::builder#hook-addition) for the
+	/// detail of the hook generated by this method.
+	///
+	/// # Example
+	/// ```
+	/// use bindgen::Builder;
+	/// use kusabira::builder::{Config, HeaderBinding};
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/*.h")
+	/// 	.add_header_bindgen_builder_hook(|builder: Builder, header_binding: &HeaderBinding|
+	/// 	{
+	/// 		let header_dir = header_binding.input_header_file
+	/// 			.parent()
+	/// 			.expect("header file MUST have a parent directory");
+	/// 		builder.allowlist_file(format!("{}/.*", header_dir.display()))
+	/// 	})
+	/// 	.add_header_bindgen_builder_hook(|builder: Builder, header_binding: &HeaderBinding|
+	/// 	{
+	/// 		builder.derive_default(header_binding.input_header_file.ends_with("foo_types.h"))
+	/// 	});
+	/// ```
+	///
+	pub fn add_header_bindgen_builder_hook<HeaderBindgenBuildHook>(
+		mut self,
+		header_bindgen_builder_hook: HeaderBindgenBuildHook)
+		-> Self
+		where HeaderBindgenBuildHook: FnMut(Builder, &HeaderBinding) -> Builder + 'a
+	{
+		let mut header_bindgen_builder_hook_ = header_bindgen_builder_hook;
+		let mut header_bindgen_builder_hook_fn = self.header_bindgen_builder_hook
+			.replace(Box::new(reflect_header_bindgen));
+		self.header_bindgen_builder_hook = RefCell::new(Box::new(
+			move |builder: Builder, header_binding: &HeaderBinding|
+			{
+				header_bindgen_builder_hook_(
+					header_bindgen_builder_hook_fn(builder, header_binding),
+					header_binding)
+			}));
+		self.unsync_bindgen_builder_hooks = true;
+		self
+	}
+
+	///
+	/// Set the thread-safe hook to configure [`bindgen::Builder`] for each
+	/// header file.
+	///
+	/// The configured hook is called for each configured C header file during
+	/// the execution of [`Config::build`], after the hook configured by
+	/// [`Config::header_bindgen_builder_hook`], in the same way as that hook.
+	/// It is bound to [`Fn`], [`Send`] and [`Sync`] instead of [`FnMut`], so
+	/// that it is also called on the worker threads by
+	/// [`Config::parallel_bindgen`].  The shared states MUST be synchronized,
+	/// eg by [`std::sync::Mutex`].
+	///
+	/// The configured hook replaces the old one.
 	///
 	/// # Example
 	/// ```
 	/// use bindgen::Builder;
-	/// use kusabira::builder::Config;
+	/// use kusabira::builder::{Config, HeaderBinding};
 	///
+	/// let rustified_enum = String::from("rust_style_enum");
 	/// let config = Config::default()
-	/// 	.add_bindgen_builder_hook(|builder: Builder| {builder});
+	/// 	.input_file("src/*.h")
+	/// 	.sync_bindgen_builder_hook(move |builder: Builder, _header_binding: &HeaderBinding|
+	/// 	{
+	/// 		builder.rustified_enum(&rustified_enum)
+	/// 	});
 	/// ```
 	///
-	pub fn add_bindgen_builder_hook<BindgenBuildHook>(
+	pub fn sync_bindgen_builder_hook<SyncBindgenBuildHook>(
 		mut self,
-		bindgen_builder_hook: BindgenBuildHook)
+		sync_bindgen_builder_hook: SyncBindgenBuildHook)
 		-> Self
-		where BindgenBuildHook: FnMut(Builder) -> Builder + 'a
+		where SyncBindgenBuildHook: Fn(Builder, &HeaderBinding) -> Builder + Send + Sync + 'a
 	{
-		let mut bindgen_builder_hook_ = bindgen_builder_hook;
-		let mut bindgen_builder_hook_fn = self.bindgen_builder_hook
-			.replace(Box::new(reflect_bindgen));
-		self.bindgen_builder_hook = RefCell::new(Box::new(move |builder: Builder| {
-			bindgen_builder_hook_(bindgen_builder_hook_fn(builder))
-		}));
+		self.sync_bindgen_builder_hook = Box::new(sync_bindgen_builder_hook);
+		self
+	}
+
+	///
+	/// Add a new thread-safe hook to configure [`bindgen::Builder`] for each
+	/// header file.
+	///
+	/// Refer to [`Config::sync_bindgen_builder_hook`] for the hook, and to
+	/// [the Hook Addition section](super::builder#hook-addition) for the
+	/// detail of the hook generated by this method.
+	///
+	/// # Example
+	/// ```
+	/// use bindgen::Builder;
+	/// use kusabira::builder::{Config, HeaderBinding};
+	///
+	/// let config = Config::default()
+	/// 	.input_file("src/*.h")
+	/// 	.parallel_bindgen(true)
+	/// 	.add_sync_bindgen_builder_hook(|builder: Builder, _header_binding: &HeaderBinding|
+	/// 	{
+	/// 		builder.derive_debug(true)
+	/// 	})
+	/// 	.add_sync_bindgen_builder_hook(|builder: Builder, header_binding: &HeaderBinding|
+	/// 	{
+	/// 		builder.derive_default(header_binding.input_header_file.ends_with("foo_types.h"))
+	/// 	});
+	/// ```
+	///
+	pub fn add_sync_bindgen_builder_hook<SyncBindgenBuildHook>(
+		mut self,
+		sync_bindgen_builder_hook: SyncBindgenBuildHook)
+		-> Self
+		where SyncBindgenBuildHook: Fn(Builder, &HeaderBinding) -> Builder + Send + Sync + 'a
+	{
+		let sync_bindgen_builder_hook_fn = replace(
+			&mut self.sync_bindgen_builder_hook,
+			Box::new(reflect_header_bindgen));
+		self.sync_bindgen_builder_hook = Box::new(
+			move |builder: Builder, header_binding: &HeaderBinding|
+			{
+				sync_bindgen_builder_hook(
+					sync_bindgen_builder_hook_fn(builder, header_binding),
+					header_binding)
+			});
 		self
 	}
 
@@ -886,17 +2560,19 @@ impl<'a> Config<'a>
 	///
 	/// `self` is consumed.
 	///
-	/// If you have to integrate any system libraries, do so after building the
-	/// internal library by [`Config::build`]; the `-l` flags are added to the
-	/// linker in the order reported to
-	/// [`Cargo`](https://doc.rust-lang.org/cargo/), which means that the
-	/// system libraries MUST be configured after the internal ones.
-	///
 	/// # Covered `Cargo` Metadata
 	/// * The linkage to the generated library. ([`cc`])
+	/// * The linkage to the system libraries, after the generated libraries.
+	///   ([`kusabira`](crate))
 	/// * The dependency on the external C header files. ([`bindgen`])
 	/// * The dependency on the configured C source and header files.
 	///   ([`kusabira`](crate))
+	/// * The `cfg` options of the probe results. ([`kusabira`](crate))
+	/// * The `include` and `root` of the installed headers, and the metadata
+	///   configured by [`Config::metadata`]. ([`kusabira`](crate))
+	///
+	/// The files generated by [`Config::add_host_tool`] and
+	/// [`Config::generate_source`] are not reported as the dependencies.
 	///
 	/// # Panics
 	/// * The unwrap of [`Result<T, E>`] fails because of a logical error.
@@ -904,6 +2580,15 @@ impl<'a> Config<'a>
 	/// # Errors
 	/// * `self` is misconfigured.
 	/// * Any of the backends ([`cc`], [`bindgen`] and [`glob`]) fails.
+	/// * The compiler to run the probes is not found.
+	/// * Any of the system libraries is not found by `pkg-config`.
+	/// * Any of the header patterns of [`Config::install_header`] matches no
+	///   files.
+	/// * Any of the vendored binding files configured by
+	///   [`Config::vendored_bindings`] is missing when forced, or differs from
+	///   the generated one when verified.
+	/// * Any of the host tools or the closures configured by
+	///   [`Config::generate_source`] fails.
 	///
 	/// # Example
 	/// Below is the build script excerpt of
@@ -919,121 +2604,1091 @@ impl<'a> Config<'a>
 	/// ```no_run
 	/// use kusabira::builder::Config as MldBuilderConfig;
 	/// use kusabira::hooks::cc::warnings_into_errors;
-	/// use system_deps::Config as SystemDepsConfig;
 	///
-	///	MldBuilderConfig::default()
-	/// 	.lib_name("hello_world_c")
-	/// 	.input_file("src/hello_world_export_to_rust.h")
-	/// 	.add_input_files([
-	/// 		"src/hello_world_c_*.c",
-	/// 		"src/unixcw_libcw_demo.c"]
-	/// 	.into_iter())
-	/// 	.cc_build_hook(warnings_into_errors)
-	/// 	.add_cc_build_hook(|build| {build.std("c11")})
-	/// 	.bindgen_builder_hook(|builder| {builder.generate_block(true)})
-	/// 	.add_bindgen_builder_hook(|builder| {builder.generate_comments(true)})
-	/// 	.add_bindgen_builder_hook(|builder| {builder.newtype_enum("cw_return_values")})
-	/// 	.build()
-	/// 	.expect("the internal library build MUST succeed");
+	///	MldBuilderConfig::default()
+	/// 	.lib_name("hello_world_c")
+	/// 	.input_file("src/hello_world_export_to_rust.h")
+	/// 	.add_input_files([
+	/// 		"src/hello_world_c_*.c",
+	/// 		"src/unixcw_libcw_demo.c"]
+	/// 	.into_iter())
+	/// 	.system_library("libcw", ">=7.0")
+	/// 	.std("c11")
+	/// 	.cc_build_hook(warnings_into_errors)
+	/// 	.bindgen_builder_hook(|builder| {builder.generate_block(true)})
+	/// 	.add_bindgen_builder_hook(|builder| {builder.generate_comments(true)})
+	/// 	.add_bindgen_builder_hook(|builder| {builder.newtype_enum("cw_return_values")})
+	/// 	.build()
+	/// 	.expect("the library build MUST succeed");
+	/// ```
+	///
+	/// The system library `libcw` is found before the build, so that its
+	/// include directories reach both of the backends, and linked after the
+	/// internal library, so that it resolves the symbols required by the
+	/// internal library.
+	///
+	// XXX the link to `himetake` is not via the Rust name in order to avoid
+	// the cyclic package dependency.
+	pub fn build(mut self)
+		-> Result<BuildResults, MldError>
+	{
+		let mut results = BuildResults::new();
+
+		if !self.out_dir.is_dir() {
+			return Err(
+				MldError::from(
+					format!("output directory {} MUST be created before calling Config::build",
+						self.out_dir.to_str().expect("output directory path string MUST be valid"))));
+		}
+		results.out_dir = StdPathBuf::new();
+		results.out_dir.push(self.out_dir.clone());
+
+		for (key, value) in &self.metadata {
+			if key.is_empty()
+				|| key.contains(['=', '\n'])
+				|| value.contains('\n')
+				|| CARGO_INSTRUCTION_PREFIXES.iter().any(|prefix| {key.starts_with(prefix)}) {
+				return Err(MldError::from(
+					format!("metadata key {key:?} and its value MUST make a valid cargo:KEY=VALUE")));
+			}
+		}
+
+		let glob_matchoptions = MatchOptions::new();
+		let glob_matchoptions_hook_fn = (self.glob_matchoptions_hook)
+			.replace(Box::new(reflect_glob));
+		let glob_matchoptions = glob_matchoptions_hook_fn(glob_matchoptions);
+
+		let overlapped_exts: Vec<&str> = self.cc_exts
+			.iter()
+			.filter(|(cc_ext, _)|
+			{
+				self.bindgen_exts.iter().any(|(bindgen_ext, _)| {bindgen_ext == cc_ext})
+			})
+			.map(|(cc_ext, _)| {cc_ext.as_str()})
+			.collect();
+		if !overlapped_exts.is_empty() {
+			return Err(MldError::from(
+				format!("extensions {} MUST NOT be both source and header extensions",
+					str_iter_to_string(overlapped_exts.iter()))));
+		}
+
+		let source_root = env::current_dir()?
+			.join(self.source_root.clone().unwrap_or_default());
+		let mut file_type_overrides = Vec::new();
+		for (pattern, file_type) in &self.file_type_overrides {
+			for pattern in expand_braces(pattern) {
+				file_type_overrides.push((
+					Pattern::new(&resolve_pattern(&source_root, &pattern)?)?,
+					file_type.clone()));
+			}
+		}
+		let mut input_globs = InputGlobs {
+			source_root: &source_root,
+			glob_matchoptions,
+			file_type_overrides,
+			ignore_files: IgnoreFiles::default(),
+			bound_headers: HashSet::new(),
+			compiled_sources: HashSet::new(),
+			combined_headers: Vec::new(),
+			combined_input_dirs: Vec::new(),
+			bindgen_jobs: Vec::new(),
+		};
+
+		self.include_dirs = take(&mut self.include_dirs)
+			.iter()
+			.map(|include_dir| {source_root.join(include_dir)})
+			.collect();
+		if self.include_dep_dirs {
+			self.include_dirs.extend(dep_include_dirs(env::vars_os()));
+		}
+		if let Some(vendored_bindings_dir) = &self.vendored_bindings_dir {
+			self.vendored_bindings_dir = Some(source_root.join(vendored_bindings_dir));
+			println!("cargo:rerun-if-env-changed={ENV_KEY_VENDORED_BINDINGS}");
+			if let Some(value) = env::var_os(ENV_KEY_VENDORED_BINDINGS) {
+				if let Some(vendored_bindings_mode) =
+					VendoredBindingsMode::from_env_value(&value.to_string_lossy())? {
+					self.vendored_bindings_mode = vendored_bindings_mode;
+				}
+			}
+		}
+		if self.parallel_bindgen && self.unsync_bindgen_builder_hooks {
+			return Err(MldError::from(
+				"bindgen builder hooks MUST be configured by Config::sync_bindgen_builder_hook for the parallel bindgen"));
+		}
+		if self.bindgen_cache {
+			println!("cargo:rerun-if-env-changed={ENV_KEY_BINDGEN_CACHE_DIR}");
+			let out_dir: &Path = self.out_dir.as_ref();
+			self.bindgen_cache_dir = Some(match env::var_os(ENV_KEY_BINDGEN_CACHE_DIR) {
+				Some(bindgen_cache_dir) if !bindgen_cache_dir.is_empty() => {
+					source_root.join(bindgen_cache_dir)
+				},
+				_ => out_dir.join(BINDGEN_CACHE_DIR),
+			});
+		}
+		self.restrict_bindings_dirs = take(&mut self.restrict_bindings_dirs)
+			.iter()
+			.map(|restrict_bindings_dir| {normalize_path(&source_root.join(restrict_bindings_dir))})
+			.collect();
+		let mut system_link_metadata = Vec::new();
+		for (name, version) in take(&mut self.system_libraries) {
+			let system_library = find_system_library(&name, &version)?;
+			eprintln!("Found {name} {} by pkg-config.", system_library.version);
+			self.include_dirs.extend(system_library.include_dirs);
+			self.defines.extend(system_library.defines);
+			system_link_metadata.extend(system_library.link_metadata);
+			results.system_libraries.push((name, system_library.version));
+		}
+		let out_dir = StdPathBuf::from(AsRef::<Path>::as_ref(&self.out_dir));
+		if !self.config_headers.is_empty() {
+			self.include_dirs.push(out_dir.clone());
+		}
+		// Normalized as the allowlisted files are, so that the paths reported
+		// by libclang through the include directories match them.
+		self.include_dirs = take(&mut self.include_dirs)
+			.iter()
+			.map(|include_dir| {normalize_path(include_dir)})
+			.collect();
+
+		// Create the cc::Build of the default library ahead, so that the
+		// probes run with the same compiler and flags.
+		let default_build = self.cc_build(
+			self.link_kind,
+			false,
+			(self.cc_build_hook).replace(Box::new(reflect_cc)));
+		results.probes = run_probes(&self.probes, &default_build, &out_dir)?;
+		// Likewise for C++ if the ABI layout checkers need it, so that they
+		// are compiled with the same flags as the C++ sources.
+		let cxx_build_hook = (self.cxx_build_hook).replace(Box::new(reflect_cc));
+		let default_cxx_build = if self.abi_layout_tests {
+			CxxBuild::Build(Box::new(self.cc_build(self.link_kind, true, cxx_build_hook)))
+		} else {
+			CxxBuild::Hook(cxx_build_hook)
+		};
+
+		for config_header in &self.config_headers {
+			let rendered = config_header.render(&source_root, &results.probes)?;
+			let config_header_path = out_dir.join(&config_header.filename);
+			if read_to_string(&config_header_path).ok().as_ref() != Some(&rendered) {
+				if let Some(parent) = config_header_path.parent() {
+					create_dir_all(parent)?;
+				}
+				write(&config_header_path, rendered)?;
+			}
+			results.config_headers.push(config_header_path);
+		}
+
+		let mut generated_files = Vec::new();
+		for pre_build_step in take(&mut self.pre_build_steps) {
+			match pre_build_step {
+				PreBuildStep::HostTool(host_tool) => {
+					generated_files.extend(self.run_host_tool(&host_tool, &source_root)?);
+				},
+				PreBuildStep::GenerateSource(name, generate_source_fn) => {
+					let out_dir: &Path = self.out_dir.as_ref();
+					let generated_file = out_dir.join(generate_source_fn(out_dir)?);
+					eprintln!("Generated {} by {name}.", generated_file.display());
+					generated_files.push(generated_file);
+				},
+			}
+		}
+
+		results.installed_headers = self.install_headers(&input_globs, &out_dir, &generated_files)?;
+
+		let mut lib_names = HashSet::new();
+		for library in &self.libraries {
+			if Some(&library.name) == self.lib_name.as_ref()
+				|| !lib_names.insert(&library.name) {
+				return Err(MldError::from(
+					format!("library name {} MUST be unique", library.name)));
+			}
+		}
+		let mut expanded_libraries = Vec::new();
+		for library in &self.libraries {
+			let expanded_input_files = self.build_input_files(
+				&mut input_globs,
+				&library.input_files,
+				&library.exclude_input_files,
+				&[],
+				&mut results)?;
+			if expanded_input_files.source_files.is_empty() {
+				return Err(MldError::from(
+					format!("library {} MUST have at least one cc source", library.name)));
+			}
+			expanded_libraries.push(expanded_input_files);
+		}
+		let expanded_input_files = self.build_input_files(
+			&mut input_globs,
+			&self.input_files,
+			&[],
+			&generated_files,
+			&mut results)?;
+
+		let mut bindgen_jobs = take(&mut input_globs.bindgen_jobs);
+		let mut combined_binding_job = None;
+		if let Some(name) = &self.combined_binding {
+			combined_binding_job = self.combined_binding_job(name, &input_globs, &out_dir)?;
+		}
+		if !self.parallel_bindgen {
+			if let Some(bindgen_job) = combined_binding_job.take() {
+				results.combined_binding = Some(self.generate_binding(bindgen_job)?);
+			}
+		}
+		let parallel_combined_binding = combined_binding_job.is_some();
+		bindgen_jobs.extend(combined_binding_job);
+
+		let abi_layout_builds = match &default_cxx_build {
+			CxxBuild::Build(cxx_build) => Some((default_build.clone(), Build::clone(cxx_build))),
+			CxxBuild::Hook(_) => None,
+		};
+
+		// The cc sources are compiled on the current thread while the
+		// bindings are generated in parallel.
+		let bindgen_settings = self.bindgen_settings();
+		let (generated, compiled) = parallel::run_while(
+			bindgen_jobs,
+			|bindgen_job| {bindgen_settings.generate(bindgen_job, |builder, _| {builder})},
+			|| {
+				self.compile_libraries(
+					expanded_libraries,
+					expanded_input_files,
+					default_build,
+					default_cxx_build,
+					&mut results)
+			});
+		compiled?;
+		let mut header_bindings = generated.into_iter().collect::<Result<Vec<_>, _>>()?;
+		if parallel_combined_binding {
+			results.combined_binding = header_bindings.pop();
+		}
+		results.header_bindings.extend(header_bindings);
+		bindings_mod::check_module_names(
+			results.header_bindings.iter().chain(&results.combined_binding))?;
+
+		if let Some((abi_layout_build, abi_layout_cxx_build)) = &abi_layout_builds {
+			let combined_cxx = input_globs.combined_headers
+				.iter()
+				.any(|(_, language)| {language.is_cxx()});
+			for header_binding in &mut results.header_bindings {
+				let cpp = matches!(
+					self.find_filetype(header_binding.input_header_file.extension()),
+					FileType::Header(language) if language.is_cxx());
+				self.generate_abi_layout_tests(
+					header_binding,
+					if cpp {abi_layout_cxx_build} else {abi_layout_build},
+					cpp)?;
+			}
+			if let Some(header_binding) = &mut results.combined_binding {
+				self.generate_abi_layout_tests(
+					header_binding,
+					if combined_cxx {abi_layout_cxx_build} else {abi_layout_build},
+					combined_cxx)?;
+			}
+		}
+
+		let header_bindings: Vec<&HeaderBinding> = results.header_bindings
+			.iter()
+			.chain(&results.combined_binding)
+			.collect();
+		for header_binding in &header_bindings {
+			match header_binding.bindgen_cache_hit {
+				Some(true) => results.bindgen_cache_hits.push(header_binding.rust_binding_file.clone()),
+				Some(false) => results.bindgen_cache_misses.push(header_binding.rust_binding_file.clone()),
+				None => {},
+			}
+		}
+		if !header_bindings.is_empty() {
+			let rendered = bindings_mod::render(&header_bindings);
+			let bindings_mod_path = out_dir.join(BINDINGS_MOD_FILENAME);
+			if read_to_string(&bindings_mod_path).ok().as_ref() != Some(&rendered) {
+				write(&bindings_mod_path, rendered)?;
+			}
+			results.bindings_mod = Some(bindings_mod_path);
+		}
+
+		if results.libraries.is_empty()
+			&& results.header_bindings.is_empty()
+			&& results.combined_binding.is_none() {
+			return Err(MldError::from("no source files configured"));
+		}
+
+		// The linker resolves the symbols in the order of the libraries.
+		for link_metadata in system_link_metadata {
+			println!("{link_metadata}");
+		}
+
+		if !results.installed_headers.is_empty() {
+			println!("cargo:include={}", out_dir.join(INSTALL_INCLUDE_DIR).display());
+			println!("cargo:root={}", out_dir.display());
+		}
+		for (key, value) in &self.metadata {
+			println!("cargo:{key}={value}");
+		}
+
+		Ok(results)
+	}
+
+	///
+	/// Expand the input files of a library and generate the
+	/// [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding files
+	/// for the header files.
+	///
+	/// The exclusion patterns configured to `self` apply on top of
+	/// `exclude_input_files`.  A header file already bound, or a source file
+	/// already compiled into another library, is skipped, so that no symbols
+	/// are defined twice.
+	///
+	/// The `generated_files` by the pre-build steps follow the globbed input
+	/// files, subject to neither the exclusions nor the ignore files.  They
+	/// are not reported by `cargo:rerun-if-changed`; otherwise the build
+	/// script would always rerun as they are newer than the last run.
+	///
+	/// The directories of the source and header files are the include
+	/// directories of the header files, unless disabled by
+	/// [`Config::include_input_dirs`].
+	///
+	/// Returns the source files and their languages, along with the
+	/// directories of the source and header files.
+	///
+	fn build_input_files(
+		&self,
+		input_globs: &mut InputGlobs,
+		input_files: &[String],
+		exclude_input_files: &[String],
+		generated_files: &[StdPathBuf],
+		results: &mut BuildResults)
+		-> Result<ExpandedInputFiles, MldError>
+	{
+		let (include_globs, mut exclude_globs) = split_patterns(
+			input_files.iter().map(String::as_str));
+		exclude_globs.extend(self.exclude_input_files
+			.iter()
+			.chain(exclude_input_files)
+			.flat_map(|exclude_glob| {expand_braces(exclude_glob)}));
+		let include_globs: Vec<String> = include_globs
+			.iter()
+			.map(|include_glob| {resolve_pattern(input_globs.source_root, include_glob)})
+			.collect::<Result<_, _>>()?;
+		let exclude_patterns = exclude_globs
+			.iter()
+			.map(|exclude_glob|
+			{
+				Ok(Pattern::new(&resolve_pattern(input_globs.source_root, exclude_glob)?)?)
+			})
+			.collect::<Result<Vec<Pattern>, MldError>>()?;
+		let glob_matchoptions = input_globs.glob_matchoptions;
+		let mut globbed = HashSet::new();
+		let mut input_paths = Vec::new();
+		let mut source_files = Vec::new();
+
+		for src_fn_glob in &include_globs {
+			for src_fn_pathbuf in glob_with(src_fn_glob, glob_matchoptions)?
+				.filter_map(Result::ok)
+				.map(|src_fn_pathbuf| {normalize_path(&src_fn_pathbuf)}) {
+				if exclude_patterns.iter().any(|pattern| {
+					pattern.matches_path_with(&src_fn_pathbuf, glob_matchoptions)
+				}) {
+					continue;
+				}
+				if self.respect_ignore_files
+					&& input_globs.ignore_files.is_ignored(&src_fn_pathbuf)? {
+					continue;
+				}
+				if !globbed.insert(src_fn_pathbuf.clone()) {
+					continue;
+				}
+				input_paths.push((src_fn_pathbuf, false));
+			}
+		}
+		for generated_file in generated_files {
+			let generated_file = normalize_path(generated_file);
+			if globbed.insert(generated_file.clone()) {
+				input_paths.push((generated_file, true));
+			}
+		}
+
+		let mut typed_paths = Vec::new();
+		let mut input_dirs: Vec<StdPathBuf> = Vec::new();
+		for (src_fn_pathbuf, generated) in input_paths {
+			let src_filename = src_fn_pathbuf
+				.to_str()
+				.expect("globbed path MUST make a valid string");
+
+			let ext = input_globs.file_type_overrides
+				.iter()
+				.rev()
+				.find(|(pattern, _)|
+				{
+					pattern.matches_path_with(&src_fn_pathbuf, glob_matchoptions)
+				})
+				.map(|(_, file_type)| {file_type.clone()})
+				.unwrap_or_else(|| {self.find_filetype(src_fn_pathbuf.extension())});
+			match ext {
+				FileType::Source(_) | FileType::Header(_) => {
+					if !generated {
+						println!("cargo:rerun-if-changed={src_filename}");
+					}
+					if let Some(input_dir) = src_fn_pathbuf.parent() {
+						if self.include_input_dirs
+							&& !input_dirs.iter().any(|x| {x == input_dir}) {
+							input_dirs.push(input_dir.to_path_buf());
+						}
+					}
+					typed_paths.push((src_fn_pathbuf, ext));
+				},
+				FileType::Unsupported(_) => {
+					eprintln!("Ignoring non-source file {src_filename}.");
+				},
+			}
+		}
+
+		for (src_fn_pathbuf, ext) in typed_paths {
+			match ext {
+				FileType::Source(language) => {
+					if input_globs.compiled_sources.insert(src_fn_pathbuf.clone()) {
+						source_files.push((src_fn_pathbuf, language));
+					}
+				},
+				FileType::Header(language) => {
+					if !input_globs.bound_headers.insert(src_fn_pathbuf.clone()) {
+						continue;
+					}
+					if self.combined_binding.is_some() {
+						for input_dir in &input_dirs {
+							if !input_globs.combined_input_dirs.contains(input_dir) {
+								input_globs.combined_input_dirs.push(input_dir.clone());
+							}
+						}
+						input_globs.combined_headers.push((src_fn_pathbuf, language));
+						continue;
+					}
+					let mut binding_pathbuf = self.out_dir
+						.clone()
+						.join(src_fn_pathbuf.file_name()
+							.expect("source file PathBuf MUST make a valid string"));
+					binding_pathbuf.set_extension(&self.binding_ext);
+					let bindgen_job = BindgenJob {
+						header_binding: HeaderBinding::from((src_fn_pathbuf.clone(), binding_pathbuf)),
+						language,
+						headers: vec![src_fn_pathbuf],
+						input_dirs: input_dirs.clone(),
+					};
+					if self.parallel_bindgen {
+						input_globs.bindgen_jobs.push(bindgen_job);
+					} else {
+						results.header_bindings.push(self.generate_binding(bindgen_job)?);
+					}
+				},
+				FileType::Unsupported(_) => {},
+			}
+		}
+
+		Ok(ExpandedInputFiles {
+			source_files,
+			input_dirs,
+		})
+	}
+
+	///
+	/// Compile the library groups out of `expanded_libraries` and then the
+	/// default library out of `expanded_input_files`, if any cc sources, by
+	/// `default_build` and `default_cxx_build`.
+	///
+	fn compile_libraries(
+		&self,
+		expanded_libraries: Vec<ExpandedInputFiles>,
+		expanded_input_files: ExpandedInputFiles,
+		default_build: Build,
+		default_cxx_build: CxxBuild,
+		results: &mut BuildResults)
+		-> Result<(), MldError>
+	{
+		for (library, ExpandedInputFiles {source_files, input_dirs}) in self.libraries
+			.iter()
+			.zip(expanded_libraries) {
+			let build = self.cc_build(
+				library.link_kind,
+				false,
+				(library.cc_build_hook).replace(Box::new(reflect_cc)));
+			let library_builds = LibraryBuilds {
+				cxx: CxxBuild::Hook((library.cxx_build_hook).replace(Box::new(reflect_cc))),
+				nasm: (library.nasm_build_hook).replace(Box::new(reflect_nasm)),
+			};
+			results.libraries.push(self.compile_library(
+				&library.name,
+				library.link_kind,
+				build,
+				library_builds,
+				source_files,
+				&input_dirs)?);
+		}
+
+		let ExpandedInputFiles {source_files, input_dirs} = expanded_input_files;
+		if !source_files.is_empty() {
+			let lib_name = self.lib_name.as_deref().ok_or_else(
+				|| MldError::from(
+					"library name MUST be configured when at least one cc source is configured"))?;
+			let library_builds = LibraryBuilds {
+				cxx: default_cxx_build,
+				nasm: (self.nasm_build_hook).replace(Box::new(reflect_nasm)),
+			};
+			let library_results = self.compile_library(
+				lib_name,
+				self.link_kind,
+				default_build,
+				library_builds,
+				source_files,
+				&input_dirs)?;
+			results.lib_name = Some(library_results.lib_name.clone());
+			results.source_files = library_results.source_files.clone();
+			results.libraries.push(library_results);
+		}
+
+		Ok(())
+	}
+
+	///
+	/// Create the [`cc::Build`] for C, or for C++ if `cpp`, configured by the
+	/// defaults of `link_kind`, the include directories, the definitions and
+	/// the language standard, and then by `cc_build_hook`.
+	///
+	fn cc_build(&self, link_kind: LinkKind, cpp: bool, cc_build_hook: CcBuildHookFn) -> Build
+	{
+		let mut build = Build::default();
+		// Allow the override by the mock.
+		build.out_dir::<&Path>(self.out_dir.as_ref());
+		if cpp {
+			build.cpp(true);
+		}
+		if let LinkKind::Shared(_) = link_kind {
+			build.pic(true);
+		}
+		for include_dir in &self.include_dirs {
+			build.include(include_dir);
+		}
+		for (name, value) in &self.defines {
+			build.define(name, value.as_deref());
+		}
+		if let Some(std) = if cpp {&self.cxx_std} else {&self.std} {
+			build.std(std);
+		}
+		cc_build_hook(&mut build);
+		build
+	}
+
+	///
+	/// Compile the source files into a library.
+	///
+	/// The C sources are compiled by `build`, configured by
+	/// [`Config::cc_build`], and the C++ sources by the separate
+	/// [`cc::Build`], while the NASM sources are assembled by
+	/// [`nasm_rs::Build`], both taken out of `library_builds`.  The library is archived by the [`cc::Build`] for C++ if
+	/// there are any C++ sources, so that the C++ standard library is linked,
+	/// or the one for C otherwise.
+	///
+	/// The `input_dirs` are added to the [`cc::Build`]s after the hooks.
+	///
+	fn compile_library(
+		&self,
+		lib_name: &str,
+		link_kind: LinkKind,
+		mut build: Build,
+		library_builds: LibraryBuilds,
+		source_files: Vec<(StdPathBuf, Language)>,
+		input_dirs: &[StdPathBuf])
+		-> Result<LibraryResults, MldError>
+	{
+		let source_paths = source_files
+			.iter()
+			.map(|(source_file, _)| {source_file.clone()})
+			.collect();
+		let mut c_source_files = Vec::new();
+		let mut cxx_source_files = Vec::new();
+		let mut nasm_source_files = Vec::new();
+		let mut overridden_source_files = Vec::new();
+		for (source_file, language) in source_files {
+			match language {
+				Language::Nasm => nasm_source_files.push(source_file),
+				language if !is_conventional_source(&source_file, language) => {
+					overridden_source_files.push((source_file, language));
+				},
+				language if language.is_cxx() => cxx_source_files.push(source_file),
+				_ => c_source_files.push(source_file),
+			}
+		}
+
+		let mut objects = Vec::new();
+		let mut shared_library = None;
+		if !nasm_source_files.is_empty() {
+			// nasm_rs runs NASM in the package directory, and panics if it is
+			// unknown, eg outside Cargo.
+			if env::var_os(ENV_KEY_CARGO_MANIFEST_DIR).is_none() {
+				return Err(MldError::from(format!(
+					"{ENV_KEY_CARGO_MANIFEST_DIR} MUST be defined to assemble the NASM sources of {lib_name}")));
+			}
+			let out_dir: &Path = self.out_dir.as_ref();
+			let mut nasm_build = NasmBuild::new();
+			// Separate the objects by the library; NASM names an object after
+			// the source filename.
+			nasm_build.out_dir(out_dir.join("nasm").join(lib_name));
+			if let Some(nasm_object_format) = &self.nasm_object_format {
+				nasm_build.flag(&format!("-f{nasm_object_format}"));
+			}
+			let mut object_names = Vec::new();
+			for source_file in &nasm_source_files {
+				let object_name = source_file.file_stem();
+				if object_names.contains(&object_name) {
+					return Err(MldError::from(format!(
+						"NASM source {} MUST NOT share the object name with another in {lib_name}",
+						source_file.display())));
+				}
+				object_names.push(object_name);
+			}
+			for include_dir in &self.include_dirs {
+				nasm_build.include(include_dir);
+			}
+			for (name, value) in &self.defines {
+				nasm_build.define(name, value.as_deref());
+			}
+			let mut include_dirs = Vec::new();
+			for source_file in &nasm_source_files {
+				if let Some(include_dir) = source_file.parent() {
+					if !include_dirs.contains(&include_dir) {
+						nasm_build.include(include_dir);
+						include_dirs.push(include_dir);
+					}
+				}
+			}
+			(library_builds.nasm)(&mut nasm_build);
+			for source_file in &nasm_source_files {
+				nasm_build.file(source_file);
+			}
+			objects = nasm_build.compile_objects().map_err(MldError::from)?;
+		}
+
+		for input_dir in input_dirs {
+			build.include(input_dir);
+		}
+
+		let mut cxx_build = None;
+		if !cxx_source_files.is_empty()
+			|| overridden_source_files.iter().any(|(_, language)| {language.is_cxx()}) {
+			let mut build = match library_builds.cxx {
+				CxxBuild::Hook(cxx_build_hook) => self.cc_build(link_kind, true, cxx_build_hook),
+				CxxBuild::Build(build) => *build,
+			};
+			for input_dir in input_dirs {
+				build.include(input_dir);
+			}
+			cxx_build = Some(build);
+		}
+
+		// The compiler driver selects the language by the extension, so the
+		// overridden sources are compiled separately with the explicit
+		// language.
+		for (source_file, language) in &overridden_source_files {
+			let base_build = match &cxx_build {
+				Some(cxx_build) if language.is_cxx() => cxx_build,
+				_ => &build,
+			};
+			let mut overridden_build = base_build.clone();
+			if let Some(flag) = language_flag(*language, base_build.try_get_compiler()?.is_like_msvc()) {
+				overridden_build.flag(flag);
+			}
+			overridden_build.file(source_file);
+			objects.extend(overridden_build.try_compile_intermediates()?);
+		}
+
+		for source_file in &c_source_files {
+			build.file(source_file.as_path());
+		}
+		if let Some(cxx_build) = &mut cxx_build {
+			for source_file in &cxx_source_files {
+				cxx_build.file(source_file.as_path());
+			}
+		}
+
+		match (link_kind, cxx_build) {
+			(LinkKind::Shared(soversion), cxx_build) => {
+				if !c_source_files.is_empty() {
+					objects.extend(build.try_compile_intermediates()?);
+				}
+				let linker_build = match &cxx_build {
+					Some(cxx_build) => {
+						objects.extend(cxx_build.try_compile_intermediates()?);
+						cxx_build
+					},
+					None => &build,
+				};
+				shared_library = Some(self.link_shared(lib_name, soversion, linker_build, &objects)?);
+				objects.clear();
+			},
+			(LinkKind::Objects, cxx_build) => {
+				if !c_source_files.is_empty() {
+					objects.extend(build.try_compile_intermediates()?);
+				}
+				if let Some(cxx_build) = &cxx_build {
+					objects.extend(cxx_build.try_compile_intermediates()?);
+				}
+				let mut link_metadata = objects_link_metadata(&objects);
+				// cc links the C++ standard library only for an archive.
+				if cxx_build.is_some() {
+					let target = env::var(ENV_KEY_TARGET).map_err(
+						|_| MldError::from(format!("{ENV_KEY_TARGET} MUST be defined to link the C++ objects of {lib_name}")))?;
+					link_metadata.extend(cxx_stdlib_link_metadata(&target));
+				}
+				for link_metadata in link_metadata {
+					println!("{link_metadata}");
+				}
+			},
+			(_, None) => {
+				link_kind.apply(&mut build);
+				for object in &objects {
+					build.object(object);
+				}
+				build.try_compile(lib_name)?;
+				objects.clear();
+			},
+			(_, Some(mut cxx_build)) => {
+				link_kind.apply(&mut cxx_build);
+				if !c_source_files.is_empty() {
+					objects.extend(build.try_compile_intermediates()?);
+				}
+				for object in &objects {
+					cxx_build.object(object);
+				}
+				cxx_build.try_compile(lib_name)?;
+				objects.clear();
+			},
+		}
+
+		Ok(LibraryResults {
+			lib_name: lib_name.to_string(),
+			link_kind,
+			source_files: source_paths,
+			objects,
+			shared_library,
+		})
+	}
+
+	///
+	/// Link the objects into the versioned shared library by the compiler of
+	/// `build`, named as [`shared_library_args`] does for the target.
+	///
+	/// The [`Cargo`](https://doc.rust-lang.org/cargo/) metadata is emitted to
+	/// link the shared library by the filename, and to find it at runtime by
+	/// the rpath.  The rpath applies only to the targets of the current
+	/// package; the output directory is also emitted as `cargo:rpath`, ie
+	/// `DEP_<LINKS>_RPATH` of the dependent packages, if the package has
+	/// `links`.
+	///
+	fn link_shared(
+		&self,
+		lib_name: &str,
+		soversion: u32,
+		build: &Build,
+		objects: &[StdPathBuf])
+		-> Result<StdPathBuf, MldError>
+	{
+		let compiler = build.try_get_compiler()?;
+		if compiler.is_like_msvc() {
+			return Err(MldError::from(format!(
+				"shared library {lib_name} MUST be linked by a GNU compatible compiler")));
+		}
+
+		let out_dir: &Path = self.out_dir.as_ref();
+		let target = env::var(ENV_KEY_TARGET).map_err(
+			|_| MldError::from(format!("{ENV_KEY_TARGET} MUST be defined to link shared library {lib_name}")))?;
+		let (filename, args) = shared_library_args(lib_name, soversion, &target)?;
+		let shared_library = out_dir.join(&filename);
+		let status = compiler.to_command()
+			.args(&args)
+			.arg("-o")
+			.arg(&shared_library)
+			.args(objects)
+			.status()?;
+		if !status.success() {
+			return Err(MldError::from(format!(
+				"shared library {filename} MUST be linked successfully ({status})")));
+		}
+
+		println!("cargo:rustc-link-search=native={}", out_dir.display());
+		println!("cargo:rustc-link-lib=dylib:+verbatim={filename}");
+		println!("cargo:rustc-link-arg=-Wl,-rpath,{}", out_dir.display());
+		println!("cargo:rpath={}", out_dir.display());
+
+		Ok(shared_library)
+	}
+
+	///
+	/// Generate the [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html)
+	/// binding file of `bindgen_job` on the current thread, configured by all
+	/// of the [`bindgen::Builder`] hooks.
+	///
+	fn generate_binding(&self, bindgen_job: BindgenJob) -> Result<HeaderBinding, MldError>
+	{
+		self.bindgen_settings().generate(bindgen_job, |builder, header_binding| {
+			let builder = (self.
+				bindgen_builder_hook
+				.borrow_mut())
+				(builder);
+			(self.
+				header_bindgen_builder_hook
+				.borrow_mut())
+				(builder, header_binding)
+		})
+	}
+
+	///
+	/// Borrow the parameters of the binding file generation, which are shared
+	/// among the threads by [`Config::parallel_bindgen`].
+	///
+	fn bindgen_settings(&self) -> BindgenSettings<'_>
+	{
+		BindgenSettings {
+			std: self.std.as_deref(),
+			cxx_std: self.cxx_std.as_deref(),
+			include_dirs: &self.include_dirs,
+			defines: &self.defines,
+			restrict_bindings: self.restrict_bindings,
+			restrict_bindings_dirs: &self.restrict_bindings_dirs,
+			vendored_bindings_dir: self.vendored_bindings_dir.as_deref(),
+			vendored_bindings_mode: self.vendored_bindings_mode,
+			bindgen_cache_dir: self.bindgen_cache_dir.as_deref(),
+			sync_bindgen_builder_hook: &*self.sync_bindgen_builder_hook,
+		}
+	}
+
+	///
+	/// Generate the wrapper header of [`Config::combined_binding`], and
+	/// create the job to generate its
+	/// [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding file.
+	///
+	/// Returns [`None`] if no header files are configured.  The wrapper
+	/// header is rewritten only when its contents change.
+	///
+	fn combined_binding_job(&self, name: &str, input_globs: &InputGlobs, out_dir: &Path)
+		-> Result<Option<BindgenJob>, MldError>
+	{
+		if input_globs.combined_headers.is_empty() {
+			return Ok(None);
+		}
+
+		let mut wrapper = String::new();
+		for (header, _) in &input_globs.combined_headers {
+			wrapper += &format!("#include \"{}\"\n", header.display());
+		}
+		let wrapper_path = out_dir.join(format!("{name}.h"));
+		if read_to_string(&wrapper_path).ok().as_ref() != Some(&wrapper) {
+			write(&wrapper_path, wrapper)?;
+		}
+
+		let language = if input_globs.combined_headers.iter().any(|(_, language)| {language.is_cxx()}) {
+			Language::Cxx
+		} else {
+			Language::C
+		};
+
+		Ok(Some(BindgenJob {
+			header_binding: HeaderBinding::from((
+				wrapper_path,
+				out_dir.join(format!("{name}.{}", self.binding_ext)))),
+			language,
+			headers: input_globs.combined_headers
+				.iter()
+				.map(|(header, _)| {header.clone()})
+				.collect(),
+			input_dirs: input_globs.combined_input_dirs.clone(),
+		}))
+	}
+
+	///
+	/// Install the public headers into [`INSTALL_INCLUDE_DIR`] in the output
+	/// directory.
 	///
-	/// SystemDepsConfig::new()
-	/// 	.probe()
-	/// 	.expect("the system library integration MUST succeed");
-	/// ```
+	/// Returns the installed headers.  A header is rewritten only when its
+	/// contents change, so that the downstream builds are not triggered in
+	/// vain.
 	///
-	/// [`system_deps::Config::probe`] after [`Config::build`] integrates the
-	/// system libraries configured in the
-	/// [`Cargo`](https://doc.rust-lang.org/cargo/) manifest so that they
-	/// resolve the symbols required by the internal library.
+	/// The headers in the output directory and the `generated_files` by the
+	/// pre-build steps are not reported by `cargo:rerun-if-changed`, as in
+	/// [`Config::build_input_files`].
 	///
-	// XXX the link to `himetake` is not via the Rust name in order to avoid
-	// the cyclic package dependency.
-	pub fn build(self)
-		-> Result<BuildResults, MldError>
+	fn install_headers(&self, input_globs: &InputGlobs, out_dir: &Path,
+		generated_files: &[StdPathBuf])
+		-> Result<Vec<StdPathBuf>, MldError>
 	{
-		let mut results = BuildResults::new();
-		let mut built_something = false;
+		let include_dir = out_dir.join(INSTALL_INCLUDE_DIR);
+		let normalized_out_dir = normalize_path(out_dir);
+		let mut installed_headers = Vec::new();
 
-		if !self.out_dir.is_dir() {
-			return Err(
-				MldError::from(
-					format!("output directory {} MUST be created before calling Config::build",
-						self.out_dir.to_str().expect("output directory path string MUST be valid"))));
+		for (pattern, dest_dir) in &self.installed_headers {
+			if !Path::new(dest_dir)
+				.components()
+				.all(|component| {matches!(component, Component::Normal(_) | Component::CurDir)}) {
+				return Err(MldError::from(
+					format!("header install directory {dest_dir} MUST be relative without ..")));
+			}
+			let mut headers = Vec::new();
+			for pattern in expand_braces(pattern) {
+				let pattern = resolve_pattern(input_globs.source_root, &pattern)?;
+				headers.extend(glob_with(&pattern, input_globs.glob_matchoptions)?
+					.filter_map(Result::ok)
+					.map(|header| {normalize_path(&header)}));
+			}
+			if headers.is_empty() {
+				return Err(MldError::from(
+					format!("header pattern {pattern} MUST match at least one file")));
+			}
+
+			for header in headers {
+				let filename = header.file_name().ok_or_else(
+					|| MldError::from(
+						format!("header {} MUST have a filename", header.display())))?;
+				let installed_header = normalize_path(&include_dir.join(dest_dir).join(filename));
+				if installed_headers.contains(&installed_header) {
+					return Err(MldError::from(
+						format!("installed header {} MUST be unique", installed_header.display())));
+				}
+				let contents = read(&header)?;
+				if read(&installed_header).ok().as_ref() != Some(&contents) {
+					if let Some(parent) = installed_header.parent() {
+						create_dir_all(parent)?;
+					}
+					write(&installed_header, contents)?;
+				}
+				if !header.starts_with(&normalized_out_dir)
+					&& !generated_files.iter().any(|generated_file| {normalize_path(generated_file) == header}) {
+					println!("cargo:rerun-if-changed={}", header.display());
+				}
+				installed_headers.push(installed_header);
+			}
+		}
+
+		Ok(installed_headers)
+	}
+
+	///
+	/// Compile the host tool for the host and run it in the output directory.
+	///
+	/// Returns the output files of the host tool, failing if any of them is
+	/// not created.
+	///
+	fn run_host_tool(&self, host_tool: &HostTool, source_root: &Path)
+		-> Result<Vec<StdPathBuf>, MldError>
+	{
+		let host = env::var(ENV_KEY_HOST)
+			.map_err(|_| MldError::from(
+				format!("{ENV_KEY_HOST} MUST be defined to build host tool {}", host_tool.name)))?;
+		if host_tool.source_files.is_empty() {
+			return Err(MldError::from(
+				format!("host tool {} MUST have at least one source file", host_tool.name)));
 		}
-		results.out_dir = StdPathBuf::new();
-		results.out_dir.push(self.out_dir.clone());
 
+		let out_dir: &Path = self.out_dir.as_ref();
+		let tool_dir = out_dir.join("host").join(&host_tool.name);
 		let mut build = Build::default();
-		let cc_build_hook_fn = (self.cc_build_hook)
+		build.out_dir(&tool_dir);
+		build.target(&host);
+		build.host(&host);
+		build.cargo_metadata(false);
+		let mut source_files = Vec::new();
+		for source_file in &host_tool.source_files {
+			let source_file = normalize_path(&source_root.join(source_file));
+			match self.find_filetype(source_file.extension()) {
+				FileType::Source(language) => {
+					if language.is_cxx() {
+						build.cpp(true);
+					}
+				},
+				_ => {
+					return Err(MldError::from(
+						format!("host tool source {} MUST be a source file", source_file.display())));
+				},
+			}
+			println!("cargo:rerun-if-changed={}", source_file.display());
+			source_files.push(source_file);
+		}
+		let cc_build_hook_fn = (host_tool.cc_build_hook)
 			.replace(Box::new(reflect_cc));
-		// Allow the override by the mock.
-		build.out_dir::<&Path>(self.out_dir.as_ref());
 		cc_build_hook_fn(&mut build);
+		for source_file in &source_files {
+			build.file(source_file);
+		}
 
-		let glob_matchoptions = MatchOptions::new();
-		let glob_matchoptions_hook_fn = (self.glob_matchoptions_hook)
-			.replace(Box::new(reflect_glob));
-		let glob_matchoptions = glob_matchoptions_hook_fn(glob_matchoptions);
+		let executable = tool_dir.join(format!("{}{EXE_SUFFIX}", host_tool.name));
+		link_executable(&build, &executable, &format!("host tool {}", host_tool.name))?;
 
-		for src_fn_glob in &self.input_files {
-			for src_fn_pathbuf in glob_with(src_fn_glob, glob_matchoptions)?
-				.filter_map(Result::ok) {
-				let src_filename = src_fn_pathbuf
-					.to_str()
-					.expect("globbed path MUST make a valid string");
-
-				let ext = self.find_filetype(src_fn_pathbuf.extension());
-				match ext {
-					FileType::Source | FileType::Header => {
-						println!("cargo:rerun-if-changed={src_filename}");
-					},
-					FileType::Unsupported(_) => {
-						eprintln!("Ignoring non-source file {src_filename}.");
-					},
-				}
-				match ext {
-					FileType::Source => {
-						build.file(src_fn_pathbuf.as_path());
-						results.source_files.push(src_fn_pathbuf);
-					},
-					FileType::Header => {
-						let mut binding_pathbuf = self.out_dir
-							.clone()
-							.join(src_fn_pathbuf.file_name()
-								.expect("source file PathBuf MUST make a valid string"));
-						binding_pathbuf.set_extension(self.binding_ext);
-						let builder = Builder::default()
-							.header(src_filename)
-							.parse_callbacks(Box::new(CargoCallbacks));
-						let builder = (self.
-							bindgen_builder_hook
-							.borrow_mut())
-							(builder);
-						let bindings = builder.generate()?;
-						bindings.write_to_file(&binding_pathbuf)?;
-						built_something = true;
-						results.header_bindings.push(
-							HeaderBinding::from((src_fn_pathbuf, binding_pathbuf)));
-					},
-					FileType::Unsupported(_) => {},
-				}
-			}
+		let status = Command::new(&executable)
+			.args(&host_tool.args)
+			.current_dir(out_dir)
+			.status()?;
+		if !status.success() {
+			return Err(MldError::from(
+				format!("host tool {} MUST exit successfully ({status})", host_tool.name)));
 		}
 
-		if !results.source_files.is_empty() {
-			let lib_name = self.lib_name.ok_or_else(
-				|| MldError::from(
-					"library name MUST be configured when at least one cc source is configured"))?;
-			build.try_compile(lib_name)?;
-			built_something = true;
-			results.lib_name = Some(String::from(lib_name));
+		let output_files: Vec<StdPathBuf> = host_tool.output_files
+			.iter()
+			.map(|output_file| {out_dir.join(output_file)})
+			.collect();
+		if let Some(missing) = output_files.iter().find(|output_file| {!output_file.is_file()}) {
+			return Err(MldError::from(
+				format!("host tool {} MUST create output file {}", host_tool.name, missing.display())));
 		}
 
-		if !built_something {
-			return Err(MldError::from("no source files configured"));
+		Ok(output_files)
+	}
+
+	///
+	/// Generate the C program printing the layouts of the types bound by
+	/// `header_binding` and the Rust tests comparing them, as configured by
+	/// [`Config::abi_layout_tests`].
+	///
+	/// The program is compiled by a copy of `build`, which is for C++ if
+	/// `cpp`, and linked into [`ABI_LAYOUT_DIR`].  Nothing is generated if no types are
+	/// checked.  The generated files are rewritten only when their contents
+	/// change.
+	///
+	fn generate_abi_layout_tests(&self, header_binding: &mut HeaderBinding, build: &Build, cpp: bool)
+		-> Result<(), MldError>
+	{
+		let binding = read_to_string(&header_binding.rust_binding_file)?;
+		let headers = read_headers(&header_binding.input_header_file)?;
+		let layouts: Vec<TypeLayout> = parse_binding(&binding, &headers)?;
+		if layouts.is_empty() {
+			return Ok(());
 		}
 
-		Ok(results)
+		let out_dir: &Path = self.out_dir.as_ref();
+		let abi_layout_dir = out_dir.join(ABI_LAYOUT_DIR);
+		let name = bindings_mod::module_name(&header_binding.rust_binding_file);
+		let checker_dir = abi_layout_dir.join(&name);
+		create_dir_all(&checker_dir)?;
+		let checker_source = abi_layout_dir.join(format!("{name}.{}", if cpp {"cpp"} else {"c"}));
+		let rendered = render_checker(&header_binding.input_header_file, &layouts);
+		if read_to_string(&checker_source).ok().as_ref() != Some(&rendered) {
+			write(&checker_source, rendered)?;
+		}
+
+		let mut build = build.clone();
+		build.out_dir(&checker_dir);
+		build.cargo_metadata(false);
+		if let Some(header_dir) = header_binding.input_header_file.parent() {
+			build.include(header_dir);
+		}
+		build.file(&checker_source);
+		let checker = checker_dir.join(format!("{name}{EXE_SUFFIX}"));
+		link_executable(&build, &checker, &format!("ABI layout checker {name}"))?;
+
+		let test_file = abi_layout_dir.join(format!("{name}.rs"));
+		let rendered = render_tests(&checker, &layouts);
+		if read_to_string(&test_file).ok().as_ref() != Some(&rendered) {
+			write(&test_file, rendered)?;
+		}
+		header_binding.abi_layout_checker = Some(checker);
+		header_binding.abi_layout_test_file = Some(test_file);
+
+		Ok(())
 	}
 
 	/// Look up the [`FileType`] value matching the given extension.
@@ -1043,10 +3698,10 @@ impl<'a> Config<'a>
 			Some(ext_os) => {
 				match ext_os.to_str() {
 					Some(ext_str) => {
-						if self.cc_exts.iter().find(|x| {*x == ext_str}).is_some() {
-							FileType::Source
-						} else if self.bindgen_exts.iter().find(|x| {*x == ext_str}).is_some() {
-							FileType::Header
+						if let Some((_, language)) = self.cc_exts.iter().find(|(x, _)| {x == ext_str}) {
+							FileType::Source(*language)
+						} else if let Some((_, language)) = self.bindgen_exts.iter().find(|(x, _)| {x == ext_str}) {
+							FileType::Header(*language)
 						} else {
 							FileType::Unsupported(String::from(ext_str))
 						}
@@ -1061,6 +3716,256 @@ impl<'a> Config<'a>
 	}
 }
 
+///
+/// The parameters of the binding file generation borrowed from [`Config`],
+/// which are shared among the threads by [`Config::parallel_bindgen`].
+///
+struct BindgenSettings<'s>
+{
+	/// The C language standard.
+	std: Option<&'s str>,
+	/// The C++ language standard.
+	cxx_std: Option<&'s str>,
+	/// The include directories.
+	include_dirs: &'s [StdPathBuf],
+	/// The macro definitions.
+	defines: &'s [(String, Option<String>)],
+	/// Whether the bindings are restricted to the header files.
+	restrict_bindings: bool,
+	/// The directories the bindings are also restricted to.
+	restrict_bindings_dirs: &'s [StdPathBuf],
+	/// The vendored bindings directory.
+	vendored_bindings_dir: Option<&'s Path>,
+	/// The usage of the vendored binding files.
+	vendored_bindings_mode: VendoredBindingsMode,
+	/// The binding file cache directory.
+	bindgen_cache_dir: Option<&'s Path>,
+	/// The thread-safe hook to configure [`bindgen::Builder`].
+	sync_bindgen_builder_hook: &'s (dyn Fn(Builder, &HeaderBinding) -> Builder + Send + Sync + 's),
+}
+
+impl<'s> BindgenSettings<'s>
+{
+	///
+	/// Generate the [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html)
+	/// binding file of `bindgen_job`.
+	///
+	/// The bindings are restricted to the headers of `bindgen_job` if
+	/// configured by [`Config::restrict_bindings`].  [`bindgen::Builder`] is
+	/// configured by `hooks` and then by the hook configured by
+	/// [`Config::sync_bindgen_builder_hook`], after which the input
+	/// directories are added as the include directories.
+	///
+	/// The binding file is restored from [`Config::bindgen_cache`] if the
+	/// cached one is up to date, or stored into it after the generation.
+	///
+	/// The vendored binding file is copied instead if forced, or if `libclang`
+	/// cannot be loaded, ie [`bindgen::Builder::generate`] panics, in
+	/// [`VendoredBindingsMode::Fallback`].  Otherwise, the generated binding
+	/// file is checked against the vendored one in
+	/// [`VendoredBindingsMode::Verify`] and [`VendoredBindingsMode::Update`].
+	///
+	fn generate<H>(&self, bindgen_job: BindgenJob, hooks: H) -> Result<HeaderBinding, MldError>
+		where H: FnOnce(Builder, &HeaderBinding) -> Builder
+	{
+		let BindgenJob {mut header_binding, language, headers, input_dirs} = bindgen_job;
+		let vendored_bindings_mode = self.vendored_bindings_dir
+			.map(|_| {self.vendored_bindings_mode});
+		if vendored_bindings_mode == Some(VendoredBindingsMode::Force) {
+			return self.copy_vendored_binding(header_binding);
+		}
+
+		let mut builder = Builder::default()
+			.header(header_binding.input_header_file
+				.to_str()
+				.expect("header path MUST make a valid string"))
+			.parse_callbacks(Box::new(CargoCallbacks));
+		let std = if language.is_cxx() {
+			builder = builder.clang_arg("-xc++");
+			self.cxx_std
+		} else {
+			self.std
+		};
+		if let Some(std) = std {
+			builder = builder.clang_arg(format!("-std={std}"));
+		}
+		for include_dir in self.include_dirs {
+			builder = builder.clang_arg(format!("-I{}", include_dir.display()));
+		}
+		for (name, value) in self.defines {
+			builder = builder.clang_arg(match value {
+				Some(value) => format!("-D{name}={value}"),
+				None => format!("-D{name}"),
+			});
+		}
+		if self.restrict_bindings {
+			for header in &headers {
+				builder = builder.allowlist_file(escape_regex(&header.to_string_lossy()));
+			}
+			for restrict_bindings_dir in self.restrict_bindings_dirs {
+				builder = builder.allowlist_file(format!("{}{}.*",
+					escape_regex(&restrict_bindings_dir.to_string_lossy()),
+					escape_regex(MAIN_SEPARATOR_STR)));
+			}
+		}
+		let builder = hooks(builder, &header_binding);
+		let mut builder = (self.sync_bindgen_builder_hook)(builder, &header_binding);
+		for input_dir in &input_dirs {
+			builder = builder.clang_arg(format!("-I{}", input_dir.display()));
+		}
+
+		let bindgen_cache = self.bindgen_cache_dir
+			.and_then(|bindgen_cache_dir| {
+				BindgenCache::new(
+					bindgen_cache_dir,
+					&builder.command_line_flags(),
+					env::var(ENV_KEY_TARGET).ok().as_deref())
+			});
+		let cached_dependencies = match &bindgen_cache {
+			Some(bindgen_cache) => bindgen_cache.restore(&header_binding.rust_binding_file)?,
+			None => None,
+		};
+		if let Some(dependencies) = cached_dependencies {
+			// Report the dependencies instead of CargoCallbacks.
+			for file in &dependencies.files {
+				println!("cargo:rerun-if-changed={}", file.display());
+			}
+			for env_var in &dependencies.env_vars {
+				println!("cargo:rerun-if-env-changed={env_var}");
+			}
+			header_binding.bindgen_cache_hit = Some(true);
+		} else {
+			let dependencies = Arc::new(Mutex::new(Dependencies {
+				files: vec![header_binding.input_header_file.clone()],
+				env_vars: Vec::new(),
+			}));
+			if bindgen_cache.is_some() {
+				builder = builder.parse_callbacks(Box::new(DependencyRecorder(dependencies.clone())));
+			}
+			let bindings = if vendored_bindings_mode == Some(VendoredBindingsMode::Fallback) {
+				// Probe libclang alone, so that any other panic of bindgen is
+				// not taken for the missing libclang.
+				match catch_unwind(clang_version) {
+					Ok(_) => builder.generate()?,
+					Err(panic) => {
+						if !self.vendored_binding_path(&header_binding)?.is_file() {
+							resume_unwind(panic);
+						}
+						eprintln!("Falling back on the vendored binding of {}.",
+							header_binding.input_header_file.display());
+						return self.copy_vendored_binding(header_binding);
+					},
+				}
+			} else {
+				builder.generate()?
+			};
+			bindings.write_to_file(&header_binding.rust_binding_file)?;
+			if let Some(bindgen_cache) = &bindgen_cache {
+				let dependencies = dependencies.lock().expect("dependencies MUST NOT be poisoned");
+				bindgen_cache.store(&header_binding.rust_binding_file, &dependencies)?;
+				header_binding.bindgen_cache_hit = Some(false);
+			}
+		}
+
+		match vendored_bindings_mode {
+			Some(VendoredBindingsMode::Verify) => self.verify_vendored_binding(&header_binding)?,
+			Some(VendoredBindingsMode::Update) => self.update_vendored_binding(&header_binding)?,
+			_ => {},
+		}
+
+		Ok(header_binding)
+	}
+
+	///
+	/// Check the generated binding file of `header_binding` against the
+	/// vendored one, failing with the difference if they differ.
+	///
+	fn verify_vendored_binding(&self, header_binding: &HeaderBinding)
+		-> Result<(), MldError>
+	{
+		let vendored_binding_file = self.vendored_binding_path(header_binding)?;
+		if !vendored_binding_file.is_file() {
+			return Err(MldError::from(format!(
+				"vendored binding {} MUST exist; set {ENV_KEY_VENDORED_BINDINGS}=update to create it",
+				vendored_binding_file.display())));
+		}
+		let generated = read_to_string(&header_binding.rust_binding_file)?;
+		let vendored = read_to_string(&vendored_binding_file)?;
+		if generated != vendored {
+			return Err(MldError::from(format!(
+				"vendored binding {} MUST match the generated binding; set {ENV_KEY_VENDORED_BINDINGS}=update to update it\n{}",
+				vendored_binding_file.display(),
+				vendored_bindings::diff(
+					&vendored,
+					&generated,
+					&vendored_binding_file.to_string_lossy(),
+					&header_binding.rust_binding_file.to_string_lossy()))));
+		}
+
+		Ok(())
+	}
+
+	///
+	/// Rewrite the vendored binding file of `header_binding` by the generated
+	/// one if they differ.
+	///
+	fn update_vendored_binding(&self, header_binding: &HeaderBinding)
+		-> Result<(), MldError>
+	{
+		let vendored_binding_file = self.vendored_binding_path(header_binding)?;
+		let generated = read(&header_binding.rust_binding_file)?;
+		if read(&vendored_binding_file).ok().as_ref() != Some(&generated) {
+			if let Some(parent) = vendored_binding_file.parent() {
+				create_dir_all(parent)?;
+			}
+			write(&vendored_binding_file, generated)?;
+			eprintln!("Updated vendored binding {}.", vendored_binding_file.display());
+		}
+
+		Ok(())
+	}
+
+	///
+	/// Locate the vendored binding file of `header_binding` for the target.
+	///
+	fn vendored_binding_path(&self, header_binding: &HeaderBinding)
+		-> Result<StdPathBuf, MldError>
+	{
+		let vendored_bindings_dir = self.vendored_bindings_dir.ok_or_else(
+			|| MldError::from("vendored bindings directory MUST be configured to use the vendored bindings"))?;
+		let target = env::var(ENV_KEY_TARGET).map_err(
+			|_| MldError::from(format!("{ENV_KEY_TARGET} MUST be defined to select the vendored bindings")))?;
+		let filename = header_binding.rust_binding_file.file_name().ok_or_else(
+			|| MldError::from(
+				format!("binding file {} MUST have a filename",
+					header_binding.rust_binding_file.display())))?;
+
+		Ok(vendored_bindings_dir.join(target).join(filename))
+	}
+
+	///
+	/// Copy the vendored binding file of `header_binding` into the binding
+	/// file, rewriting it only when its contents change.
+	///
+	fn copy_vendored_binding(&self, mut header_binding: HeaderBinding)
+		-> Result<HeaderBinding, MldError>
+	{
+		let vendored_binding_file = self.vendored_binding_path(&header_binding)?;
+		let contents = read(&vendored_binding_file).map_err(
+			|err| MldError::from(
+				format!("vendored binding {} MUST be readable: {err}",
+					vendored_binding_file.display())))?;
+		if read(&header_binding.rust_binding_file).ok().as_ref() != Some(&contents) {
+			write(&header_binding.rust_binding_file, contents)?;
+		}
+		println!("cargo:rerun-if-changed={}", vendored_binding_file.display());
+		header_binding.vendored_binding_file = Some(vendored_binding_file);
+
+		Ok(header_binding)
+	}
+
+}
+
 ///
 /// The pair of an input header file and the generated
 /// [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding file,
@@ -1074,6 +3979,20 @@ pub struct HeaderBinding
 	/// The [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding
 	/// file.
 	pub rust_binding_file: StdPathBuf,
+	/// The vendored binding file copied into the binding file, or [`None`]
+	/// if the binding file is generated by [`bindgen::Builder::generate`].
+	pub vendored_binding_file: Option<StdPathBuf>,
+	/// Whether the binding file is restored from [`Config::bindgen_cache`]
+	/// (`true`) or generated and stored into it (`false`), or [`None`] if
+	/// the cache is not used.
+	pub bindgen_cache_hit: Option<bool>,
+	/// The C program printing the layouts of the bound types, if generated
+	/// by [`Config::abi_layout_tests`].
+	pub abi_layout_checker: Option<StdPathBuf>,
+	/// The Rust tests comparing the layouts with the ones printed by
+	/// [`HeaderBinding::abi_layout_checker`], if generated by
+	/// [`Config::abi_layout_tests`].
+	pub abi_layout_test_file: Option<StdPathBuf>,
 }
 
 impl From<(StdPathBuf, StdPathBuf)> for HeaderBinding
@@ -1086,6 +4005,10 @@ impl From<(StdPathBuf, StdPathBuf)> for HeaderBinding
 		HeaderBinding {
 			input_header_file: paths.0,
 			rust_binding_file: paths.1,
+			vendored_binding_file: None,
+			bindgen_cache_hit: None,
+			abi_layout_checker: None,
+			abi_layout_test_file: None,
 		}
 	}
 }
@@ -1095,9 +4018,21 @@ impl Display for HeaderBinding
 	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError>
 	{
 		write!(f,
-			"({} -> {})",
+			"({} -> {}",
 			self.input_header_file.display(),
-			self.rust_binding_file.display())
+			self.rust_binding_file.display())?;
+		if let Some(vendored_binding_file) = &self.vendored_binding_file {
+			write!(f, ", vendored from {}", vendored_binding_file.display())?;
+		}
+		match self.bindgen_cache_hit {
+			Some(true) => write!(f, ", cache hit")?,
+			Some(false) => write!(f, ", cache miss")?,
+			None => {},
+		}
+		if let Some(abi_layout_test_file) = &self.abi_layout_test_file {
+			write!(f, ", ABI layout tests in {}", abi_layout_test_file.display())?;
+		}
+		write!(f, ")")
 	}
 }
 
@@ -1113,13 +4048,38 @@ pub struct BuildResults
 {
 	/// The output directory.
 	pub out_dir: StdPathBuf,
-	/// The library name, if generated.
+	/// The default library name, if generated.
 	pub lib_name: Option<String>,
-	/// The source files.
+	/// The source files of the default library.
 	pub source_files: Vec<StdPathBuf>,
 	/// The header and generated
 	/// [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding file pairs.
 	pub header_bindings: Vec<HeaderBinding>,
+	/// The generated libraries, including the default one, in the order
+	/// reported to [`Cargo`](https://doc.rust-lang.org/cargo/).
+	pub libraries: Vec<LibraryResults>,
+	/// The generated configuration headers.
+	pub config_headers: Vec<StdPathBuf>,
+	/// The name and value pairs of the probe results, unset if
+	/// [`None`].
+	pub probes: Vec<(String, Option<String>)>,
+	/// The name and version pairs of the system libraries found by
+	/// `pkg-config`.
+	pub system_libraries: Vec<(String, String)>,
+	/// The public headers installed into [`INSTALL_INCLUDE_DIR`].
+	pub installed_headers: Vec<StdPathBuf>,
+	/// The wrapper header and the single
+	/// [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding file
+	/// pair, if configured by [`Config::combined_binding`].
+	pub combined_binding: Option<HeaderBinding>,
+	/// The Rust module of the binding files, named
+	/// [`BINDINGS_MOD_FILENAME`], if any binding file is generated.
+	pub bindings_mod: Option<StdPathBuf>,
+	/// The binding files restored from [`Config::bindgen_cache`].
+	pub bindgen_cache_hits: Vec<StdPathBuf>,
+	/// The binding files generated and stored into
+	/// [`Config::bindgen_cache`].
+	pub bindgen_cache_misses: Vec<StdPathBuf>,
 }
 
 impl BuildResults
@@ -1132,6 +4092,15 @@ impl BuildResults
 			lib_name: None,
 			source_files: Vec::new(),
 			header_bindings: Vec::new(),
+			libraries: Vec::new(),
+			config_headers: Vec::new(),
+			probes: Vec::new(),
+			system_libraries: Vec::new(),
+			installed_headers: Vec::new(),
+			combined_binding: None,
+			bindings_mod: None,
+			bindgen_cache_hits: Vec::new(),
+			bindgen_cache_misses: Vec::new(),
 		}
 	}
 }
@@ -1141,11 +4110,61 @@ impl Display for BuildResults
 	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError>
 	{
 		write!(f,
-			"(out_dir: {}, lib_name: {}, source_files: {}, header_bindings: {})",
+			"(out_dir: {}, lib_name: {}, source_files: {}, header_bindings: {}, libraries: {}, config_headers: {}, probes: {}, system_libraries: {}, installed_headers: {}, combined_binding: {}, bindings_mod: {}, bindgen_cache_hits: {}, bindgen_cache_misses: {})",
 			self.out_dir.display(),
 			(self.lib_name.as_ref()).unwrap_or(&("None".to_string())),
 			str_iter_to_string(self.source_files.iter().map(|path_buf| {path_buf.display()})),
-			str_iter_to_string(self.header_bindings.iter()))
+			str_iter_to_string(self.header_bindings.iter()),
+			str_iter_to_string(self.libraries.iter()),
+			str_iter_to_string(self.config_headers.iter().map(|path_buf| {path_buf.display()})),
+			str_iter_to_string(self.probes.iter().map(|(name, value)|
+			{
+				format!("{name}={}", value.as_deref().unwrap_or("None"))
+			})),
+			str_iter_to_string(self.system_libraries.iter().map(|(name, version)|
+			{
+				format!("{name} {version}")
+			})),
+			str_iter_to_string(self.installed_headers.iter().map(|path_buf| {path_buf.display()})),
+			self.combined_binding.as_ref().map_or("None".to_string(), ToString::to_string),
+			self.bindings_mod.as_ref().map_or("None".to_string(), |path_buf| {path_buf.display().to_string()}),
+			str_iter_to_string(self.bindgen_cache_hits.iter().map(|path_buf| {path_buf.display()})),
+			str_iter_to_string(self.bindgen_cache_misses.iter().map(|path_buf| {path_buf.display()})))
+	}
+}
+
+///
+/// The library generated by [`Config::build`], held in [`BuildResults`].
+///
+#[derive(Debug)]
+pub struct LibraryResults
+{
+	/// The library name.
+	pub lib_name: String,
+	/// The linkage reported to [`Cargo`](https://doc.rust-lang.org/cargo/).
+	pub link_kind: LinkKind,
+	/// The source files.
+	pub source_files: Vec<StdPathBuf>,
+	/// The objects linked directly by [`LinkKind::Objects`], empty for the
+	/// other link kinds.
+	pub objects: Vec<StdPathBuf>,
+	/// The versioned shared library linked by [`LinkKind::Shared`], eg
+	/// `lib<name>.so.<soversion>` in the output directory, for the processes
+	/// other than Rust to load.
+	pub shared_library: Option<StdPathBuf>,
+}
+
+impl Display for LibraryResults
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError>
+	{
+		write!(f,
+			"(lib_name: {}, link_kind: {}, source_files: {}, objects: {}, shared_library: {})",
+			self.lib_name,
+			self.link_kind,
+			str_iter_to_string(self.source_files.iter().map(|path_buf| {path_buf.display()})),
+			str_iter_to_string(self.objects.iter().map(|path_buf| {path_buf.display()})),
+			self.shared_library.as_ref().map_or("None".to_string(), |path_buf| {path_buf.display().to_string()}))
 	}
 }
 
@@ -1158,50 +4177,314 @@ impl Termination for BuildResults
 	}
 }
 
+///
+/// Collect the include directories of the dependencies, out of the
+/// `DEP_<LINKS>_INCLUDE` environment variables in `vars`.
+///
+/// The variables are sorted by the name for the reproducible build.  Each of
+/// them MAY list multiple directories, separated as in `PATH`.
+///
+fn dep_include_dirs<IT>(vars: IT) -> Vec<StdPathBuf>
+	where IT: Iterator<Item = (OsString, OsString)>
+{
+	let mut dep_includes: Vec<(OsString, OsString)> = vars
+		.filter(|(key, _)|
+		{
+			key.to_str().is_some_and(|key| {
+				key.starts_with("DEP_") && key.ends_with("_INCLUDE") && key.len() > "DEP__INCLUDE".len()
+			})
+		})
+		.collect();
+	dep_includes.sort();
+
+	dep_includes
+		.iter()
+		.flat_map(|(_, value)| {env::split_paths(value).collect::<Vec<_>>()})
+		.filter(|include_dir| {!include_dir.as_os_str().is_empty()})
+		.collect()
+}
+
+///
+/// Escape the regular expression metacharacters in `s`, so that
+/// [`bindgen::Builder::allowlist_file`] matches it literally.
+///
+fn escape_regex(s: &str) -> String
+{
+	let mut escaped = String::new();
+	for c in s.chars() {
+		if "\\.+*?()|[]{}^$#&-~".contains(c) {
+			escaped.push('\\');
+		}
+		escaped.push(c);
+	}
+
+	escaped
+}
+
+///
+/// Compile the source files of `build` and link them into `executable` by
+/// the compiler, failing with `name` if the linker fails.
+///
+fn link_executable(build: &Build, executable: &Path, name: &str)
+	-> Result<(), MldError>
+{
+	let objects = build.try_compile_intermediates()?;
+	let compiler = build.try_get_compiler()?;
+	let mut command = compiler.to_command();
+	if compiler.is_like_msvc() {
+		command.args(&objects)
+			.arg(format!("/Fe{}", executable.display()));
+	} else {
+		command.arg("-o")
+			.arg(executable)
+			.args(&objects);
+	}
+	let status = command.status()?;
+	if !status.success() {
+		return Err(MldError::from(
+			format!("{name} MUST be linked successfully ({status})")));
+	}
+
+	Ok(())
+}
+
 /// Display each string in the iterator as the comma-separated elements wrapped
 /// by the square brackets.
 fn str_iter_to_string<T, IT>(iter: IT) -> String
 	where T: ToString, IT: Iterator<Item = T>
 {
-	let mut string = String::new();
+	format!("[{}]", iter.map(|i| {i.to_string()}).collect::<Vec<_>>().join(", "))
+}
 
-	string += "[";
-	let mut first = true;
-	for i in iter {
-		string += &format!("{}", i.to_string());
-		if first {
-			string += ", ";
-			first = false;
-		}
+///
+/// List the [`Cargo`](https://doc.rust-lang.org/cargo/) metadata to link the
+/// `objects` of [`LinkKind::Objects`] into the targets of the current
+/// package.
+///
+fn objects_link_metadata(objects: &[StdPathBuf]) -> Vec<String>
+{
+	objects
+		.iter()
+		.map(|object| {format!("cargo:rustc-link-arg={}", object.display())})
+		.collect()
+}
+
+///
+/// List the [`Cargo`](https://doc.rust-lang.org/cargo/) metadata to link the
+/// C++ standard library for `target`, as [`cc::Build`] does for an archive.
+///
+/// The library is named by the `CXXSTDLIB` environment variable of the
+/// target, or by the default of the target, eg `stdc++` on Linux and `c++`
+/// on the Apple targets.  Nothing is linked for MSVC or an empty
+/// `CXXSTDLIB`.  The library is linked statically if `CXXSTDLIB_STATIC` is
+/// set to none of `""`, `"0"`, `"no"` and `"false"`.
+///
+fn cxx_stdlib_link_metadata(target: &str) -> Vec<String>
+{
+	let mut link_metadata = Vec::new();
+	let mut target_env_var = |key: &str| {
+		let kind = if env::var(ENV_KEY_HOST).is_ok_and(|host| {host == target}) {"HOST"} else {"TARGET"};
+		[
+			format!("{key}_{target}"),
+			format!("{key}_{}", target.replace('-', "_")),
+			format!("{kind}_{key}"),
+			key.to_string(),
+		].into_iter().find_map(|key| {
+			link_metadata.push(format!("cargo:rerun-if-env-changed={key}"));
+			env::var(key).ok()
+		})
+	};
+
+	let stdlib = match target_env_var("CXXSTDLIB") {
+		Some(stdlib) => stdlib,
+		None if target.ends_with("-msvc") => String::new(),
+		None if ["-apple-", "-freebsd", "-openbsd", "-aix", "-ohos", "-wasi", "-pauthtest"]
+			.iter()
+			.any(|os| {target.contains(os)}) => "c++".to_string(),
+		None if target.contains("-android") => "c++_shared".to_string(),
+		None => "stdc++".to_string(),
+	};
+	let is_static = target_env_var("CXXSTDLIB_STATIC")
+		.is_some_and(|value| {!matches!(value.as_str(), "" | "0" | "no" | "false")});
+	if stdlib.is_empty() {
+		return link_metadata;
+	}
+
+	let link_lib = if stdlib.contains('=') || !is_static {
+		stdlib
+	} else if target.starts_with("wasm32-") || target.contains("-apple-") {
+		format!("static={stdlib}")
+	} else {
+		format!("static:-bundle={stdlib}")
+	};
+	link_metadata.push(format!("cargo:rustc-link-lib={link_lib}"));
+
+	link_metadata
+}
+
+///
+/// Name the shared library of `lib_name` and `soversion` for `target`, and
+/// list the compiler driver arguments to link it.
+///
+/// The shared library is `lib<lib_name>.<soversion>.dylib` with the install
+/// name `@rpath/<filename>` on the Apple targets, or
+/// `lib<lib_name>.so.<soversion>` with the soname of its own filename on the
+/// other ELF targets.  The Windows targets are not supported.
+///
+fn shared_library_args(lib_name: &str, soversion: u32, target: &str)
+	-> Result<(String, Vec<String>), MldError>
+{
+	if target.contains("-windows") {
+		return Err(MldError::from(format!(
+			"shared library {lib_name} MUST be linked for an ELF or Mach-O target, not {target}")));
 	}
-	string += "]";
 
-	string
+	if target.contains("-apple-") {
+		let filename = format!("lib{lib_name}.{soversion}.dylib");
+		let args = vec![
+			"-dynamiclib".to_string(),
+			format!("-Wl,-install_name,@rpath/{filename}"),
+		];
+		Ok((filename, args))
+	} else {
+		let filename = format!("lib{lib_name}.so.{soversion}");
+		let args = vec![
+			"-shared".to_string(),
+			format!("-Wl,-soname,{filename}"),
+		];
+		Ok((filename, args))
+	}
 }
 
 ///
-/// The input file types.
+/// Check if the compiler driver compiles `source_file` as `language` by its
+/// extension, following [`SOURCE_EXTS`].
 ///
-#[derive(Debug, PartialEq)]
-enum FileType
+fn is_conventional_source(source_file: &Path, language: Language) -> bool
 {
-	/// A source file for [`cc`].
-	Source,
-	/// A header file for [`bindgen`].
-	Header,
-	/// An unsupported extension.
-	Unsupported(String),
+	source_file
+		.extension()
+		.and_then(OsStr::to_str)
+		.and_then(|ext| {SOURCE_EXTS.iter().find(|(x, _)| {*x == ext})})
+		.is_some_and(|&(_, conventional)| {conventional == language})
 }
 
-impl Display for FileType
+///
+/// Look up the compiler flag to compile the following sources as
+/// `language` regardless of their extensions, by MSVC if `msvc` or by a
+/// GNU compatible compiler otherwise.
+///
+/// Returns [`None`] if the compiler has no such flag for the language.
+///
+fn language_flag(language: Language, msvc: bool) -> Option<&'static str>
 {
-	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError>
-	{
-		match self {
-			FileType::Source => write!(f, "FileType: Source"),
-			FileType::Header => write!(f, "FileType: Header"),
-			FileType::Unsupported(ext) => write!(f, "FileType: Unsupported({})", ext),
-		}
-		
+	match (language, msvc) {
+		(Language::C, false) => Some("-xc"),
+		(Language::Cxx, false) => Some("-xc++"),
+		(Language::PreprocessedC, false) => Some("-xcpp-output"),
+		(Language::PreprocessedCxx, false) => Some("-xc++-cpp-output"),
+		(Language::Assembly, false) => Some("-xassembler"),
+		(Language::PreprocessedAssembly, false) => Some("-xassembler-with-cpp"),
+		(Language::C, true) => Some("/TC"),
+		(Language::Cxx, true) => Some("/TP"),
+		_ => None,
 	}
 }
+
+/// Look up the conventional language of an extension, or C if not listed.
+fn conventional_language(exts: &[(&str, Language)], ext: &str) -> Language
+{
+	exts.iter()
+		.find(|(x, _)| {*x == ext})
+		.map_or(Language::C, |&(_, language)| {language})
+}
+
+///
+/// A step run by [`Config::build`] before the input files are expanded.
+///
+enum PreBuildStep<'a>
+{
+	/// Build and run a host tool.
+	HostTool(HostTool<'a>),
+	/// Call a closure generating a file, identified by the name.
+	GenerateSource(String, GenerateSourceFn<'a>),
+}
+
+///
+/// The [`cc::Build`] for C++ of a library, created by [`Config::cc_build`]
+/// when the library has any C++ sources, or ahead.
+///
+enum CxxBuild<'h>
+{
+	/// The hook to create the [`cc::Build`] by.
+	Hook(CcBuildHookFn<'h>),
+	/// The [`cc::Build`] created ahead.
+	Build(Box<Build>),
+}
+
+///
+/// The backend configurations of a library, taken out of [`Config`] or
+/// [`Library`] during [`Config::build`].
+///
+struct LibraryBuilds<'h>
+{
+	/// The [`cc::Build`] for C++.
+	cxx: CxxBuild<'h>,
+	/// The hook on [`nasm_rs::Build`].
+	nasm: NasmBuildHookFn<'h>,
+}
+
+///
+/// The input files of a library expanded by [`Config::build`].
+///
+struct ExpandedInputFiles
+{
+	/// The source files and their languages.
+	source_files: Vec<(StdPathBuf, Language)>,
+	/// The directories of the source and header files, to be included.
+	input_dirs: Vec<StdPathBuf>,
+}
+
+///
+/// The state of the input file expansion shared across the libraries during
+/// [`Config::build`].
+///
+struct InputGlobs<'r>
+{
+	/// The absolute source root.
+	source_root: &'r Path,
+	/// The options of the glob expansion.
+	glob_matchoptions: MatchOptions,
+	/// The file type override patterns.
+	file_type_overrides: Vec<(Pattern, FileType)>,
+	/// The ignore files looked up so far.
+	ignore_files: IgnoreFiles,
+	/// The header files bound so far.
+	bound_headers: HashSet<StdPathBuf>,
+	/// The source files compiled into the libraries so far.
+	compiled_sources: HashSet<StdPathBuf>,
+	/// The header files and their languages to bind together by
+	/// [`Config::combined_binding`].
+	combined_headers: Vec<(StdPathBuf, Language)>,
+	/// The directories of the input files for [`Config::combined_binding`].
+	combined_input_dirs: Vec<StdPathBuf>,
+	/// The binding files to generate by [`Config::parallel_bindgen`].
+	bindgen_jobs: Vec<BindgenJob>,
+}
+
+///
+/// A binding file to generate, sent to a worker thread by
+/// [`Config::parallel_bindgen`].
+///
+struct BindgenJob
+{
+	/// The header file and the binding file to generate.
+	header_binding: HeaderBinding,
+	/// The language of the header file.
+	language: Language,
+	/// The header files bound, restricting the bindings if configured by
+	/// [`Config::restrict_bindings`].
+	headers: Vec<StdPathBuf>,
+	/// The directories of the input files, to be included.
+	input_dirs: Vec<StdPathBuf>,
+}
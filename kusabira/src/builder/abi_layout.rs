@@ -0,0 +1,474 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//!
+//! The ABI layout checks of the
+//! [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding files
+//! against the C compiler.
+//!
+//! Refer to [`super::Config::abi_layout_tests`] for the usage.
+//!
+//! The structs, the unions and the enums in a binding file are looked up by
+//! the tokens rather than the lines, as [`bindgen`] writes the bindings
+//! unformatted if `rustfmt` is not available.  Their C spellings, eg
+//! `struct foo` or the typedef name `foo`, are looked up in the bound header
+//! files in the same way, and the types not declared there are not checked.
+//!
+
+#![deny(missing_docs)]
+
+use std::collections::{HashMap, HashSet};
+use std::fs::read_to_string;
+use std::io::Result as IoResult;
+use std::path::Path;
+
+use syn::{Fields, Generics, Ident, Item, Visibility, parse_file};
+
+use super::bindings_mod::RUST_KEYWORDS;
+use super::pattern::normalize_path;
+use super::super::error::Error as MldError;
+
+/// The field name prefixes of the bindgen internals, which are not checked.
+static INTERNAL_FIELD_PREFIXES: [&str; 3] =
+[
+	"_bitfield_",
+	"__bindgen_",
+	"_bindgen_",
+];
+
+/// The field names of the placeholders in the opaque and empty types, whose
+/// layouts are not comparable with C.
+static PLACEHOLDER_FIELDS: [&str; 2] =
+[
+	"_unused",
+	"_address",
+];
+
+///
+/// The layout of a bound type to check.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) struct TypeLayout
+{
+	/// The Rust type name.
+	pub(super) rust_name: String,
+	/// The C type name, eg `struct foo`.
+	pub(super) c_name: String,
+	/// The Rust and C field name pairs.
+	pub(super) fields: Vec<(String, String)>,
+}
+
+///
+/// The C declaration kinds of the names, out of the header files.
+///
+#[derive(Debug, Default)]
+struct CDeclarations
+{
+	/// The tags, eg `foo` of `struct foo`, by the kind.
+	tags: Vec<(String, String)>,
+	/// The typedef names and the kinds of their types.
+	typedefs: HashMap<String, String>,
+}
+
+impl CDeclarations
+{
+	///
+	/// Scan the tokens of the header files for the tags and the typedef
+	/// names.
+	///
+	fn scan(tokens: &[String]) -> CDeclarations
+	{
+		let mut declarations = CDeclarations::default();
+		// The kind of the typedef being scanned and its nesting depth.
+		let mut typedef: Option<(String, usize)> = None;
+		let mut depth = 0usize;
+
+		for (i, token) in tokens.iter().enumerate() {
+			let next = tokens.get(i + 1).map(|next| {next.as_str()});
+			match token.as_str() {
+				kind @ ("struct" | "union" | "enum") => {
+					if let Some(tag) = next.filter(|next| {is_ident(next)}) {
+						declarations.tags.push((kind.to_string(), tag.to_string()));
+					}
+				},
+				"typedef" if typedef.is_none() => {
+					let kind = tokens[i + 1..]
+						.iter()
+						.find(|token| {!matches!(token.as_str(), "const" | "volatile")})
+						.cloned()
+						.unwrap_or_default();
+					typedef = Some((kind, depth));
+				},
+				"{" | "(" | "[" => depth += 1,
+				"}" | ")" | "]" => depth = depth.saturating_sub(1),
+				";" if typedef.as_ref().is_some_and(|(_, typedef_depth)| {*typedef_depth == depth}) => {
+					typedef = None;
+				},
+				name if is_ident(name) && matches!(next, Some(";" | "," | "[")) => {
+					if let Some((kind, typedef_depth)) = &typedef {
+						if *typedef_depth == depth {
+							declarations.typedefs.insert(name.to_string(), kind.clone());
+						}
+					}
+				},
+				_ => {},
+			}
+		}
+
+		declarations
+	}
+
+	///
+	/// Spell the type `name` of one of `kinds` in C, by the tag or by the
+	/// typedef name.
+	///
+	fn spell(&self, name: &str, kinds: &[&str]) -> Option<String>
+	{
+		if let Some((kind, _)) = self.tags
+			.iter()
+			.find(|(kind, tag)| {tag == name && kinds.contains(&kind.as_str())}) {
+			return Some(format!("{kind} {name}"));
+		}
+
+		self.typedefs
+			.get(name)
+			.filter(|kind| {kinds.contains(&kind.as_str())})
+			.map(|_| {name.to_string()})
+	}
+}
+
+///
+/// Look up the structs, the unions and the enums in `binding` to check,
+/// declared in the header files of `headers`.
+///
+/// The binding file is parsed by [`syn`], so that the types and the fields
+/// do not depend on the text layout of bindgen.  A type is not checked if it
+/// is anonymous, generic, opaque or empty.  The fields of the bindgen
+/// internals, such as the bitfields, are not checked.
+///
+pub(super) fn parse_binding(binding: &str, headers: &[String]) -> Result<Vec<TypeLayout>, MldError>
+{
+	let header_tokens: Vec<String> = headers
+		.iter()
+		.flat_map(|header| {tokenize(header)})
+		.collect();
+	let declarations = CDeclarations::scan(&header_tokens);
+	let binding = parse_file(binding).map_err(
+		|e| {MldError::from(format!("binding file MUST be parsed for the ABI layout tests: {e}"))})?;
+	let mut layouts = Vec::new();
+
+	for item in &binding.items {
+		let (name, c_name, fields) = match item {
+			Item::Struct(item) if is_checked(&item.vis, &item.ident, &item.generics) => {
+				let name = item.ident.to_string();
+				match &item.fields {
+					Fields::Named(fields) => {
+						let c_name = declarations.spell(&name, &["struct"]);
+						(name, c_name, Some(&fields.named))
+					},
+					// A newtype enum.
+					Fields::Unnamed(_) => {
+						let c_name = declarations.spell(&name, &["enum"]);
+						(name, c_name, None)
+					},
+					Fields::Unit => {
+						if let Some(c_name) = declarations.spell(&name, &["struct"]) {
+							return Err(MldError::from(format!(
+								"binding of {c_name} MUST have the fields for the ABI layout tests")));
+						}
+						continue;
+					},
+				}
+			},
+			Item::Union(item) if is_checked(&item.vis, &item.ident, &item.generics) => {
+				let name = item.ident.to_string();
+				let c_name = declarations.spell(&name, &["union"]);
+				(name, c_name, Some(&item.fields.named))
+			},
+			Item::Enum(item) if is_checked(&item.vis, &item.ident, &item.generics) => {
+				let name = item.ident.to_string();
+				let c_name = declarations.spell(&name, &["enum"]);
+				(name, c_name, None)
+			},
+			// A constified enum.
+			Item::Type(item) if is_checked(&item.vis, &item.ident, &item.generics) => {
+				let name = item.ident.to_string();
+				let c_name = declarations.spell(&name, &["enum"]);
+				(name, c_name, None)
+			},
+			_ => continue,
+		};
+		let Some(c_name) = c_name else {
+			continue;
+		};
+		let fields = match fields {
+			Some(fields) => {
+				let fields: Vec<String> = fields
+					.iter()
+					.filter_map(|field| {field.ident.as_ref()})
+					.map(|ident| {ident.to_string()})
+					.collect();
+				if fields.is_empty() {
+					return Err(MldError::from(format!(
+						"binding of {c_name} MUST have the fields for the ABI layout tests")));
+				}
+				if fields.iter().any(|field| {PLACEHOLDER_FIELDS.contains(&field.as_str())}) {
+					continue;
+				}
+				fields
+					.into_iter()
+					.filter(|field| {
+						!INTERNAL_FIELD_PREFIXES.iter().any(|prefix| {field.starts_with(prefix)})
+					})
+					.map(|field| {
+						let c_field = c_field_name(&field);
+						(field, c_field)
+					})
+					.collect()
+			},
+			None => Vec::new(),
+		};
+		layouts.push(TypeLayout {
+			rust_name: name,
+			c_name,
+			fields,
+		});
+	}
+
+	Ok(layouts)
+}
+
+///
+/// Check if the bound type `ident` is public, not generic and not of the
+/// bindgen internals.
+///
+fn is_checked(vis: &Visibility, ident: &Ident, generics: &Generics) -> bool
+{
+	let name = ident.to_string();
+	matches!(vis, Visibility::Public(_))
+		&& generics.params.is_empty()
+		&& !name.starts_with("__")
+		&& !name.contains("__bindgen")
+}
+
+///
+/// Map the Rust field name back to the C one, which bindgen suffixes by `_`
+/// if it is a Rust keyword.
+///
+fn c_field_name(field: &str) -> String
+{
+	let field = field.strip_prefix("r#").unwrap_or(field);
+	match field.strip_suffix('_') {
+		Some(stem) if RUST_KEYWORDS.contains(&stem) => stem.to_string(),
+		_ => field.to_string(),
+	}
+}
+
+///
+/// Render the C program printing the layouts, one per line as
+/// `size <type> <bytes>`, `align <type> <bytes>` and
+/// `offset <type> <field> <bytes>` by the Rust names.
+///
+pub(super) fn render_checker(header: &Path, layouts: &[TypeLayout]) -> String
+{
+	let mut rendered = String::from("/* Generated by kusabira.  DO NOT EDIT. */\n\n");
+	rendered += "#include <stddef.h>\n#include <stdio.h>\n\n";
+	rendered += &format!("#include {:?}\n\n", header.to_string_lossy());
+	rendered += "#if defined(__cplusplus)\n";
+	rendered += "#define KUSABIRA_ALIGNOF(t) alignof(t)\n";
+	rendered += "#elif defined(__STDC_VERSION__) && __STDC_VERSION__ >= 201112L\n";
+	rendered += "#define KUSABIRA_ALIGNOF(t) _Alignof(t)\n";
+	rendered += "#elif defined(_MSC_VER)\n";
+	rendered += "#define KUSABIRA_ALIGNOF(t) __alignof(t)\n";
+	rendered += "#else\n";
+	rendered += "#define KUSABIRA_ALIGNOF(t) offsetof(struct {char c; t x;}, x)\n";
+	rendered += "#endif\n\n";
+	rendered += "int main(void)\n{\n";
+	for layout in layouts {
+		let TypeLayout {rust_name, c_name, fields} = layout;
+		rendered += &format!("\tprintf(\"size {rust_name} %lu\\n\", (unsigned long)sizeof({c_name}));\n");
+		rendered += &format!("\tprintf(\"align {rust_name} %lu\\n\", (unsigned long)KUSABIRA_ALIGNOF({c_name}));\n");
+		for (rust_field, c_field) in fields {
+			rendered += &format!(
+				"\tprintf(\"offset {rust_name} {rust_field} %lu\\n\", (unsigned long)offsetof({c_name}, {c_field}));\n");
+		}
+	}
+	rendered += "\treturn 0;\n}\n";
+
+	rendered
+}
+
+///
+/// Render the Rust tests comparing the layouts of the Rust types with the
+/// ones printed by the `checker` program.
+///
+/// The tests are `include!`d into the module of the binding file, so that
+/// the types are in the scope.
+///
+pub(super) fn render_tests(checker: &Path, layouts: &[TypeLayout]) -> String
+{
+	let mut rendered = String::from("// Generated by kusabira.  DO NOT EDIT.\n\n");
+	rendered += "fn kusabira_abi_layout(key: &str) -> usize\n{\n";
+	rendered += "\tstatic LAYOUTS: ::std::sync::OnceLock<String> = ::std::sync::OnceLock::new();\n";
+	rendered += "\tlet layouts = LAYOUTS.get_or_init(|| {\n";
+	rendered += &format!("\t\tlet output = ::std::process::Command::new({:?})\n", checker.to_string_lossy());
+	rendered += "\t\t\t.output()\n";
+	rendered += "\t\t\t.expect(\"ABI layout checker MUST run\");\n";
+	rendered += "\t\tassert!(output.status.success(), \"ABI layout checker MUST exit successfully\");\n";
+	rendered += "\t\tString::from_utf8(output.stdout).expect(\"ABI layouts MUST be UTF-8\")\n";
+	rendered += "\t});\n";
+	rendered += "\tlayouts\n";
+	rendered += "\t\t.lines()\n";
+	rendered += "\t\t.find_map(|line| {line.strip_prefix(key)?.strip_prefix(' ')?.parse().ok()})\n";
+	rendered += "\t\t.unwrap_or_else(|| {panic!(\"ABI layout {key} MUST be printed\")})\n";
+	rendered += "}\n";
+	for layout in layouts {
+		let TypeLayout {rust_name, c_name, fields} = layout;
+		rendered += &format!("\n#[test]\nfn kusabira_abi_layout_{rust_name}()\n{{\n");
+		rendered += &format!(
+			"\tassert_eq!(::std::mem::size_of::<{rust_name}>(), kusabira_abi_layout(\"size {rust_name}\"), \"size of {c_name}\");\n");
+		rendered += &format!(
+			"\tassert_eq!(::std::mem::align_of::<{rust_name}>(), kusabira_abi_layout(\"align {rust_name}\"), \"alignment of {c_name}\");\n");
+		if !fields.is_empty() {
+			rendered += &format!("\tlet uninit = ::std::mem::MaybeUninit::<{rust_name}>::uninit();\n");
+			rendered += "\tlet ptr = uninit.as_ptr();\n";
+		}
+		for (rust_field, c_field) in fields {
+			rendered += &format!(
+				"\tassert_eq!(unsafe {{::std::ptr::addr_of!((*ptr).{rust_field}) as usize - ptr as usize}}, kusabira_abi_layout(\"offset {rust_name} {rust_field}\"), \"offset of {c_name}::{c_field}\");\n");
+		}
+		rendered += "}\n";
+	}
+
+	rendered
+}
+
+///
+/// Read the header file and the ones included by `#include "..."`
+/// recursively, as the sources to look up the C declarations in.
+///
+/// The included files not found relative to the including file, such as
+/// the ones in the include directories, are skipped.
+///
+pub(super) fn read_headers(header: &Path) -> IoResult<Vec<String>>
+{
+	let mut headers = Vec::new();
+	let mut visited = HashSet::from([normalize_path(header)]);
+	let mut pending = vec![normalize_path(header)];
+
+	while let Some(path) = pending.pop() {
+		let source = read_to_string(&path)?;
+		let dir = path.parent().unwrap_or(Path::new(""));
+		for line in source.lines() {
+			let included = line
+				.trim_start()
+				.strip_prefix('#')
+				.and_then(|directive| {directive.trim_start().strip_prefix("include")})
+				.and_then(|included| {included.trim().strip_prefix('"')?.split('"').next()});
+			if let Some(included) = included {
+				let included = normalize_path(&dir.join(included));
+				if included.is_file() && visited.insert(included.clone()) {
+					pending.push(included);
+				}
+			}
+		}
+		headers.push(source);
+	}
+
+	Ok(headers)
+}
+
+///
+/// Split the C source into the identifiers, the numbers and the
+/// punctuations, skipping the comments and the literals.
+///
+fn tokenize(source: &str) -> Vec<String>
+{
+	let chars: Vec<char> = source.chars().collect();
+	let mut tokens = Vec::new();
+
+	let mut i = 0;
+	while i < chars.len() {
+		let c = chars[i];
+		if c.is_whitespace() {
+			i += 1;
+		} else if c == '/' && chars.get(i + 1) == Some(&'/') {
+			while i < chars.len() && chars[i] != '\n' {
+				i += 1;
+			}
+		} else if c == '/' && chars.get(i + 1) == Some(&'*') {
+			i += 2;
+			while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+				i += 1;
+			}
+			i += 2;
+		} else if c == '"' || c == '\'' {
+			i += 1;
+			while i < chars.len() && chars[i] != c {
+				if chars[i] == '\\' {
+					i += 1;
+				}
+				i += 1;
+			}
+			i += 1;
+		} else if c.is_alphanumeric() || c == '_' {
+			let start = i;
+			while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+				i += 1;
+			}
+			tokens.push(chars[start..i].iter().collect());
+		} else {
+			tokens.push(c.to_string());
+			i += 1;
+		}
+	}
+
+	tokens
+}
+
+/// Check if `token` is an identifier.
+fn is_ident(token: &str) -> bool
+{
+	token.starts_with(|c: char| {c.is_alphabetic() || c == '_'})
+}
@@ -0,0 +1,358 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//!
+//! The content-addressed cache of the
+//! [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding files.
+//!
+//! Refer to [`super::Config::bindgen_cache`] for the usage.
+//!
+//! The cache directory holds two kinds of the files:
+//! * *Manifest* `<args key>.deps`, named by the hash of the `bindgen` and
+//!   `libclang` versions, the target and the command line flags of
+//!   [`bindgen::Builder`], which lists the files and the environment
+//!   variables read by the last generation.
+//! * *Binding* `<content key>.rs`, named by the hash of the args key and the
+//!   contents of the files and the environment variables in the manifest.
+//!
+//! As in `ccache`, the transitive includes of a header file are not known
+//! until [`bindgen::Builder::generate`] runs, so the manifest records them
+//! for the next build.
+//!
+
+#![deny(missing_docs)]
+
+#[cfg(not(test))]
+use bindgen::{Builder, clang_version};
+#[cfg(test)]
+use super::tests::busshi::bindgen_builder::{Builder, clang_version};
+use bindgen::callbacks::ParseCallbacks;
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs::{create_dir_all, read, read_to_string, rename, write};
+use std::panic::catch_unwind;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Arc, Mutex, OnceLock};
+use super::super::error::Error as MldError;
+
+/// The `bindgen` version resolved in `Cargo.lock`, told by the build
+/// script, or empty if unknown.
+static RESOLVED_BINDGEN_VERSION: &str = env!("KUSABIRA_BINDGEN_VERSION");
+
+/// The header comment of the bindings, which `bindgen` completes by its
+/// version and ` */`.
+static BINDGEN_HEADER_COMMENT: &str = "/* automatically generated by rust-bindgen ";
+
+/// The `bindgen` version hashed into the args key, found once by
+/// [`bindgen_version`].
+static BINDGEN_VERSION: OnceLock<Option<String>> = OnceLock::new();
+
+/// The `libclang` version hashed into the args key, probed once by
+/// [`probed_clang_version`].
+static CLANG_VERSION: OnceLock<String> = OnceLock::new();
+
+/// The manifest line prefix of a file.
+static MANIFEST_FILE: &str = "file ";
+
+/// The manifest line prefix of an environment variable.
+static MANIFEST_ENV: &str = "env ";
+
+///
+/// The cache entries of a binding file, selected by the
+/// [`bindgen::Builder`] configuration.
+///
+pub(super) struct BindgenCache
+{
+	/// The cache directory.
+	dir: PathBuf,
+	/// The hex digest of the `bindgen` and `libclang` versions, the target
+	/// and the command line flags.
+	args_key: String,
+}
+
+///
+/// The files and the environment variables read by
+/// [`bindgen::Builder::generate`], recorded for [`BindgenCache::store`].
+///
+#[derive(Clone, Debug, Default)]
+pub(super) struct Dependencies
+{
+	/// The files, including the header file itself.
+	pub(super) files: Vec<PathBuf>,
+	/// The environment variable names.
+	pub(super) env_vars: Vec<String>,
+}
+
+///
+/// The [`ParseCallbacks`] recording the files and the environment variables
+/// read by [`bindgen::Builder::generate`] into the shared [`Dependencies`].
+///
+#[derive(Debug)]
+pub(super) struct DependencyRecorder(pub(super) Arc<Mutex<Dependencies>>);
+
+impl ParseCallbacks for DependencyRecorder
+{
+	fn include_file(&self, filename: &str)
+	{
+		let mut dependencies = self.0.lock().expect("dependencies MUST NOT be poisoned");
+		let filename = PathBuf::from(filename);
+		if !dependencies.files.contains(&filename) {
+			dependencies.files.push(filename);
+		}
+	}
+
+	fn read_env_var(&self, key: &str)
+	{
+		let mut dependencies = self.0.lock().expect("dependencies MUST NOT be poisoned");
+		if !dependencies.env_vars.iter().any(|env_var| {env_var == key}) {
+			dependencies.env_vars.push(key.to_string());
+		}
+	}
+}
+
+impl BindgenCache
+{
+	///
+	/// Select the cache entries in `dir` by the command line `flags` of
+	/// [`bindgen::Builder`] and the `target` triple.
+	///
+	/// Returns [`None`] to bypass the cache if the `bindgen` version is
+	/// unknown.
+	///
+	pub(super) fn new(dir: &Path, flags: &[String], target: Option<&str>) -> Option<BindgenCache>
+	{
+		let bindgen_version = bindgen_version()?;
+		let mut hasher = Sha256::new();
+		for field in [env!("CARGO_PKG_VERSION"), bindgen_version, probed_clang_version(), target.unwrap_or_default()]
+			.into_iter()
+			.chain(flags.iter().map(String::as_str)) {
+			hasher.update(field);
+			hasher.update([0]);
+		}
+
+		Some(BindgenCache {
+			dir: dir.to_path_buf(),
+			args_key: hex(&hasher.finalize()),
+		})
+	}
+
+	///
+	/// Copy the cached binding file into `binding_file` if the dependencies
+	/// recorded in the manifest are unchanged, rewriting it only when its
+	/// contents change.
+	///
+	/// Returns the dependencies on a hit, or [`None`] on a miss.
+	///
+	pub(super) fn restore(&self, binding_file: &Path) -> Result<Option<Dependencies>, MldError>
+	{
+		let Ok(manifest) = read_to_string(self.manifest_path()) else {
+			return Ok(None);
+		};
+		let mut dependencies = Dependencies::default();
+		for line in manifest.lines() {
+			if let Some(file) = line.strip_prefix(MANIFEST_FILE) {
+				dependencies.files.push(PathBuf::from(file));
+			} else if let Some(env_var) = line.strip_prefix(MANIFEST_ENV) {
+				dependencies.env_vars.push(env_var.to_string());
+			} else {
+				return Ok(None);
+			}
+		}
+		let Ok(contents) = read(self.binding_path(&dependencies)) else {
+			return Ok(None);
+		};
+		if read(binding_file).ok().as_ref() != Some(&contents) {
+			write(binding_file, contents)?;
+		}
+
+		Ok(Some(dependencies))
+	}
+
+	///
+	/// Store the generated `binding_file` and the manifest of its
+	/// `dependencies`.
+	///
+	/// The files are written by the rename, so that the builds sharing the
+	/// cache directory do not read them partially written.
+	///
+	pub(super) fn store(&self, binding_file: &Path, dependencies: &Dependencies)
+		-> Result<(), MldError>
+	{
+		let mut manifest = String::new();
+		for file in &dependencies.files {
+			let file = file.to_str().ok_or_else(
+				|| MldError::from(format!("dependency {} MUST make a valid string", file.display())))?;
+			if file.contains('\n') {
+				return Err(MldError::from(format!("dependency {file:?} MUST NOT contain a newline")));
+			}
+			manifest += &format!("{MANIFEST_FILE}{file}\n");
+		}
+		for env_var in &dependencies.env_vars {
+			manifest += &format!("{MANIFEST_ENV}{env_var}\n");
+		}
+
+		create_dir_all(&self.dir)?;
+		write_by_rename(&self.binding_path(dependencies), &read(binding_file)?)?;
+		write_by_rename(&self.manifest_path(), manifest.as_bytes())?;
+
+		Ok(())
+	}
+
+	/// The path of the manifest.
+	fn manifest_path(&self) -> PathBuf
+	{
+		self.dir.join(format!("{}.deps", self.args_key))
+	}
+
+	///
+	/// The path of the binding file, named by the hash of the args key and
+	/// the current contents of the `dependencies`.
+	///
+	/// A missing file or environment variable is hashed as such, so that its
+	/// appearance changes the hash.
+	///
+	fn binding_path(&self, dependencies: &Dependencies) -> PathBuf
+	{
+		let mut hasher = Sha256::new();
+		hasher.update(&self.args_key);
+		for file in &dependencies.files {
+			hasher.update([0]);
+			hasher.update(file.to_string_lossy().as_bytes());
+			match read(file) {
+				Ok(contents) => {
+					hasher.update([1]);
+					hasher.update((contents.len() as u64).to_le_bytes());
+					hasher.update(contents);
+				},
+				Err(_) => hasher.update([0]),
+			}
+		}
+		for env_var in &dependencies.env_vars {
+			hasher.update([0]);
+			hasher.update(env_var);
+			match env::var_os(env_var) {
+				Some(value) => {
+					hasher.update([1]);
+					hasher.update(value.to_string_lossy().as_bytes());
+				},
+				None => hasher.update([0]),
+			}
+		}
+
+		self.dir.join(format!("{}.rs", hex(&hasher.finalize())))
+	}
+}
+
+/// Write `contents` into a temporary file and rename it to `path`.
+fn write_by_rename(path: &Path, contents: &[u8]) -> Result<(), MldError>
+{
+	let mut temp_path = path.as_os_str().to_os_string();
+	temp_path.push(format!(".{}.tmp", process::id()));
+	write(&temp_path, contents)?;
+	rename(&temp_path, path)?;
+
+	Ok(())
+}
+
+///
+/// Find the `bindgen` version once per build script.
+///
+/// The version is the one resolved in `Cargo.lock` if the build script of
+/// `kusabira` has found it.  Otherwise, an empty header is bound to read the
+/// version out of the header comment of the bindings, which is [`None`] if
+/// not readable.
+///
+fn bindgen_version() -> Option<&'static str>
+{
+	BINDGEN_VERSION.get_or_init(|| {
+		if !RESOLVED_BINDGEN_VERSION.is_empty() {
+			return Some(RESOLVED_BINDGEN_VERSION.to_string());
+		}
+		catch_unwind(|| {
+			Builder::default()
+				.header_contents("kusabira_bindgen_version.h", "")
+				.generate()
+				.map(|bindings| {bindings.to_string()})
+		})
+			.ok()
+			.and_then(Result::ok)
+			.and_then(|bindings| {parse_bindgen_version(&bindings)})
+	}).as_deref()
+}
+
+///
+/// Read the `bindgen` version out of the header comment of `bindings`.
+///
+pub(super) fn parse_bindgen_version(bindings: &str) -> Option<String>
+{
+	bindings
+		.lines()
+		.next()
+		.and_then(|line| {line.strip_prefix(BINDGEN_HEADER_COMMENT)})
+		.and_then(|line| {line.strip_suffix(" */")})
+		.filter(|version| {!version.is_empty()})
+		.map(str::to_string)
+}
+
+///
+/// Probe the `libclang` version once per build script.
+///
+/// The version is empty if `libclang` cannot be loaded, so that such a
+/// build does not share the cache entries with the builds by `libclang`.
+///
+fn probed_clang_version() -> &'static str
+{
+	CLANG_VERSION.get_or_init(|| {
+		catch_unwind(clang_version)
+			.map(|clang_version| {clang_version.full})
+			.unwrap_or_default()
+	})
+}
+
+/// Format `bytes` in the lowercase hex digits.
+fn hex(bytes: &[u8]) -> String
+{
+	bytes.iter().map(|byte| {format!("{byte:02x}")}).collect()
+}
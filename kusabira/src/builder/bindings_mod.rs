@@ -0,0 +1,165 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//!
+//! The Rust module declaring one submodule per
+//! [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding file,
+//! generated by [`super::Config::build`].
+//!
+//! Refer to [`crate::include_bindings`] for the usage.
+//!
+
+#![deny(missing_docs)]
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::HeaderBinding;
+use super::super::error::Error as MldError;
+
+/// The lints allowed in each submodule, as the bound C identifiers do not
+/// follow the Rust naming conventions.
+static ALLOWED_LINTS: [&str; 4] =
+[
+	"non_upper_case_globals",
+	"non_camel_case_types",
+	"non_snake_case",
+	"missing_docs",
+];
+
+/// The Rust keywords, which MUST NOT be used as the submodule names as they
+/// are.
+pub(super) static RUST_KEYWORDS: [&str; 51] =
+[
+	"Self", "abstract", "as", "async", "await", "become", "box", "break",
+	"const", "continue", "crate", "do", "dyn", "else", "enum", "extern",
+	"false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop",
+	"macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+	"return", "self", "static", "struct", "super", "trait", "true", "try",
+	"type", "typeof", "unsafe", "unsized", "use", "virtual", "where", "while",
+];
+
+///
+/// Make the submodule name out of the filename stem of a binding file.
+///
+/// The characters other than the ASCII alphanumerics are replaced by `_`.
+/// A leading digit and a keyword are suffixed or prefixed by `_` so that the
+/// name makes a valid identifier.
+///
+pub(super) fn module_name(binding_file: &Path) -> String
+{
+	let stem = binding_file
+		.file_stem()
+		.map(|stem| {stem.to_string_lossy()})
+		.unwrap_or_default();
+	let mut name: String = stem
+		.chars()
+		.map(|c| {if c.is_ascii_alphanumeric() {c} else {'_'}})
+		.collect();
+	if name.is_empty() || name.starts_with(|c: char| {c.is_ascii_digit()}) {
+		name.insert(0, '_');
+	}
+	if name == "_" || RUST_KEYWORDS.contains(&name.as_str()) {
+		name.push('_');
+	}
+
+	name
+}
+
+///
+/// Check that the binding files make distinct submodule names.
+///
+/// [`module_name`] maps distinct filenames to the same name, eg
+/// `foo-bar.in` and `foo_bar.in`, which would declare the submodule twice.
+///
+pub(super) fn check_module_names<'a, IT>(header_bindings: IT) -> Result<(), MldError>
+	where IT: Iterator<Item = &'a HeaderBinding>
+{
+	let mut header_files = HashMap::new();
+	for header_binding in header_bindings {
+		let name = module_name(&header_binding.rust_binding_file);
+		if let Some(header_file) = header_files.insert(name.clone(), &header_binding.input_header_file) {
+			return Err(MldError::from(
+				format!("bindings of {} and {} MUST have distinct module names, not both {name}",
+					header_file.display(), header_binding.input_header_file.display())));
+		}
+	}
+
+	Ok(())
+}
+
+///
+/// Render the module declaring one submodule per binding file.
+///
+/// Each submodule `include!`s the binding file by the absolute path, so that
+/// the module works wherever the output directory is, along with the ABI
+/// layout tests under `#[cfg(test)]` if generated.
+///
+pub(super) fn render(header_bindings: &[&HeaderBinding]) -> String
+{
+	let mut rendered = String::from("// Generated by kusabira.  DO NOT EDIT.\n");
+
+	for header_binding in header_bindings {
+		let header_filename = header_binding.input_header_file
+			.file_name()
+			.map(|filename| {filename.to_string_lossy()})
+			.unwrap_or_default();
+		rendered += &format!("\n/// The bindings of `{header_filename}`.\n");
+		rendered += &format!("pub mod {}\n{{\n",
+			module_name(&header_binding.rust_binding_file));
+		for lint in ALLOWED_LINTS {
+			rendered += &format!("\t#![allow({lint})]\n");
+		}
+		rendered += &format!("\n\tinclude!({:?});\n",
+			header_binding.rust_binding_file.to_string_lossy());
+		if let Some(abi_layout_test_file) = &header_binding.abi_layout_test_file {
+			rendered += "\n\t#[cfg(test)]\n\tmod abi_layout_tests\n\t{\n\t\tuse super::*;\n\n";
+			rendered += &format!("\t\tinclude!({:?});\n\t}}\n",
+				abi_layout_test_file.to_string_lossy());
+		}
+		rendered += "}\n";
+	}
+
+	rendered
+}
@@ -0,0 +1,449 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//!
+//! The configuration headers generated by [`super::Config::build`], the
+//! equivalent of `configure_file` of CMake and `AC_CONFIG_HEADERS` of
+//! Autoconf.
+//!
+//! Refer to [`super::Config::add_config_header`] for the usage.
+//!
+
+#![deny(missing_docs)]
+
+use std::env;
+use std::fs::read_to_string;
+use std::path::Path;
+
+use super::super::error::Error as MldError;
+
+/// The environment variable name [`Cargo`](https://doc.rust-lang.org/cargo/)
+/// configures the target pointer width.
+static ENV_KEY_CARGO_CFG_TARGET_POINTER_WIDTH: &str = "CARGO_CFG_TARGET_POINTER_WIDTH";
+
+/// The environment variable name [`Cargo`](https://doc.rust-lang.org/cargo/)
+/// configures the target endianness.
+static ENV_KEY_CARGO_CFG_TARGET_ENDIAN: &str = "CARGO_CFG_TARGET_ENDIAN";
+
+/// The environment variable name [`Cargo`](https://doc.rust-lang.org/cargo/)
+/// configures the target operating system.
+static ENV_KEY_CARGO_CFG_TARGET_OS: &str = "CARGO_CFG_TARGET_OS";
+
+/// The constants evaluated as false by `#cmakedefine`, compared
+/// case-insensitively.  A value ending with `-NOTFOUND` is also false.
+static CMAKE_FALSE_CONSTANTS: [&str; 8] = ["", "0", "OFF", "NO", "FALSE", "N", "IGNORE", "NOTFOUND"];
+
+///
+/// A C configuration header generated in the output directory by
+/// [`super::Config::build`].
+///
+/// The header is generated either from a template or from the configured
+/// values alone.  A template follows the syntax of `configure_file` of CMake:
+///
+/// * `#cmakedefine VAR ...` becomes `#define VAR ...` if `VAR` is true, or
+///   `/* #undef VAR */` otherwise.  A value is true unless unset, empty, `0`,
+///   `OFF`, `NO`, `FALSE`, `N`, `IGNORE`, `NOTFOUND` or ending with
+///   `-NOTFOUND`, case-insensitively.
+/// * `#cmakedefine01 VAR` becomes `#define VAR 1` if `VAR` is true, or
+///   `#define VAR 0` otherwise.
+/// * `@VAR@` and `${VAR}` are replaced by the value of `VAR`, or removed if
+///   unset.
+///
+/// Without a template, each value is written as `#define VAR value`, or
+/// `/* #undef VAR */` if unset, in the order configured.
+///
+/// All of the configuration methods return `self` by the value, so the method
+/// calls can be chained.
+///
+/// # Example
+/// ```
+/// use kusabira::builder::{Config, ConfigHeader};
+///
+/// let config = Config::default()
+/// 	.lib_name("foo")
+/// 	.input_file("src/*.c")
+/// 	.add_config_header(ConfigHeader::new("config.h")
+/// 		.template("src/config.h.in")
+/// 		.target_properties()
+/// 		.set("FOO_VERSION", "\"1.0.0\"")
+/// 		.set_bool("HAVE_FOO_THREADS", true));
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct ConfigHeader
+{
+	pub(super) filename: String,
+	template: Option<String>,
+	values: Vec<(String, Option<String>)>,
+	target_properties: bool,
+	probes: bool,
+}
+
+impl ConfigHeader
+{
+	///
+	/// Create the configuration header of the given filename.
+	///
+	/// The filename is relative to the output directory, which is added as
+	/// an include directory.  A filename with a directory, eg `foo/config.h`,
+	/// is included as such.
+	///
+	/// The configuration header starts with no template and no values.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::ConfigHeader;
+	///
+	/// let config_header = ConfigHeader::new("config.h");
+	/// ```
+	///
+	pub fn new(filename: &str) -> ConfigHeader
+	{
+		ConfigHeader {
+			filename: filename.to_string(),
+			template: None,
+			values: Vec::new(),
+			target_properties: false,
+			probes: false,
+		}
+	}
+
+	///
+	/// Set the template file, relative to the source root of
+	/// [`super::Config`].
+	///
+	/// The template is reported by `cargo:rerun-if-changed`.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::ConfigHeader;
+	///
+	/// let config_header = ConfigHeader::new("config.h")
+	/// 	.template("src/config.h.in");
+	/// ```
+	///
+	pub fn template(mut self, template: &str) -> Self
+	{
+		self.template = Some(template.to_string());
+		self
+	}
+
+	///
+	/// Set a value.
+	///
+	/// The value is written as it is, so a C string literal MUST be quoted.
+	/// An empty value defines the macro without a value.  A value set later
+	/// overrides the earlier one of the same name.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::ConfigHeader;
+	///
+	/// let config_header = ConfigHeader::new("config.h")
+	/// 	.set("FOO_VERSION", "\"1.0.0\"")
+	/// 	.set("FOO_MAX_PLUGINS", "16");
+	/// ```
+	///
+	pub fn set(mut self, name: &str, value: &str) -> Self
+	{
+		self.values.push((name.to_string(), Some(value.to_string())));
+		self
+	}
+
+	///
+	/// Set a boolean value.
+	///
+	/// `true` is the value `1`, while `false` unsets the value.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::ConfigHeader;
+	///
+	/// let config_header = ConfigHeader::new("config.h")
+	/// 	.set_bool("HAVE_FOO_THREADS", true)
+	/// 	.set_bool("FOO_DEBUG", cfg!(debug_assertions));
+	/// ```
+	///
+	pub fn set_bool(mut self, name: &str, value: bool) -> Self
+	{
+		self.values.push((name.to_string(), value.then(|| {"1".to_string()})));
+		self
+	}
+
+	///
+	/// Set the values of the target properties given by
+	/// [`Cargo`](https://doc.rust-lang.org/cargo/).
+	///
+	/// The values are taken out of the `CARGO_CFG_TARGET_*` environment
+	/// variables during [`super::Config::build`], before the ones configured
+	/// by [`ConfigHeader::set`] and [`ConfigHeader::set_bool`] so that they
+	/// can be overridden.
+	///
+	/// | Name | Value |
+	/// |------|-------|
+	/// | `TARGET_POINTER_WIDTH` | The pointer width in bits, eg `64` |
+	/// | `SIZEOF_VOID_P` | The pointer width in bytes, eg `8` |
+	/// | `WORDS_BIGENDIAN` | `1` on a big-endian target, unset otherwise |
+	/// | `TARGET_OS_<OS>` | `1`, where `<OS>` is the target OS in the upper case, eg `TARGET_OS_LINUX` |
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::ConfigHeader;
+	///
+	/// let config_header = ConfigHeader::new("config.h")
+	/// 	.target_properties();
+	/// ```
+	///
+	pub fn target_properties(mut self) -> Self
+	{
+		self.target_properties = true;
+		self
+	}
+
+	///
+	/// Set the results of the probes configured by
+	/// [`super::Config::add_probe`].
+	///
+	/// The results are set after the target properties and before the
+	/// values configured by [`ConfigHeader::set`] and
+	/// [`ConfigHeader::set_bool`] so that they can be overridden.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::ConfigHeader;
+	///
+	/// let config_header = ConfigHeader::new("config.h")
+	/// 	.target_properties()
+	/// 	.probes();
+	/// ```
+	///
+	pub fn probes(mut self) -> Self
+	{
+		self.probes = true;
+		self
+	}
+
+	///
+	/// Render the configuration header.
+	///
+	/// The template, if any, is resolved against `source_root`.
+	/// `probe_values` are the results of the probes.
+	///
+	pub(super) fn render(&self, source_root: &Path, probe_values: &[(String, Option<String>)])
+		-> Result<String, MldError>
+	{
+		let mut values = Vec::new();
+		if self.target_properties {
+			values.extend(target_property_values()?);
+		}
+		if self.probes {
+			values.extend(probe_values.iter().cloned());
+		}
+		values.extend(self.values.iter().cloned());
+
+		match &self.template {
+			Some(template) => {
+				let template_path = source_root.join(template);
+				println!("cargo:rerun-if-changed={}", template_path.display());
+				let template = read_to_string(&template_path)?;
+				Ok(render_template(&template, &values))
+			},
+			None => Ok(render_values(&self.filename, &values)),
+		}
+	}
+}
+
+/// Get the values of the target properties.
+fn target_property_values() -> Result<Vec<(String, Option<String>)>, MldError>
+{
+	let cargo_cfg = |key: &str| -> Result<String, MldError>
+	{
+		env::var(key).map_err(|_| {
+			MldError::from(format!("{key} MUST be defined for the target properties"))
+		})
+	};
+
+	let pointer_width: u32 = cargo_cfg(ENV_KEY_CARGO_CFG_TARGET_POINTER_WIDTH)?
+		.parse()
+		.map_err(|_| {
+			MldError::from(
+				format!("{ENV_KEY_CARGO_CFG_TARGET_POINTER_WIDTH} MUST be an integer"))
+		})?;
+	let endian = cargo_cfg(ENV_KEY_CARGO_CFG_TARGET_ENDIAN)?;
+	let os = cargo_cfg(ENV_KEY_CARGO_CFG_TARGET_OS)?;
+
+	Ok(vec![
+		("TARGET_POINTER_WIDTH".to_string(), Some(pointer_width.to_string())),
+		("SIZEOF_VOID_P".to_string(), Some((pointer_width / 8).to_string())),
+		("WORDS_BIGENDIAN".to_string(), (endian == "big").then(|| {"1".to_string()})),
+		(format!("TARGET_OS_{}", os.to_uppercase()), Some("1".to_string())),
+	])
+}
+
+/// Look up a value; the last one of the name wins.
+fn lookup<'v>(values: &'v [(String, Option<String>)], name: &str) -> Option<&'v str>
+{
+	values
+		.iter()
+		.rev()
+		.find(|(value_name, _)| {value_name == name})
+		.and_then(|(_, value)| {value.as_deref()})
+}
+
+/// Evaluate a value as `#cmakedefine` does.
+fn is_true(value: Option<&str>) -> bool
+{
+	match value {
+		Some(value) => {
+			let value = value.to_uppercase();
+			!CMAKE_FALSE_CONSTANTS.contains(&value.as_str()) && !value.ends_with("-NOTFOUND")
+		},
+		None => false,
+	}
+}
+
+/// Render a template.
+pub(super) fn render_template(template: &str, values: &[(String, Option<String>)]) -> String
+{
+	let mut rendered = String::new();
+
+	for line in template.lines() {
+		let directive = line
+			.trim_start()
+			.strip_prefix('#')
+			.map(str::trim_start);
+		let cmakedefine01 = directive
+			.and_then(|directive| {directive.strip_prefix("cmakedefine01")})
+			.filter(|rest| {rest.starts_with(char::is_whitespace)});
+		let cmakedefine = directive
+			.and_then(|directive| {directive.strip_prefix("cmakedefine")})
+			.filter(|rest| {rest.starts_with(char::is_whitespace)});
+
+		if let Some(rest) = cmakedefine01 {
+			let name = rest.split_whitespace().next().unwrap_or_default();
+			let value = if is_true(lookup(values, name)) {1} else {0};
+			rendered += &format!("#define {name} {value}");
+		} else if let Some(rest) = cmakedefine {
+			let rest = rest.trim_start();
+			let name_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+			let (name, value) = rest.split_at(name_end);
+			if is_true(lookup(values, name)) {
+				rendered += &format!("#define {name}{}", substitute(value, values));
+			} else {
+				rendered += &format!("/* #undef {name} */");
+			}
+		} else {
+			rendered += &substitute(line, values);
+		}
+		rendered += "\n";
+	}
+
+	rendered
+}
+
+/// Replace `@VAR@` and `${VAR}` in a line.
+fn substitute(line: &str, values: &[(String, Option<String>)]) -> String
+{
+	let bytes = line.as_bytes();
+	let name_end = |start: usize| -> usize
+	{
+		bytes[start..]
+			.iter()
+			.position(|c| {!c.is_ascii_alphanumeric() && *c != b'_'})
+			.map_or(bytes.len(), |len| {start + len})
+	};
+	let mut substituted = String::new();
+	let mut copied = 0;
+	let mut i = 0;
+
+	while i < bytes.len() {
+		let (name_start, close) = match bytes[i] {
+			b'@' => (i + 1, b'@'),
+			b'$' if bytes.get(i + 1) == Some(&b'{') => (i + 2, b'}'),
+			_ => {
+				i += 1;
+				continue;
+			},
+		};
+		let end = name_end(name_start);
+		if end > name_start && bytes.get(end) == Some(&close) {
+			substituted += &line[copied..i];
+			substituted += lookup(values, &line[name_start..end]).unwrap_or_default();
+			i = end + 1;
+			copied = i;
+		} else {
+			i += 1;
+		}
+	}
+	substituted += &line[copied..];
+
+	substituted
+}
+
+/// Render the values without a template.
+pub(super) fn render_values(filename: &str, values: &[(String, Option<String>)]) -> String
+{
+	let guard: String = filename
+		.chars()
+		.map(|c| {if c.is_ascii_alphanumeric() {c.to_ascii_uppercase()} else {'_'}})
+		.collect();
+	let mut rendered = format!(
+		"/* Generated by kusabira; do not edit. */\n#ifndef {guard}\n#define {guard}\n\n");
+	let mut names: Vec<&str> = Vec::new();
+
+	for (name, _) in values {
+		if names.contains(&name.as_str()) {
+			continue;
+		}
+		names.push(name);
+		rendered += &match lookup(values, name) {
+			Some("") => format!("#define {name}\n"),
+			Some(value) => format!("#define {name} {value}\n"),
+			None => format!("/* #undef {name} */\n"),
+		};
+	}
+	rendered += &format!("\n#endif /* {guard} */\n");
+
+	rendered
+}
@@ -0,0 +1,329 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//!
+//! The host tools built and run by [`super::Config::build`] before the input
+//! files are expanded.
+//!
+//! Refer to [`super::Config::add_host_tool`] for the usage.
+//!
+
+#![deny(missing_docs)]
+
+#[cfg(not(test))]
+use cc::Build;
+#[cfg(test)]
+use super::tests::busshi::cc_build::Build;
+use std::boxed::Box;
+use std::cell::RefCell;
+use std::iter::Iterator;
+
+use super::super::hooks::cc::reflect as reflect_cc;
+use super::CcBuildHookFn;
+
+///
+/// A helper program compiled for the host and run by
+/// [`super::Config::build`], eg a code generator of the tables or headers.
+///
+/// The host tool is compiled by [`cc::Build`] configured with the host given
+/// by [`Cargo`](https://doc.rust-lang.org/cargo/) as both of the target and
+/// host, so that it runs on the build machine under the cross-compilation as
+/// well.  The compiler and flags for the host are taken from the `HOST_CC`,
+/// `HOST_CFLAGS` and so on as documented in [`cc`].
+///
+/// The host tool is then run in the output directory with the configured
+/// arguments.  The configured output files, relative to the output
+/// directory, are fed into the input files of the default library; the
+/// source files are compiled and the header files are bound, as classified
+/// by the same extension rules as the other input files.
+///
+/// All of the configuration methods return `self` by the value, so the method
+/// calls can be chained.
+///
+/// # Example
+/// ```
+/// use kusabira::builder::{Config, HostTool};
+///
+/// let config = Config::default()
+/// 	.lib_name("foo")
+/// 	.input_file("src/*.c")
+/// 	.add_host_tool(HostTool::new("gen_tables")
+/// 		.source_file("tools/gen_tables.c")
+/// 		.args(["tables.c", "tables.h"].into_iter())
+/// 		.output_files(["tables.c", "tables.h"].into_iter()));
+/// ```
+///
+pub struct HostTool<'a>
+{
+	pub(super) name: String,
+	pub(super) source_files: Vec<String>,
+	pub(super) args: Vec<String>,
+	pub(super) output_files: Vec<String>,
+	pub(super) cc_build_hook: RefCell<CcBuildHookFn<'a>>,
+}
+
+impl<'a> HostTool<'a>
+{
+	///
+	/// Create the host tool of the given name.
+	///
+	/// The executable is named after the host tool, in the directory
+	/// `host/<name>` of the output directory.
+	///
+	/// The host tool starts with no source files, arguments and output files,
+	/// and [`super::super::hooks::cc::reflect`] as the configuration hook.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::HostTool;
+	///
+	/// let host_tool = HostTool::new("gen_tables");
+	/// ```
+	///
+	pub fn new(name: &str) -> HostTool<'a>
+	{
+		HostTool {
+			name: name.to_string(),
+			source_files: Vec::new(),
+			args: Vec::new(),
+			output_files: Vec::new(),
+			cc_build_hook: RefCell::new(Box::new(reflect_cc)),
+		}
+	}
+
+	///
+	/// Set a single source file.
+	///
+	/// The source file is relative to the source root of
+	/// [`super::Config`], without the glob support.  The source files are
+	/// compiled as C++ if any of them is C++ by the extension, or C
+	/// otherwise.
+	///
+	/// Any existing source files are removed from the host tool.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::HostTool;
+	///
+	/// let host_tool = HostTool::new("gen_tables")
+	/// 	.source_file("tools/gen_tables.c");
+	/// ```
+	///
+	pub fn source_file(mut self, filename: &str) -> Self
+	{
+		self.source_files.clear();
+		self.add_source_file(filename)
+	}
+
+	///
+	/// Add a single source file.
+	///
+	/// Any existing source files are preserved in the host tool.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::HostTool;
+	///
+	/// let host_tool = HostTool::new("gen_tables")
+	/// 	.source_file("tools/gen_tables.c")
+	/// 	.add_source_file("tools/crc.c");
+	/// ```
+	///
+	pub fn add_source_file(mut self, filename: &str) -> Self
+	{
+		self.source_files.push(filename.to_string());
+		self
+	}
+
+	///
+	/// Set the arguments via an iterator.
+	///
+	/// The host tool runs in the output directory, so the relative paths in
+	/// the arguments are relative to the output directory.
+	///
+	/// Any existing arguments are removed from the host tool.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::HostTool;
+	///
+	/// let host_tool = HostTool::new("gen_tables")
+	/// 	.args(["--output", "tables.c"].into_iter());
+	/// ```
+	///
+	pub fn args<'s, IT>(mut self, arg_iter: IT) -> Self
+		where IT: Iterator<Item = &'s str>
+	{
+		self.args.clear();
+		for arg in arg_iter {
+			self.args.push(arg.to_string());
+		}
+		self
+	}
+
+	///
+	/// Add a single argument.
+	///
+	/// Any existing arguments are preserved in the host tool.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::HostTool;
+	///
+	/// let host_tool = HostTool::new("gen_tables")
+	/// 	.args(["--output", "tables.c"].into_iter())
+	/// 	.add_arg("--verbose");
+	/// ```
+	///
+	pub fn add_arg(mut self, arg: &str) -> Self
+	{
+		self.args.push(arg.to_string());
+		self
+	}
+
+	///
+	/// Set the output files via an iterator.
+	///
+	/// The output files are relative to the output directory, without the
+	/// glob support.  Each output file MUST be created by the host tool;
+	/// [`super::Config::build`] fails otherwise.
+	///
+	/// Any existing output files are removed from the host tool.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::HostTool;
+	///
+	/// let host_tool = HostTool::new("gen_tables")
+	/// 	.output_files(["tables.c", "tables.h"].into_iter());
+	/// ```
+	///
+	pub fn output_files<'s, IT>(mut self, filename_iter: IT) -> Self
+		where IT: Iterator<Item = &'s str>
+	{
+		self.output_files.clear();
+		for filename in filename_iter {
+			self.output_files.push(filename.to_string());
+		}
+		self
+	}
+
+	///
+	/// Add a single output file.
+	///
+	/// Any existing output files are preserved in the host tool.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::HostTool;
+	///
+	/// let host_tool = HostTool::new("gen_tables")
+	/// 	.output_files(["tables.c"].into_iter())
+	/// 	.add_output_file("tables.h");
+	/// ```
+	///
+	pub fn add_output_file(mut self, filename: &str) -> Self
+	{
+		self.output_files.push(filename.to_string());
+		self
+	}
+
+	///
+	/// Set the hook to configure [`cc::Build`] of the host tool.
+	///
+	/// The hook is called after [`cc::Build`] is configured for the host.
+	/// The hooks configured to [`super::Config`] are not called on the host
+	/// tool.
+	///
+	/// The configured hook replaces the old one.
+	///
+	/// # Example
+	/// ```
+	/// use cc::Build;
+	/// use kusabira::builder::HostTool;
+	///
+	/// let host_tool = HostTool::new("gen_tables")
+	/// 	.cc_build_hook(|build: &mut Build| {build.std("c99")});
+	/// ```
+	///
+	pub fn cc_build_hook<CcBuildHook>(
+		mut self,
+		cc_build_hook: CcBuildHook)
+		-> Self
+		where CcBuildHook: FnOnce(&mut Build) -> &mut Build + 'a
+	{
+		self.cc_build_hook = RefCell::new(Box::new(cc_build_hook));
+		self
+	}
+
+	///
+	/// Add a new hook to configure [`cc::Build`] of the host tool.
+	///
+	/// Refer to [the Hook Addition section](super#hook-addition) for the
+	/// detail of the hook generated by this method.
+	///
+	/// # Example
+	/// ```
+	/// use cc::Build;
+	/// use kusabira::builder::HostTool;
+	///
+	/// let host_tool = HostTool::new("gen_tables")
+	/// 	.cc_build_hook(|build: &mut Build| {build.std("c99")})
+	/// 	.add_cc_build_hook(|build: &mut Build| {build.define("GEN_TABLES_SMALL", None)});
+	/// ```
+	///
+	pub fn add_cc_build_hook<CcBuildHook>(
+		mut self,
+		cc_build_hook: CcBuildHook)
+		-> Self
+		where CcBuildHook: FnOnce(&mut Build) -> &mut Build + 'a
+	{
+		let cc_build_hook_fn = (self.cc_build_hook)
+			.replace(Box::new(reflect_cc));
+		self.cc_build_hook = RefCell::new(Box::new(move |build: &mut Build| {
+			cc_build_hook(cc_build_hook_fn(build))
+		}));
+		self
+	}
+}
@@ -0,0 +1,146 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//!
+//! The input file language model of [`super::Config::build`].
+//!
+//! Each input file is classified into a [`FileType`], which tells whether the
+//! file is a source file compiled by [`cc`], a header file bound by
+//! [`bindgen`] or neither, as well as the [`Language`] of the file.
+//!
+
+#![deny(missing_docs)]
+
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+///
+/// The languages of the input files.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Language
+{
+	/// C.
+	C,
+	/// C++.
+	Cxx,
+	/// C already preprocessed, eg `*.i`.
+	PreprocessedC,
+	/// C++ already preprocessed, eg `*.ii`.
+	PreprocessedCxx,
+	/// The GNU assembly, eg `*.s`.
+	Assembly,
+	/// The assembly to be preprocessed, eg `*.S`.
+	PreprocessedAssembly,
+	/// The NASM assembly, eg `*.asm`, assembled by [`nasm_rs`].
+	///
+	/// YASM is also supported by configuring it as the assembler by
+	/// [`nasm_rs::Build::nasm`], as long as the sources are compatible with
+	/// NASM.
+	Nasm,
+}
+
+impl Language
+{
+	///
+	/// Check if the language is compiled as C++.
+	///
+	/// The C++ sources are compiled by [`cc::Build`] configured by
+	/// [`cc::Build::cpp`], and the C++ headers are bound by [`bindgen`] with
+	/// `-xc++`.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Language;
+	///
+	/// assert!(Language::PreprocessedCxx.is_cxx());
+	/// assert!(!Language::C.is_cxx());
+	/// ```
+	///
+	pub fn is_cxx(self) -> bool
+	{
+		matches!(self, Language::Cxx | Language::PreprocessedCxx)
+	}
+}
+
+impl Display for Language
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError>
+	{
+		match self {
+			Language::C => write!(f, "C"),
+			Language::Cxx => write!(f, "C++"),
+			Language::PreprocessedC => write!(f, "preprocessed C"),
+			Language::PreprocessedCxx => write!(f, "preprocessed C++"),
+			Language::Assembly => write!(f, "assembly"),
+			Language::PreprocessedAssembly => write!(f, "preprocessed assembly"),
+			Language::Nasm => write!(f, "NASM assembly"),
+		}
+	}
+}
+
+///
+/// The input file types.
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FileType
+{
+	/// A source file for [`cc`].
+	Source(Language),
+	/// A header file for [`bindgen`].
+	Header(Language),
+	/// An unsupported extension.
+	Unsupported(String),
+}
+
+impl Display for FileType
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError>
+	{
+		match self {
+			FileType::Source(language) => write!(f, "FileType: Source({})", language),
+			FileType::Header(language) => write!(f, "FileType: Header({})", language),
+			FileType::Unsupported(ext) => write!(f, "FileType: Unsupported({})", ext),
+		}
+	}
+}
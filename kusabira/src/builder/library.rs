@@ -0,0 +1,620 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//!
+//! The library groups built by [`super::Config::build`] on top of the default
+//! library.
+//!
+//! Refer to [`super::Config::add_library`] for the usage.
+//!
+
+#![deny(missing_docs)]
+
+#[cfg(not(test))]
+use cc::Build;
+#[cfg(test)]
+use super::tests::busshi::cc_build::Build;
+#[cfg(not(test))]
+use nasm_rs::Build as NasmBuild;
+#[cfg(test)]
+use super::tests::busshi::nasm_build::Build as NasmBuild;
+use std::boxed::Box;
+use std::cell::RefCell;
+use std::fmt::{Display, Error as FmtError, Formatter};
+use std::iter::Iterator;
+
+use super::super::hooks::cc::reflect as reflect_cc;
+use super::super::hooks::nasm::reflect as reflect_nasm;
+use super::{CcBuildHookFn, NasmBuildHookFn};
+
+///
+/// The linkage of a library reported to
+/// [`Cargo`](https://doc.rust-lang.org/cargo/).
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LinkKind
+{
+	/// Link the library statically, the default.
+	///
+	/// Only the objects resolving the undefined symbols are linked.
+	#[default]
+	Static,
+	/// Link the library statically with the `+whole-archive` modifier.
+	///
+	/// All of the objects are linked, including the ones not referenced from
+	/// Rust, eg the plugins registering themselves by the constructors.
+	WholeArchive,
+	/// Build the library but do not link it.
+	///
+	/// No [`Cargo`](https://doc.rust-lang.org/cargo/) metadata is emitted by
+	/// [`cc`] for the library.  This is for the libraries linked by some other
+	/// means, eg loaded by another library or the build script.
+	Unlinked,
+	/// Link the objects into the shared library of the given soversion and
+	/// link it dynamically.
+	///
+	/// The shared library `lib<name>.so.<soversion>` has the soname of its
+	/// own filename, so that the processes other than Rust can load it as
+	/// well.  On the Apple targets, it is `lib<name>.<soversion>.dylib`
+	/// with the install name `@rpath/<filename>` instead.  It is linked by
+	/// the compiler driver in the GNU convention, eg GCC or Clang, and the
+	/// objects are compiled by [`cc::Build::pic`].  The Windows targets are
+	/// not supported.  The shared library is reported in
+	/// [`super::LibraryResults::shared_library`].
+	///
+	/// The [`Cargo`](https://doc.rust-lang.org/cargo/) metadata to link the
+	/// shared library by the filename and to find it in the output directory
+	/// at runtime by the rpath is emitted.  The rpath by
+	/// `cargo:rustc-link-arg` applies only to the targets of the current
+	/// package, so a dependent package MUST add the rpath by itself, eg out
+	/// of `DEP_<LINKS>_RPATH` emitted as `cargo:rpath` if the package has
+	/// `links`.
+	Shared(u32),
+	/// Link the objects directly without any library.
+	///
+	/// Each object is passed to the linker by `cargo:rustc-link-arg`, so that
+	/// the linker cannot drop the objects not referenced from Rust, eg the
+	/// ones registering themselves by `__attribute__((constructor))`.  The
+	/// objects are listed in [`super::LibraryResults::objects`].  If any of
+	/// the objects are C++, the C++ standard library is linked as
+	/// [`cc::Build`] does for an archive, named by `CXXSTDLIB` and
+	/// `CXXSTDLIB_STATIC` or by the default of the target.
+	///
+	/// `cargo:rustc-link-arg` applies only to the binaries, the `cdylib`, the
+	/// tests, the examples and the benchmarks of the current package, and is
+	/// not propagated to the dependent packages.  A library package, eg a `-sys`
+	/// crate, SHOULD use [`LinkKind::WholeArchive`] instead, which keeps all
+	/// of the objects as well and is propagated.
+	Objects,
+}
+
+impl Display for LinkKind
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError>
+	{
+		match self {
+			LinkKind::Static => write!(f, "static"),
+			LinkKind::WholeArchive => write!(f, "static:+whole-archive"),
+			LinkKind::Unlinked => write!(f, "unlinked"),
+			LinkKind::Shared(soversion) => write!(f, "dylib:+verbatim (soversion {soversion})"),
+			LinkKind::Objects => write!(f, "link-arg"),
+		}
+	}
+}
+
+impl LinkKind
+{
+	/// Configure [`cc::Build`] to link the archived library as `self`.
+	pub(super) fn apply(self, build: &mut Build)
+	{
+		match self {
+			LinkKind::Static => {},
+			LinkKind::WholeArchive => {
+				build.link_lib_modifier("+whole-archive");
+			},
+			LinkKind::Unlinked => {
+				build.cargo_metadata(false);
+			},
+			// Linked by super::Config::build, not by cc::Build.
+			LinkKind::Shared(_) | LinkKind::Objects => {},
+		}
+	}
+}
+
+///
+/// A named library group, built into its own archive by
+/// [`super::Config::build`].
+///
+/// A library group has its own input files, [`cc::Build`] configuration hook
+/// and [`LinkKind`].  The other parameters, namely the output directory, the
+/// source root, the extensions, the ignore files and the hooks on [`bindgen`]
+/// and [`glob`], are shared with [`super::Config`].
+///
+/// All of the configuration methods return `self` by the value, so the method
+/// calls can be chained.
+///
+/// # Example
+/// ```
+/// use kusabira::builder::{Config, Library, LinkKind};
+///
+/// let config = Config::default()
+/// 	.lib_name("foo_core")
+/// 	.input_file("src/core/*.c")
+/// 	.add_library(Library::new("foo_plugins")
+/// 		.input_file("src/plugins/*.c")
+/// 		.link_kind(LinkKind::WholeArchive)
+/// 		.cc_build_hook(|build| {build.define("FOO_PLUGIN", None)}));
+/// ```
+///
+pub struct Library<'a>
+{
+	pub(super) name: String,
+	pub(super) input_files: Vec<String>,
+	pub(super) exclude_input_files: Vec<String>,
+	pub(super) link_kind: LinkKind,
+	pub(super) cc_build_hook: RefCell<CcBuildHookFn<'a>>,
+	pub(super) cxx_build_hook: RefCell<CcBuildHookFn<'a>>,
+	pub(super) nasm_build_hook: RefCell<NasmBuildHookFn<'a>>,
+}
+
+impl<'a> Library<'a>
+{
+	///
+	/// Create the library group of the given name.
+	///
+	/// The library name is passed to [`cc::Build::try_compile`].  Refer to
+	/// [`cc::Build::compile`] for the convention upon the library name.
+	///
+	/// The library group starts with no input files, [`LinkKind::Static`] and
+	/// [`super::super::hooks::cc::reflect`] and
+	/// [`super::super::hooks::nasm::reflect`] as the configuration hooks.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Library;
+	///
+	/// let library = Library::new("foo_plugins");
+	/// ```
+	///
+	pub fn new(name: &str) -> Library<'a>
+	{
+		Library {
+			name: name.to_string(),
+			input_files: Vec::new(),
+			exclude_input_files: Vec::new(),
+			link_kind: LinkKind::default(),
+			cc_build_hook: RefCell::new(Box::new(reflect_cc)),
+			cxx_build_hook: RefCell::new(Box::new(reflect_cc)),
+			nasm_build_hook: RefCell::new(Box::new(reflect_nasm)),
+		}
+	}
+
+	///
+	/// Set a single input file.
+	///
+	/// The filename follows the same syntax as
+	/// [`super::Config::input_file`].
+	///
+	/// Any existing input files are removed from the library group.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Library;
+	///
+	/// let library = Library::new("foo_plugins")
+	/// 	.input_file("src/plugins/*.c");
+	/// ```
+	///
+	pub fn input_file(mut self, filename: &str) -> Self
+	{
+		self.input_files.clear();
+		self.add_input_file(filename)
+	}
+
+	///
+	/// Set either a single or multiple input files via an iterator.
+	///
+	/// Any existing input files are removed from the library group.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Library;
+	///
+	/// let library = Library::new("foo_plugins")
+	/// 	.input_files(
+	/// 		["src/plugins/*.c", "src/plugins/*.s"]
+	/// 		.into_iter());
+	/// ```
+	///
+	pub fn input_files<'s, IT>(mut self, filename_iter: IT) -> Self
+		where IT: Iterator<Item = &'s str>
+	{
+		self.input_files.clear();
+		self.add_input_files(filename_iter)
+	}
+
+	///
+	/// Add a single input file.
+	///
+	/// Any existing input files are preserved in the library group.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Library;
+	///
+	/// let library = Library::new("foo_plugins")
+	/// 	.input_file("src/plugins/*.c")
+	/// 	.add_input_file("src/compat/plugin_compat.c");
+	/// ```
+	///
+	pub fn add_input_file(mut self, filename: &str) -> Self
+	{
+		self.input_files.push(filename.to_string());
+		self
+	}
+
+	///
+	/// Add either a single or multiple input files via an iterator.
+	///
+	/// Any existing input files are preserved in the library group.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Library;
+	///
+	/// let library = Library::new("foo_plugins")
+	/// 	.input_file("src/plugins/*.c")
+	/// 	.add_input_files(
+	/// 		["src/compat/plugin_*.c", "src/compat/plugin_*.s"]
+	/// 		.into_iter());
+	/// ```
+	///
+	pub fn add_input_files<'s, IT>(mut self, filename_iter: IT) -> Self
+		where IT: Iterator<Item = &'s str>
+	{
+		for filename in filename_iter {
+			self.input_files.push(filename.to_string());
+		}
+		self
+	}
+
+	///
+	/// Set a single exclusion input file pattern.
+	///
+	/// The pattern follows the same syntax as
+	/// [`super::Config::exclude_input_file`].  The exclusion applies only to
+	/// the library group, on top of the ones configured to
+	/// [`super::Config`].
+	///
+	/// Any existing exclusion patterns are removed from the library group.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Library;
+	///
+	/// let library = Library::new("foo_plugins")
+	/// 	.input_file("src/plugins/**/*.c")
+	/// 	.exclude_input_file("src/plugins/experimental/**");
+	/// ```
+	///
+	pub fn exclude_input_file(mut self, pattern: &str) -> Self
+	{
+		self.exclude_input_files.clear();
+		self.add_exclude_input_file(pattern)
+	}
+
+	///
+	/// Set either a single or multiple exclusion input file patterns via an
+	/// iterator.
+	///
+	/// Refer to [`Library::exclude_input_file`] for the pattern syntax.
+	///
+	/// Any existing exclusion patterns are removed from the library group.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Library;
+	///
+	/// let library = Library::new("foo_plugins")
+	/// 	.input_file("src/plugins/**/*.c")
+	/// 	.exclude_input_files(
+	/// 		["src/plugins/**/test_*.c", "src/plugins/{examples,experimental}/**"]
+	/// 		.into_iter());
+	/// ```
+	///
+	pub fn exclude_input_files<'s, IT>(mut self, pattern_iter: IT) -> Self
+		where IT: Iterator<Item = &'s str>
+	{
+		self.exclude_input_files.clear();
+		self.add_exclude_input_files(pattern_iter)
+	}
+
+	///
+	/// Add a single exclusion input file pattern.
+	///
+	/// Any existing exclusion patterns are preserved in the library group.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Library;
+	///
+	/// let library = Library::new("foo_plugins")
+	/// 	.input_file("src/plugins/**/*.c")
+	/// 	.exclude_input_file("src/plugins/experimental/**")
+	/// 	.add_exclude_input_file("src/plugins/**/test_*.c");
+	/// ```
+	///
+	pub fn add_exclude_input_file(mut self, pattern: &str) -> Self
+	{
+		self.exclude_input_files.push(pattern.to_string());
+		self
+	}
+
+	///
+	/// Add either a single or multiple exclusion input file patterns via an
+	/// iterator.
+	///
+	/// Refer to [`Library::exclude_input_file`] for the pattern syntax.
+	///
+	/// Any existing exclusion patterns are preserved in the library group.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Library;
+	///
+	/// let library = Library::new("foo_plugins")
+	/// 	.input_file("src/plugins/**/*.c")
+	/// 	.exclude_input_file("src/plugins/**/test_*.c")
+	/// 	.add_exclude_input_files(
+	/// 		["src/plugins/examples/**", "src/plugins/experimental/**"]
+	/// 		.into_iter());
+	/// ```
+	///
+	pub fn add_exclude_input_files<'s, IT>(mut self, pattern_iter: IT) -> Self
+		where IT: Iterator<Item = &'s str>
+	{
+		for pattern in pattern_iter {
+			self.exclude_input_files.push(pattern.to_string());
+		}
+		self
+	}
+
+	///
+	/// Set the linkage of the library.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::{Library, LinkKind};
+	///
+	/// let library = Library::new("foo_plugins")
+	/// 	.link_kind(LinkKind::WholeArchive);
+	/// ```
+	///
+	pub fn link_kind(mut self, link_kind: LinkKind) -> Self
+	{
+		self.link_kind = link_kind;
+		self
+	}
+
+	///
+	/// Set the hook to configure [`cc::Build`] of the C and assembly sources
+	/// in the library group.
+	///
+	/// The configured hook is called only once during the execution of
+	/// [`super::Config::build`].  The hook configured to [`super::Config`] is
+	/// not called on the library group.
+	///
+	/// The configured hook replaces the old one.
+	///
+	/// # Example
+	/// ```
+	/// use cc::Build;
+	/// use kusabira::builder::Library;
+	///
+	/// let library = Library::new("foo_plugins")
+	/// 	.cc_build_hook(|build: &mut Build| {build.warnings(false)});
+	/// ```
+	///
+	pub fn cc_build_hook<CcBuildHook>(
+		mut self,
+		cc_build_hook: CcBuildHook)
+		-> Self
+		where CcBuildHook: FnOnce(&mut Build) -> &mut Build + 'a
+	{
+		self.cc_build_hook = RefCell::new(Box::new(cc_build_hook));
+		self
+	}
+
+	///
+	/// Add a new hook to configure [`cc::Build`] of the C and assembly sources
+	/// in the library group.
+	///
+	/// The configured hook is called only once during the execution of
+	/// [`super::Config::build`].
+	///
+	/// Refer to [the Hook Addition section](super#hook-addition) for the
+	/// detail of the hook generated by this method.
+	///
+	/// # Example
+	/// ```
+	/// use cc::Build;
+	/// use kusabira::builder::Library;
+	///
+	/// let library = Library::new("foo_plugins")
+	/// 	.cc_build_hook(|build: &mut Build| {build.warnings(false)})
+	/// 	.add_cc_build_hook(|build: &mut Build| {build.define("FOO_PLUGIN", None)});
+	/// ```
+	///
+	pub fn add_cc_build_hook<CcBuildHook>(
+		mut self,
+		cc_build_hook: CcBuildHook)
+		-> Self
+		where CcBuildHook: FnOnce(&mut Build) -> &mut Build + 'a
+	{
+		let cc_build_hook_fn = (self.cc_build_hook)
+			.replace(Box::new(reflect_cc));
+		self.cc_build_hook = RefCell::new(Box::new(move |build: &mut Build| {
+			cc_build_hook(cc_build_hook_fn(build))
+		}));
+		self
+	}
+
+	///
+	/// Set the hook to configure [`cc::Build`] of the C++ sources in the
+	/// library group.
+	///
+	/// Refer to [`super::Config::cxx_build_hook`] for the detail.  The hook
+	/// configured to [`super::Config`] is not called on the library group.
+	///
+	/// The configured hook replaces the old one.
+	///
+	/// # Example
+	/// ```
+	/// use cc::Build;
+	/// use kusabira::builder::Library;
+	///
+	/// let library = Library::new("foo_plugins")
+	/// 	.cxx_build_hook(|build: &mut Build| {build.std("c++17")});
+	/// ```
+	///
+	pub fn cxx_build_hook<CcBuildHook>(
+		mut self,
+		cxx_build_hook: CcBuildHook)
+		-> Self
+		where CcBuildHook: FnOnce(&mut Build) -> &mut Build + 'a
+	{
+		self.cxx_build_hook = RefCell::new(Box::new(cxx_build_hook));
+		self
+	}
+
+	///
+	/// Add a new hook to configure [`cc::Build`] of the C++ sources in the
+	/// library group.
+	///
+	/// Refer to [the Hook Addition section](super#hook-addition) for the
+	/// detail of the hook generated by this method.
+	///
+	/// # Example
+	/// ```
+	/// use cc::Build;
+	/// use kusabira::builder::Library;
+	///
+	/// let library = Library::new("foo_plugins")
+	/// 	.cxx_build_hook(|build: &mut Build| {build.std("c++17")})
+	/// 	.add_cxx_build_hook(|build: &mut Build| {build.define("FOO_PLUGIN", None)});
+	/// ```
+	///
+	pub fn add_cxx_build_hook<CcBuildHook>(
+		mut self,
+		cxx_build_hook: CcBuildHook)
+		-> Self
+		where CcBuildHook: FnOnce(&mut Build) -> &mut Build + 'a
+	{
+		let cxx_build_hook_fn = (self.cxx_build_hook)
+			.replace(Box::new(reflect_cc));
+		self.cxx_build_hook = RefCell::new(Box::new(move |build: &mut Build| {
+			cxx_build_hook(cxx_build_hook_fn(build))
+		}));
+		self
+	}
+
+	///
+	/// Set the hook to configure [`nasm_rs::Build`] of the NASM sources in
+	/// the library group.
+	///
+	/// Refer to [`super::Config::nasm_build_hook`] for the detail.  The hook
+	/// configured to [`super::Config`] is not called on the library group.
+	///
+	/// The configured hook replaces the old one.
+	///
+	/// # Example
+	/// ```
+	/// use nasm_rs::Build;
+	/// use kusabira::builder::Library;
+	///
+	/// let library = Library::new("foo_kernels")
+	/// 	.nasm_build_hook(|build: &mut Build| {build.include("src/x86/include")});
+	/// ```
+	///
+	pub fn nasm_build_hook<NasmBuildHook>(
+		mut self,
+		nasm_build_hook: NasmBuildHook)
+		-> Self
+		where NasmBuildHook: FnOnce(&mut NasmBuild) -> &mut NasmBuild + 'a
+	{
+		self.nasm_build_hook = RefCell::new(Box::new(nasm_build_hook));
+		self
+	}
+
+	///
+	/// Add a new hook to configure [`nasm_rs::Build`] of the NASM sources in
+	/// the library group.
+	///
+	/// Refer to [the Hook Addition section](super#hook-addition) for the
+	/// detail of the hook generated by this method.
+	///
+	/// # Example
+	/// ```
+	/// use nasm_rs::Build;
+	/// use kusabira::builder::Library;
+	///
+	/// let library = Library::new("foo_kernels")
+	/// 	.nasm_build_hook(|build: &mut Build| {build.include("src/x86/include")})
+	/// 	.add_nasm_build_hook(|build: &mut Build| {build.define("PIC", None)});
+	/// ```
+	///
+	pub fn add_nasm_build_hook<NasmBuildHook>(
+		mut self,
+		nasm_build_hook: NasmBuildHook)
+		-> Self
+		where NasmBuildHook: FnOnce(&mut NasmBuild) -> &mut NasmBuild + 'a
+	{
+		let nasm_build_hook_fn = (self.nasm_build_hook)
+			.replace(Box::new(reflect_nasm));
+		self.nasm_build_hook = RefCell::new(Box::new(move |build: &mut NasmBuild| {
+			nasm_build_hook(nasm_build_hook_fn(build))
+		}));
+		self
+	}
+}
@@ -0,0 +1,705 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//!
+//! The declarative configuration of [`super::Config`] out of the
+//! [`Cargo`](https://doc.rust-lang.org/cargo/) manifest.
+//!
+//! Refer to [`super::Config::from_manifest`] for the manifest format.
+//!
+
+#![deny(missing_docs)]
+
+#[cfg(not(test))]
+use bindgen::Builder;
+#[cfg(test)]
+use super::tests::busshi::bindgen_builder::Builder;
+#[cfg(not(test))]
+use cc::Build;
+#[cfg(test)]
+use super::tests::busshi::cc_build::Build;
+#[cfg(not(test))]
+use nasm_rs::Build as NasmBuild;
+#[cfg(test)]
+use super::tests::busshi::nasm_build::Build as NasmBuild;
+use std::env;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+use super::super::error::Error as MldError;
+use super::{Config, ENV_KEY_CARGO_MANIFEST_DIR, HeaderBinding, Library, LinkKind, VendoredBindingsMode};
+
+/// The filename of the [`Cargo`](https://doc.rust-lang.org/cargo/) manifest.
+pub static MANIFEST_FILENAME: &str = "Cargo.toml";
+
+/// The key path of the [`kusabira`](crate) configuration table in the
+/// [`Cargo`](https://doc.rust-lang.org/cargo/) manifest.
+pub static MANIFEST_METADATA_KEY: &str = "package.metadata.kusabira";
+
+/// The [`bindgen::Builder`] configuration method taking a string.
+type BindgenStrMethod = fn(Builder, &str) -> Builder;
+
+/// The [`bindgen::Builder`] configuration method taking a boolean.
+type BindgenBoolMethod = fn(Builder, bool) -> Builder;
+
+/// The list of the [`bindgen::Builder`] configuration methods taking a
+/// string, keyed by the manifest key in the `bindgen` table.
+static BINDGEN_STR_OPTIONS: [(&str, BindgenStrMethod); 13] =
+[
+	("allowlist-functions", |builder, arg| {builder.allowlist_function(arg)}),
+	("allowlist-types", |builder, arg| {builder.allowlist_type(arg)}),
+	("allowlist-vars", |builder, arg| {builder.allowlist_var(arg)}),
+	("allowlist-files", |builder, arg| {builder.allowlist_file(arg)}),
+	("blocklist-functions", |builder, arg| {builder.blocklist_function(arg)}),
+	("blocklist-types", |builder, arg| {builder.blocklist_type(arg)}),
+	("blocklist-items", |builder, arg| {builder.blocklist_item(arg)}),
+	("blocklist-files", |builder, arg| {builder.blocklist_file(arg)}),
+	("opaque-types", |builder, arg| {builder.opaque_type(arg)}),
+	("rustified-enums", |builder, arg| {builder.rustified_enum(arg)}),
+	("newtype-enums", |builder, arg| {builder.newtype_enum(arg)}),
+	("constified-enum-modules", |builder, arg| {builder.constified_enum_module(arg)}),
+	("bitfield-enums", |builder, arg| {builder.bitfield_enum(arg)}),
+];
+
+/// The list of the [`bindgen::Builder`] configuration methods taking a
+/// boolean, keyed by the manifest key in the `bindgen` table.
+static BINDGEN_BOOL_OPTIONS: [(&str, BindgenBoolMethod); 5] =
+[
+	("generate-comments", |builder, doit| {builder.generate_comments(doit)}),
+	("generate-block", |builder, doit| {builder.generate_block(doit)}),
+	("layout-tests", |builder, doit| {builder.layout_tests(doit)}),
+	("derive-default", |builder, doit| {builder.derive_default(doit)}),
+	("derive-debug", |builder, doit| {builder.derive_debug(doit)}),
+];
+
+/// The manifest key of the extra [`bindgen::Builder::clang_arg`]s in the
+/// `bindgen` table.
+static BINDGEN_CLANG_ARGS_KEY: &str = "clang-args";
+
+impl<'a> Config<'a>
+{
+	///
+	/// Create the configuration out of the `[package.metadata.kusabira]` table
+	/// in the [`Cargo`](https://doc.rust-lang.org/cargo/) manifest of the
+	/// package being built.
+	///
+	/// The manifest is located by the environment variable
+	/// `CARGO_MANIFEST_DIR`, which MUST be defined.  Use
+	/// [`Config::from_manifest_path`] otherwise.
+	///
+	/// The configuration starts with the default parameters of
+	/// [`Config::default`], except that the source root is the directory of
+	/// the manifest.  The parameters in the manifest are then applied.
+	///
+	/// # Manifest Format
+	/// All of the keys are OPTIONAL.  The relative paths and patterns are
+	/// resolved against the source root.
+	///
+	/// | Key | Type | Counterpart |
+	/// |-----|------|-------------|
+	/// | `source-root` | String | [`Config::source_root`] |
+	/// | `lib-name` | String | [`Config::lib_name`] |
+	/// | `link-kind` | String | [`Config::link_kind`] |
+	/// | `soversion` | Integer | The soversion of [`LinkKind::Shared`] |
+	/// | `input-files` | Array of strings | [`Config::add_input_files`] |
+	/// | `exclude-input-files` | Array of strings | [`Config::add_exclude_input_files`] |
+	/// | `respect-ignore-files` | Boolean | [`Config::respect_ignore_files`] |
+	/// | `source-exts` | Array of strings | [`Config::add_source_ext`] |
+	/// | `header-exts` | Array of strings | [`Config::add_header_ext`] |
+	/// | `binding-ext` | String | [`Config::binding_ext`] |
+	/// | `combined-binding` | String | [`Config::combined_binding`] |
+	/// | `vendored-bindings` | String | [`Config::vendored_bindings`] |
+	/// | `vendored-bindings-mode` | String | [`Config::vendored_bindings_mode`] |
+	/// | `bindgen-cache` | Boolean | [`Config::bindgen_cache`] |
+	/// | `parallel-bindgen` | Boolean | [`Config::parallel_bindgen`] |
+	/// | `abi-layout-tests` | Boolean | [`Config::abi_layout_tests`] |
+	/// | `include-dirs` | Array of strings | [`Config::include_dir`] |
+	/// | `defines` | Table | [`Config::define`] |
+	/// | `std` | String | [`Config::std`] |
+	/// | `cxx-std` | String | [`Config::cxx_std`] |
+	/// | `include-input-dirs` | Boolean | [`Config::include_input_dirs`] |
+	/// | `include-dep-dirs` | Boolean | [`Config::include_dep_dirs`] |
+	/// | `restrict-bindings` | Boolean | [`Config::restrict_bindings`] |
+	/// | `restrict-bindings-dirs` | Array of strings | [`Config::restrict_bindings_dir`] |
+	/// | `system-libraries` | Table | [`Config::system_library`] by the name and version requirement |
+	/// | `install-headers` | Table | [`Config::install_header`] by the destination directory and the array of patterns |
+	/// | `metadata` | Table | [`Config::metadata`] by the key and string value |
+	/// | `bindgen` | Table | The [`bindgen::Builder`] configuration methods below |
+	/// | `libraries` | Array of tables | [`Config::add_library`] |
+	///
+	/// A value in the `defines` table is either a string, an integer or a
+	/// boolean.  `true` defines the macro without a value, while `false` does
+	/// not define it at all.
+	///
+	/// A `vendored-bindings-mode` value is either `fallback`, `force`,
+	/// `verify` or `update`, as in [`VendoredBindingsMode`].
+	///
+	/// A `link-kind` value is either `static`, `whole-archive`, `unlinked`,
+	/// `shared` or `objects`, as in [`LinkKind`].  The `soversion` of `shared` defaults to
+	/// 0, and MUST NOT be configured for the other link kinds.
+	///
+	/// Each table in the `libraries` array configures a [`Library`] by the
+	/// keys `name` (REQUIRED), `input-files`, `exclude-input-files`,
+	/// `link-kind`, `soversion`, `include-dirs` and `defines`.  The include directories and
+	/// defines of a library apply only to [`cc::Build`] of the library, while
+	/// the top-level ones apply to all of the libraries and
+	/// [`bindgen::Builder`].
+	///
+	/// The `bindgen` table accepts the following keys:
+	///
+	/// * Array of strings: `allowlist-functions`, `allowlist-types`,
+	///   `allowlist-vars`, `allowlist-files`, `blocklist-functions`,
+	///   `blocklist-types`, `blocklist-items`, `blocklist-files`,
+	///   `opaque-types`, `rustified-enums`, `newtype-enums`,
+	///   `constified-enum-modules`, `bitfield-enums` and `clang-args`.  Each
+	///   element is passed to the [`bindgen::Builder`] method of the singular
+	///   name, eg `allowlist-functions` to
+	///   [`bindgen::Builder::allowlist_function`].
+	/// * Boolean: `generate-comments`, `generate-block`, `layout-tests`,
+	///   `derive-default` and `derive-debug`.
+	///
+	/// Any unknown key is an error, so that a typo does not go unnoticed.
+	///
+	/// ```toml
+	/// [package.metadata.kusabira]
+	/// lib-name = "hello_world_c"
+	/// input-files = [
+	/// 	"src/hello_world_export_to_rust.h",
+	/// 	"src/hello_world_c_*.c",
+	/// ]
+	/// exclude-input-files = ["src/**/test_*.c"]
+	/// include-dirs = ["include"]
+	///
+	/// [package.metadata.kusabira.defines]
+	/// HAVE_CONFIG_H = true
+	/// HELLO_WORLD_VERSION = 2
+	///
+	/// [package.metadata.kusabira.system-libraries]
+	/// libcw = ">=7.0"
+	///
+	/// [package.metadata.kusabira.install-headers]
+	/// hello_world = ["src/hello_world_export_to_rust.h"]
+	///
+	/// [package.metadata.kusabira.bindgen]
+	/// generate-comments = true
+	/// newtype-enums = ["cw_return_values"]
+	///
+	/// [[package.metadata.kusabira.libraries]]
+	/// name = "hello_world_plugins"
+	/// input-files = ["src/plugins/*.c"]
+	/// link-kind = "whole-archive"
+	/// defines = { HELLO_WORLD_PLUGIN = true }
+	/// ```
+	///
+	/// # Hooks
+	/// The `bindgen` table, and the include directories and the defines of
+	/// a library, are applied by the hooks added by
+	/// [`Config::add_cc_build_hook`], [`Config::add_cxx_build_hook`],
+	/// [`Config::add_nasm_build_hook`] and
+	/// [`Config::add_sync_bindgen_builder_hook`], the last of which keeps
+	/// `parallel-bindgen` available.  Add any further hooks in the same way
+	/// so that they layer on top of the manifest.  A hook set by
+	/// [`Config::cc_build_hook`], [`Config::cxx_build_hook`] or
+	/// [`Config::sync_bindgen_builder_hook`] replaces the manifest
+	/// configuration.
+	///
+	/// # Errors
+	/// * `CARGO_MANIFEST_DIR` is not defined.
+	/// * The manifest cannot be read or parsed.
+	/// * The `[package.metadata.kusabira]` table is missing or malformed.
+	///
+	/// # Example
+	/// The build script of a package configured by the manifest above:
+	/// ```no_run
+	/// use kusabira::builder::Config;
+	///
+	/// fn main() -> Result<(), kusabira::KusabiraError>
+	/// {
+	/// 	Config::from_manifest()?
+	/// 		.add_cc_build_hook(|build| {build.std("c11")})
+	/// 		.build()?;
+	/// 	Ok(())
+	/// }
+	/// ```
+	///
+	pub fn from_manifest() -> Result<Config<'a>, MldError>
+	{
+		let manifest_dir = env::var_os(ENV_KEY_CARGO_MANIFEST_DIR)
+			.ok_or_else(|| MldError::from(
+				format!("{ENV_KEY_CARGO_MANIFEST_DIR} MUST be defined to locate the manifest")))?;
+
+		Config::from_manifest_path(&Path::new(&manifest_dir).join(MANIFEST_FILENAME))
+	}
+
+	///
+	/// Create the configuration out of the `[package.metadata.kusabira]` table
+	/// in the given [`Cargo`](https://doc.rust-lang.org/cargo/) manifest.
+	///
+	/// Refer to [`Config::from_manifest`] for the detail.
+	///
+	/// The manifest is reported by `cargo:rerun-if-changed`, so that the
+	/// build script reruns when the table changes.
+	///
+	/// # Example
+	/// ```no_run
+	/// use std::path::Path;
+	/// use kusabira::builder::Config;
+	///
+	/// let config = Config::from_manifest_path(Path::new("vendor/libfoo/Cargo.toml"))
+	/// 	.expect("manifest MUST be valid");
+	/// ```
+	///
+	pub fn from_manifest_path(manifest_path: &Path) -> Result<Config<'a>, MldError>
+	{
+		println!("cargo:rerun-if-changed={}", manifest_path.display());
+		let manifest: Table = read_to_string(manifest_path)?.parse()?;
+		let metadata = MANIFEST_METADATA_KEY
+			.split('.')
+			.try_fold(&manifest, |table, key|
+			{
+				table.get(key).and_then(Value::as_table)
+			})
+			.ok_or_else(|| MldError::from(
+				format!("[{MANIFEST_METADATA_KEY}] MUST be configured as a table in {}",
+					manifest_path.display())))?;
+
+		let manifest_dir = env::current_dir()?
+			.join(manifest_path)
+			.parent()
+			.map(Path::to_path_buf)
+			.unwrap_or_default();
+
+		Config::default()
+			.source_root(&manifest_dir)
+			.apply_manifest(metadata)
+	}
+
+	/// Apply the `[package.metadata.kusabira]` table.
+	fn apply_manifest(mut self, metadata: &Table) -> Result<Config<'a>, MldError>
+	{
+		// The source root comes first; the include directories depend on it.
+		if let Some(source_root) = metadata.get("source-root") {
+			let source_root = manifest_str(source_root, "source-root")?;
+			let source_root = self.source_root
+				.clone()
+				.unwrap_or_default()
+				.join(source_root);
+			self = self.source_root(&source_root);
+		}
+		let source_root = env::current_dir()?
+			.join(self.source_root.clone().unwrap_or_default());
+		let mut soversion = None;
+
+		for (key, value) in metadata {
+			match key.as_str() {
+				"source-root" => {},
+				"lib-name" => {
+					self.lib_name = Some(manifest_str(value, key)?.to_string());
+				},
+				"link-kind" => {
+					self.link_kind = manifest_link_kind(value, key)?;
+				},
+				"soversion" => {
+					soversion = Some(manifest_soversion(value, key)?);
+				},
+				"input-files" => {
+					self.input_files.extend(manifest_str_array(value, key)?);
+				},
+				"exclude-input-files" => {
+					self.exclude_input_files.extend(manifest_str_array(value, key)?);
+				},
+				"respect-ignore-files" => {
+					self.respect_ignore_files = manifest_bool(value, key)?;
+				},
+				"source-exts" => {
+					for ext in manifest_str_array(value, key)? {
+						self = self.add_source_ext(&ext);
+					}
+				},
+				"header-exts" => {
+					for ext in manifest_str_array(value, key)? {
+						self = self.add_header_ext(&ext);
+					}
+				},
+				"binding-ext" => {
+					self.binding_ext = manifest_str(value, key)?.to_string();
+				},
+				"combined-binding" => {
+					self.combined_binding = Some(manifest_str(value, key)?.to_string());
+				},
+				"vendored-bindings" => {
+					self.vendored_bindings_dir = Some(source_root.join(manifest_str(value, key)?));
+				},
+				"vendored-bindings-mode" => {
+					self.vendored_bindings_mode = manifest_vendored_bindings_mode(value, key)?;
+				},
+				"bindgen-cache" => {
+					self.bindgen_cache = manifest_bool(value, key)?;
+				},
+				"parallel-bindgen" => {
+					self.parallel_bindgen = manifest_bool(value, key)?;
+				},
+				"abi-layout-tests" => {
+					self.abi_layout_tests = manifest_bool(value, key)?;
+				},
+				"include-dirs" => {
+					let include_dirs: Vec<PathBuf> = manifest_str_array(value, key)?
+						.iter()
+						.map(|include_dir| {source_root.join(include_dir)})
+						.collect();
+					self.include_dirs.extend(include_dirs);
+				},
+				"defines" => {
+					self.defines.extend(manifest_defines(value, key)?);
+				},
+				"std" => {
+					self.std = Some(manifest_str(value, key)?.to_string());
+				},
+				"cxx-std" => {
+					self.cxx_std = Some(manifest_str(value, key)?.to_string());
+				},
+				"include-input-dirs" => {
+					self.include_input_dirs = manifest_bool(value, key)?;
+				},
+				"include-dep-dirs" => {
+					self.include_dep_dirs = manifest_bool(value, key)?;
+				},
+				"restrict-bindings" => {
+					self.restrict_bindings = manifest_bool(value, key)?;
+				},
+				"restrict-bindings-dirs" => {
+					for restrict_bindings_dir in manifest_str_array(value, key)? {
+						self = self.restrict_bindings_dir(&source_root.join(restrict_bindings_dir));
+					}
+				},
+				"system-libraries" => {
+					let table = value.as_table()
+						.ok_or_else(|| invalid_value(key, "a table"))?;
+					for (name, version) in table {
+						let version = manifest_str(version, &format!("{key}.{name}"))?;
+						self = self.system_library(name, version);
+					}
+				},
+				"install-headers" => {
+					let table = value.as_table()
+						.ok_or_else(|| invalid_value(key, "a table"))?;
+					for (dest_dir, patterns) in table {
+						for pattern in manifest_str_array(patterns, &format!("{key}.{dest_dir}"))? {
+							self = self.install_header(&pattern, dest_dir);
+						}
+					}
+				},
+				"metadata" => {
+					let table = value.as_table()
+						.ok_or_else(|| invalid_value(key, "a table"))?;
+					for (metadata_key, metadata_value) in table {
+						let metadata_value = manifest_str(
+							metadata_value, &format!("{key}.{metadata_key}"))?;
+						self = self.metadata(metadata_key, metadata_value);
+					}
+				},
+				"bindgen" => {
+					self = self.add_manifest_bindgen_options(value)?;
+				},
+				"libraries" => {
+					let libraries = value.as_array()
+						.ok_or_else(|| invalid_value(key, "an array of tables"))?;
+					for (i, library) in libraries.iter().enumerate() {
+						self = self.add_library(
+							manifest_library(library, &format!("{key}[{i}]"), &source_root)?);
+					}
+				},
+				_ => {
+					return Err(unknown_key(key));
+				},
+			}
+		}
+		self.link_kind = manifest_apply_soversion(self.link_kind, soversion, "soversion")?;
+
+		Ok(self)
+	}
+
+	/// Add the hook to configure [`bindgen::Builder`] by the `bindgen` table.
+	fn add_manifest_bindgen_options(self, value: &Value) -> Result<Config<'a>, MldError>
+	{
+		let key_prefix = "bindgen.";
+		let table = value.as_table()
+			.ok_or_else(|| invalid_value("bindgen", "a table"))?;
+
+		let mut str_options: Vec<(BindgenStrMethod, Vec<String>)> = Vec::new();
+		let mut bool_options: Vec<(BindgenBoolMethod, bool)> = Vec::new();
+		let mut clang_args = Vec::new();
+		for (key, value) in table {
+			let full_key = format!("{key_prefix}{key}");
+			if key == BINDGEN_CLANG_ARGS_KEY {
+				clang_args.extend(manifest_str_array(value, &full_key)?);
+			} else if let Some((_, method)) = BINDGEN_STR_OPTIONS
+				.iter()
+				.find(|(option, _)| {option == key}) {
+				str_options.push((*method, manifest_str_array(value, &full_key)?));
+			} else if let Some((_, method)) = BINDGEN_BOOL_OPTIONS
+				.iter()
+				.find(|(option, _)| {option == key}) {
+				bool_options.push((*method, manifest_bool(value, &full_key)?));
+			} else {
+				return Err(unknown_key(&full_key));
+			}
+		}
+
+		// The options are plain data, so that the hook is thread-safe and
+		// works with the parallel bindgen.
+		Ok(self.add_sync_bindgen_builder_hook(
+			move |mut builder: Builder, _header_binding: &HeaderBinding|
+			{
+				for (method, args) in &str_options {
+					for arg in args {
+						builder = method(builder, arg);
+					}
+				}
+				for (method, doit) in &bool_options {
+					builder = method(builder, *doit);
+				}
+				for clang_arg in &clang_args {
+					builder = builder.clang_arg(clang_arg);
+				}
+				builder
+			}))
+	}
+}
+
+/// Get a library in the `libraries` array of the manifest.
+fn manifest_library<'a>(value: &Value, key: &str, source_root: &Path)
+	-> Result<Library<'a>, MldError>
+{
+	let table = value.as_table()
+		.ok_or_else(|| invalid_value(key, "a table"))?;
+	let name = table.get("name")
+		.ok_or_else(|| invalid_value(&format!("{key}.name"), "configured"))?;
+	let mut library = Library::new(manifest_str(name, &format!("{key}.name"))?);
+	let mut include_dirs = Vec::new();
+	let mut defines = Vec::new();
+	let mut soversion = None;
+
+	for (library_key, value) in table {
+		let full_key = format!("{key}.{library_key}");
+		match library_key.as_str() {
+			"name" => {},
+			"input-files" => {
+				library.input_files.extend(manifest_str_array(value, &full_key)?);
+			},
+			"exclude-input-files" => {
+				library.exclude_input_files.extend(manifest_str_array(value, &full_key)?);
+			},
+			"link-kind" => {
+				library.link_kind = manifest_link_kind(value, &full_key)?;
+			},
+			"soversion" => {
+				soversion = Some(manifest_soversion(value, &full_key)?);
+			},
+			"include-dirs" => {
+				include_dirs.extend(manifest_str_array(value, &full_key)?
+					.iter()
+					.map(|include_dir| {source_root.join(include_dir)}));
+			},
+			"defines" => {
+				defines = manifest_defines(value, &full_key)?;
+			},
+			_ => {
+				return Err(unknown_key(&full_key));
+			},
+		}
+	}
+	library.link_kind = manifest_apply_soversion(
+		library.link_kind,
+		soversion,
+		&format!("{key}.soversion"))?;
+
+	let cxx_include_dirs = include_dirs.clone();
+	let cxx_defines = defines.clone();
+	let nasm_include_dirs = include_dirs.clone();
+	let nasm_defines = defines.clone();
+
+	Ok(library
+		.add_cc_build_hook(move |build: &mut Build|
+		{
+			for include_dir in include_dirs {
+				build.include(include_dir);
+			}
+			for (name, value) in &defines {
+				build.define(name, value.as_deref());
+			}
+			build
+		})
+		.add_cxx_build_hook(move |build: &mut Build|
+		{
+			for include_dir in cxx_include_dirs {
+				build.include(include_dir);
+			}
+			for (name, value) in &cxx_defines {
+				build.define(name, value.as_deref());
+			}
+			build
+		})
+		.add_nasm_build_hook(move |build: &mut NasmBuild|
+		{
+			for include_dir in nasm_include_dirs {
+				build.include(include_dir);
+			}
+			for (name, value) in &nasm_defines {
+				build.define(name, value.as_deref());
+			}
+			build
+		}))
+}
+
+/// Get a [`LinkKind`] value in the manifest.
+fn manifest_link_kind(value: &Value, key: &str) -> Result<LinkKind, MldError>
+{
+	match value.as_str() {
+		Some("static") => Ok(LinkKind::Static),
+		Some("whole-archive") => Ok(LinkKind::WholeArchive),
+		Some("unlinked") => Ok(LinkKind::Unlinked),
+		Some("shared") => Ok(LinkKind::Shared(0)),
+		Some("objects") => Ok(LinkKind::Objects),
+		_ => Err(invalid_value(key,
+			"either \"static\", \"whole-archive\", \"unlinked\", \"shared\" or \"objects\"")),
+	}
+}
+
+/// Get a [`VendoredBindingsMode`] value in the manifest.
+fn manifest_vendored_bindings_mode(value: &Value, key: &str)
+	-> Result<VendoredBindingsMode, MldError>
+{
+	match value.as_str() {
+		Some("fallback") => Ok(VendoredBindingsMode::Fallback),
+		Some("force") => Ok(VendoredBindingsMode::Force),
+		Some("verify") => Ok(VendoredBindingsMode::Verify),
+		Some("update") => Ok(VendoredBindingsMode::Update),
+		_ => Err(invalid_value(key,
+			"either \"fallback\", \"force\", \"verify\" or \"update\"")),
+	}
+}
+
+/// Get a soversion value in the manifest.
+fn manifest_soversion(value: &Value, key: &str) -> Result<u32, MldError>
+{
+	value.as_integer()
+		.and_then(|soversion| {u32::try_from(soversion).ok()})
+		.ok_or_else(|| invalid_value(key, "a non-negative integer"))
+}
+
+/// Apply the soversion in the manifest, if any, to [`LinkKind::Shared`].
+fn manifest_apply_soversion(link_kind: LinkKind, soversion: Option<u32>, key: &str)
+	-> Result<LinkKind, MldError>
+{
+	match (link_kind, soversion) {
+		(_, None) => Ok(link_kind),
+		(LinkKind::Shared(_), Some(soversion)) => Ok(LinkKind::Shared(soversion)),
+		_ => Err(invalid_value(key, "configured only for the \"shared\" link-kind")),
+	}
+}
+
+/// Get a string value in the manifest.
+fn manifest_str<'v>(value: &'v Value, key: &str) -> Result<&'v str, MldError>
+{
+	value.as_str().ok_or_else(|| invalid_value(key, "a string"))
+}
+
+/// Get a boolean value in the manifest.
+fn manifest_bool(value: &Value, key: &str) -> Result<bool, MldError>
+{
+	value.as_bool().ok_or_else(|| invalid_value(key, "a boolean"))
+}
+
+/// Get an array of the strings in the manifest.
+fn manifest_str_array(value: &Value, key: &str) -> Result<Vec<String>, MldError>
+{
+	value.as_array()
+		.and_then(|array|
+		{
+			array.iter()
+				.map(|element| {element.as_str().map(String::from)})
+				.collect::<Option<Vec<String>>>()
+		})
+		.ok_or_else(|| invalid_value(key, "an array of strings"))
+}
+
+/// Get the table of the defines in the manifest, dropping the ones defined to
+/// `false`.
+fn manifest_defines(value: &Value, key: &str)
+	-> Result<Vec<(String, Option<String>)>, MldError>
+{
+	let table = value.as_table()
+		.ok_or_else(|| invalid_value(key, "a table"))?;
+	let mut defines = Vec::new();
+
+	for (name, value) in table {
+		match value {
+			Value::String(value) => defines.push((name.clone(), Some(value.clone()))),
+			Value::Integer(value) => defines.push((name.clone(), Some(value.to_string()))),
+			Value::Boolean(true) => defines.push((name.clone(), None)),
+			Value::Boolean(false) => {},
+			_ => {
+				return Err(invalid_value(
+					&format!("{key}.{name}"),
+					"a string, an integer or a boolean"));
+			},
+		}
+	}
+
+	Ok(defines)
+}
+
+/// Create the error of an invalid value in the manifest.
+fn invalid_value(key: &str, expected: &str) -> MldError
+{
+	MldError::from(format!("{MANIFEST_METADATA_KEY}.{key} MUST be {expected}"))
+}
+
+/// Create the error of an unknown key in the manifest.
+fn unknown_key(key: &str) -> MldError
+{
+	MldError::from(format!("{MANIFEST_METADATA_KEY}.{key} MUST NOT be configured; unknown key"))
+}
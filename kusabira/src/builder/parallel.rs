@@ -0,0 +1,146 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//!
+//! The worker threads running the jobs in parallel with the current thread,
+//! limited by the [`Cargo`](https://doc.rust-lang.org/cargo/) jobserver.
+//!
+//! Refer to [`super::Config::parallel_bindgen`] for the usage.
+//!
+
+#![deny(missing_docs)]
+
+use jobserver::{Acquired, Client};
+use std::collections::VecDeque;
+use std::panic::resume_unwind;
+use std::sync::Mutex;
+use std::thread::{available_parallelism, scope};
+
+///
+/// Run `work` on each of `jobs` in the worker threads while `main` runs on
+/// the current thread.
+///
+/// A worker thread is spawned for each jobserver token acquired without
+/// blocking, or for each available CPU but the current one if the
+/// jobserver is not available.  After `main` returns, the current thread
+/// also runs the remaining jobs by its implicit token, so that the jobs
+/// complete even if no token is acquired.
+///
+/// Returns the results of `work` in the order of `jobs`, and the result of
+/// `main`.  A panic in a worker thread is resumed on the current thread.
+///
+pub(super) fn run_while<J, R, W, M, MR>(jobs: Vec<J>, work: W, main: M) -> (Vec<R>, MR)
+	where J: Send, R: Send, W: Fn(J) -> R + Sync, M: FnOnce() -> MR
+{
+	let num_jobs = jobs.len();
+	let queue = Mutex::new(jobs.into_iter().enumerate().collect::<VecDeque<_>>());
+	let done = Mutex::new(Vec::with_capacity(num_jobs));
+	let drain = || {
+		loop {
+			let Some((index, job)) = queue.lock().expect("job queue MUST NOT be poisoned").pop_front() else {
+				break;
+			};
+			let result = work(job);
+			done.lock().expect("job results MUST NOT be poisoned").push((index, result));
+		}
+	};
+
+	let main_result = scope(|scope| {
+		let workers: Vec<_> = worker_tokens(num_jobs.saturating_sub(1))
+			.into_iter()
+			.map(|token| {
+				let drain = &drain;
+				scope.spawn(move || {
+					drain();
+					drop(token);
+				})
+			})
+			.collect();
+		let main_result = main();
+		drain();
+		for worker in workers {
+			if let Err(panic) = worker.join() {
+				resume_unwind(panic);
+			}
+		}
+		main_result
+	});
+
+	let mut done = done.into_inner().expect("job results MUST NOT be poisoned");
+	done.sort_by_key(|(index, _)| {*index});
+
+	(done.into_iter().map(|(_, result)| {result}).collect(), main_result)
+}
+
+///
+/// Acquire up to `limit` jobserver tokens without blocking, one per worker
+/// thread.
+///
+/// The tokens are [`None`] if the jobserver is not available.
+///
+fn worker_tokens(limit: usize) -> Vec<Option<Acquired>>
+{
+	if limit == 0 {
+		return Vec::new();
+	}
+
+	// SAFETY: The jobserver file descriptors inherited from Cargo are not
+	// used for any other purposes by the build script.
+	match unsafe {Client::from_env()} {
+		Some(client) => {
+			let mut tokens = Vec::new();
+			while tokens.len() < limit {
+				match client.try_acquire() {
+					Ok(Some(token)) => tokens.push(Some(token)),
+					_ => break,
+				}
+			}
+			tokens
+		},
+		None => {
+			let cpus = available_parallelism().map_or(1, |cpus| {cpus.get()});
+			(0..limit.min(cpus - 1)).map(|_| {None}).collect()
+		},
+	}
+}
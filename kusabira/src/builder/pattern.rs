@@ -0,0 +1,357 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//!
+//! The input file pattern handling of [`super::Config::build`], namely the
+//! brace expansion, the exclusion patterns, the ignore files and the
+//! resolution against the source root.
+//!
+
+#![deny(missing_docs)]
+
+use glob::Pattern;
+use ignore::Match;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::collections::HashMap;
+use std::env;
+use std::path::{Component, Path, PathBuf};
+
+use super::super::error::Error as MldError;
+
+/// The prefix of an input file pattern to mark it as an exclusion pattern.
+pub static EXCLUDE_PREFIX: char = '!';
+
+/// The ignore files honored by [`IgnoreFiles`], in the ascending order of
+/// the precedence.
+pub static IGNORE_FILENAMES: [&str; 2] =
+[
+	".gitignore",
+	".ignore",
+];
+
+/// The directory name marking the top of a repository, above which the
+/// ignore files are not searched.
+static REPOSITORY_MARKER: &str = ".git";
+
+///
+/// Expand the brace sets in a [`glob`] pattern.
+///
+/// A brace set is a comma-separated list of the alternatives wrapped by `{`
+/// and `}`, eg `src/{core,io}/*.c`, which expands into `src/core/*.c` and
+/// `src/io/*.c`.  The brace sets MAY be nested.
+///
+/// A brace set without any comma, an unbalanced brace and any braces within
+/// a character class (`[...]`) are left as they are.
+///
+pub fn expand_braces(pattern: &str) -> Vec<String>
+{
+	match find_brace_set(pattern) {
+		Some((open, close, commas)) => {
+			let prefix = &pattern[..open];
+			let suffix = &pattern[close + 1..];
+			let mut bounds = vec![open];
+			bounds.extend(commas);
+			bounds.push(close);
+
+			bounds.windows(2)
+				.flat_map(|w|
+				{
+					let alternative = &pattern[w[0] + 1..w[1]];
+					expand_braces(&format!("{prefix}{alternative}{suffix}"))
+				})
+				.collect()
+		},
+		None => vec![pattern.to_string()],
+	}
+}
+
+/// Find the first brace set with at least one comma.
+///
+/// Return the byte positions of the opening brace, the closing brace and
+/// the commas at the top level of the set.
+fn find_brace_set(pattern: &str) -> Option<(usize, usize, Vec<usize>)>
+{
+	let bytes = pattern.as_bytes();
+	let mut i = 0;
+
+	while i < bytes.len() {
+		match bytes[i] {
+			b'[' => {
+				i = skip_char_class(bytes, i);
+			},
+			b'{' => {
+				if let Some((close, commas)) = match_brace(bytes, i) {
+					if !commas.is_empty() {
+						return Some((i, close, commas));
+					}
+				}
+				i += 1;
+			},
+			_ => {
+				i += 1;
+			},
+		}
+	}
+
+	None
+}
+
+/// Find the closing brace matching the opening one at `open`, as well as the
+/// commas at the top level.
+fn match_brace(bytes: &[u8], open: usize) -> Option<(usize, Vec<usize>)>
+{
+	let mut depth = 0;
+	let mut commas = Vec::new();
+	let mut i = open + 1;
+
+	while i < bytes.len() {
+		match bytes[i] {
+			b'[' => {
+				i = skip_char_class(bytes, i);
+				continue;
+			},
+			b'{' => depth += 1,
+			b'}' if depth == 0 => return Some((i, commas)),
+			b'}' => depth -= 1,
+			b',' if depth == 0 => commas.push(i),
+			_ => {},
+		}
+		i += 1;
+	}
+
+	None
+}
+
+/// Return the position next to the character class starting at `open`, or
+/// next to `open` if the class is not closed.
+///
+/// As in [`glob::Pattern`], `]` right after `[` or `[!` is a member of the
+/// class.
+fn skip_char_class(bytes: &[u8], open: usize) -> usize
+{
+	let mut i = open + 1;
+	if bytes.get(i) == Some(&b'!') {
+		i += 1;
+	}
+	if bytes.get(i) == Some(&b']') {
+		i += 1;
+	}
+	match bytes[i.min(bytes.len())..].iter().position(|&b| {b == b']'}) {
+		Some(pos) => i + pos + 1,
+		None => open + 1,
+	}
+}
+
+///
+/// Split the input file patterns into the inclusion and exclusion ones.
+///
+/// The patterns prefixed by [`EXCLUDE_PREFIX`] are the exclusion ones, with
+/// the prefix stripped.  The brace sets are expanded in both kinds of the
+/// patterns.
+///
+pub fn split_patterns<'b, IT>(patterns: IT) -> (Vec<String>, Vec<String>)
+	where IT: Iterator<Item = &'b str>
+{
+	let mut includes = Vec::new();
+	let mut excludes = Vec::new();
+
+	for pattern in patterns {
+		match pattern.strip_prefix(EXCLUDE_PREFIX) {
+			Some(exclude) => excludes.extend(expand_braces(exclude)),
+			None => includes.extend(expand_braces(pattern)),
+		}
+	}
+
+	(includes, excludes)
+}
+
+///
+/// Resolve a [`glob`] pattern against the source root.
+///
+/// A relative pattern is joined to `source_root`, which is escaped so that
+/// any [`glob`] special characters in it are matched literally.  The
+/// resolved pattern is then normalized as [`normalize_path`] does, except
+/// that the parent directory components following the wildcard components
+/// are kept, as they depend on what the wildcards match.
+///
+/// Fails if the source root or the pattern does not make a valid string.
+///
+pub fn resolve_pattern(source_root: &Path, pattern: &str) -> Result<String, MldError>
+{
+	let pattern_path = Path::new(pattern);
+	let resolved = if pattern_path.is_absolute() {
+		pattern_path.to_path_buf()
+	} else {
+		let source_root = source_root.to_str().ok_or_else(
+			|| MldError::from(
+				format!("source root {} MUST make a valid string", source_root.display())))?;
+		PathBuf::from(Pattern::escape(source_root)).join(pattern_path)
+	};
+
+	normalize(&resolved, true)
+		.to_str()
+		.map(str::to_string)
+		.ok_or_else(|| MldError::from(format!("resolved pattern {pattern} MUST make a valid string")))
+}
+
+///
+/// Normalize a path lexically, ie without accessing the file system.
+///
+/// The current directory components (`.`) are removed, and the parent
+/// directory components (`..`) are resolved against the preceding normal
+/// components.  The symbolic links are not resolved.
+///
+pub fn normalize_path(path: &Path) -> PathBuf
+{
+	normalize(path, false)
+}
+
+///
+/// Normalize a path or, if `pattern`, a [`glob`] pattern lexically.
+///
+/// The parent directory components of a pattern are not resolved against
+/// the preceding components containing the wildcards.
+///
+fn normalize(path: &Path, pattern: bool) -> PathBuf
+{
+	let mut normalized = PathBuf::new();
+
+	for component in path.components() {
+		match component {
+			Component::CurDir => {},
+			Component::ParentDir => {
+				match normalized.components().next_back() {
+					Some(Component::Normal(last))
+						if pattern && last.to_string_lossy().contains(['*', '?', '[']) => {
+						normalized.push(component);
+					},
+					Some(Component::Normal(_)) => {
+						normalized.pop();
+					},
+					Some(Component::RootDir) | Some(Component::Prefix(_)) => {},
+					_ => normalized.push(component),
+				}
+			},
+			_ => normalized.push(component),
+		}
+	}
+
+	normalized
+}
+
+///
+/// The matcher of the ignore files, as listed in [`IGNORE_FILENAMES`].
+///
+/// The ignore files are looked up in the directory of a path and its
+/// ancestors, up to the top of the repository.  The ignore file in a deeper
+/// directory precedes.  The syntax is the same as
+/// [`.gitignore`](https://git-scm.com/docs/gitignore).
+///
+#[derive(Debug, Default)]
+pub struct IgnoreFiles
+{
+	matchers: HashMap<PathBuf, Option<Gitignore>>,
+}
+
+impl IgnoreFiles
+{
+	/// Test if the given path is ignored by any ignore files.
+	pub fn is_ignored(&mut self, path: &Path) -> Result<bool, MldError>
+	{
+		let path = if path.is_absolute() {
+			path.to_path_buf()
+		} else {
+			env::current_dir()?.join(path)
+		};
+
+		for dir in path.ancestors().skip(1) {
+			if let Some(matcher) = self.matcher(dir)? {
+				match matcher.matched_path_or_any_parents(&path, false) {
+					Match::Ignore(_) => return Ok(true),
+					Match::Whitelist(_) => return Ok(false),
+					Match::None => {},
+				}
+			}
+			if dir.join(REPOSITORY_MARKER).exists() {
+				break;
+			}
+		}
+
+		Ok(false)
+	}
+
+	/// Look up the matcher of the ignore files in the given directory,
+	/// loading them upon the first lookup.
+	fn matcher(&mut self, dir: &Path) -> Result<&Option<Gitignore>, MldError>
+	{
+		if !self.matchers.contains_key(dir) {
+			let mut builder = GitignoreBuilder::new(dir);
+			let mut found = false;
+			for filename in IGNORE_FILENAMES {
+				let ignore_file = dir.join(filename);
+				if ignore_file.is_file() {
+					if let Some(err) = builder.add(&ignore_file) {
+						return Err(MldError::from(
+							format!("ignore file {} MUST be valid: {err}",
+								ignore_file.display())));
+					}
+					println!("cargo:rerun-if-changed={}", ignore_file.display());
+					found = true;
+				}
+			}
+			let matcher = if found {
+				Some(builder.build().map_err(|err| {
+					MldError::from(
+						format!("ignore files in {} MUST be valid: {err}",
+							dir.display()))
+				})?)
+			} else {
+				None
+			};
+			self.matchers.insert(dir.to_path_buf(), matcher);
+		}
+
+		Ok(self.matchers.get(dir).expect("matcher MUST be cached"))
+	}
+}
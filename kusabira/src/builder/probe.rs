@@ -0,0 +1,488 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//!
+//! The feature probes run by [`super::Config::build`], the equivalent of
+//! `AC_CHECK_HEADERS`, `AC_CHECK_FUNCS` and `AC_CHECK_SIZEOF` of Autoconf and
+//! `check_c_compiler_flag` of CMake.
+//!
+//! Refer to [`super::Config::add_probe`] for the usage.
+//!
+
+#![deny(missing_docs)]
+
+use std::env::consts::EXE_SUFFIX;
+use std::fmt::{Display, Error as FmtError, Formatter};
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::Path;
+use toml::{Table, Value};
+
+#[cfg(not(test))]
+use cc::Tool;
+#[cfg(test)]
+use super::tests::busshi::cc_build::Tool;
+use super::Build;
+use super::super::error::Error as MldError;
+
+/// The upper bound of the type size in bytes found by a size probe.
+static SIZEOF_MAX: u64 = 1 << 16;
+
+/// The cache file of the probe results, relative to the probe directory.
+static CACHE_FILENAME: &str = "cache.toml";
+
+///
+/// The kind of a [`Probe`].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ProbeKind
+{
+	/// Check if a header can be included.
+	Header,
+	/// Check if a function can be linked.
+	Function,
+	/// Find the size of a type.
+	SizeOf,
+	/// Check if the compiler accepts a flag.
+	Flag,
+}
+
+impl Display for ProbeKind
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError>
+	{
+		write!(f, "{}", match self {
+			ProbeKind::Header => "header",
+			ProbeKind::Function => "function",
+			ProbeKind::SizeOf => "sizeof",
+			ProbeKind::Flag => "flag",
+		})
+	}
+}
+
+///
+/// A feature probe run by [`super::Config::build`] with the compiler and
+/// flags of the [`cc::Build`] for C of the default library.
+///
+/// The result of a probe is named after the subject in the convention of
+/// Autoconf, eg `HAVE_SYS_EPOLL_H` for the header `sys/epoll.h`, or by
+/// [`Probe::name`].  The result is:
+///
+/// * `1` or unset for the header, function and flag probes.
+/// * The size in bytes, or unset if the type is not found, for the size
+///   probes.
+///
+/// The results are emitted as
+/// [`cargo:rustc-cfg`](https://doc.rust-lang.org/cargo/reference/build-scripts.html#rustc-cfg)
+/// with the name in the lower case, eg `have_sys_epoll_h` and
+/// `sizeof_long_double = "16"`, along with
+/// [`cargo:rustc-check-cfg`](https://doc.rust-lang.org/cargo/reference/build-scripts.html#rustc-check-cfg),
+/// and can be set to the configuration headers by
+/// [`super::ConfigHeader::probes`].
+///
+/// All of the configuration methods return `self` by the value, so the method
+/// calls can be chained.
+///
+/// # Example
+/// ```
+/// use kusabira::builder::{Config, ConfigHeader, Probe};
+///
+/// let config = Config::default()
+/// 	.lib_name("foo")
+/// 	.input_file("src/*.c")
+/// 	.add_probe(Probe::header("sys/epoll.h"))
+/// 	.add_probe(Probe::function("pthread_setname_np"))
+/// 	.add_probe(Probe::size_of("long double"))
+/// 	.add_probe(Probe::flag("-mavx2"))
+/// 	.add_config_header(ConfigHeader::new("config.h")
+/// 		.probes());
+/// ```
+///
+#[derive(Clone, Debug)]
+pub struct Probe
+{
+	kind: ProbeKind,
+	subject: String,
+	pub(super) name: String,
+	includes: Vec<String>,
+}
+
+impl Probe
+{
+	///
+	/// Create the probe checking if the header can be included, named
+	/// `HAVE_<HEADER>`.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Probe;
+	///
+	/// let probe = Probe::header("sys/epoll.h");
+	/// ```
+	///
+	pub fn header(header: &str) -> Probe
+	{
+		Probe::new(ProbeKind::Header, header, "HAVE_")
+	}
+
+	///
+	/// Create the probe checking if the function can be linked, named
+	/// `HAVE_<FUNCTION>`.
+	///
+	/// The function is declared by the probe itself as Autoconf does, so
+	/// the included headers by [`Probe::include`] are not included.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Probe;
+	///
+	/// let probe = Probe::function("pthread_setname_np");
+	/// ```
+	///
+	pub fn function(function: &str) -> Probe
+	{
+		Probe::new(ProbeKind::Function, function, "HAVE_")
+	}
+
+	///
+	/// Create the probe finding the size of the type, named
+	/// `SIZEOF_<TYPE>`, eg `SIZEOF_VOID_P` for `void *`.
+	///
+	/// The size is found at the compile time, so the probe works on the
+	/// cross build as well.  A type larger than 65536 bytes is not found.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Probe;
+	///
+	/// let probe = Probe::size_of("long double");
+	/// ```
+	///
+	pub fn size_of(type_name: &str) -> Probe
+	{
+		Probe::new(ProbeKind::SizeOf, type_name, "SIZEOF_")
+	}
+
+	///
+	/// Create the probe checking if the compiler accepts the flag, named
+	/// `HAVE_FLAG_<FLAG>`, eg `HAVE_FLAG_MAVX2` for `-mavx2`.
+	///
+	/// The warnings are turned into the errors, ie by `-Werror` for the GNU
+	/// compatible compilers or `/WX` for MSVC, as the compilers only warn
+	/// some of the unknown flags.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Probe;
+	///
+	/// let probe = Probe::flag("-mavx2");
+	/// ```
+	///
+	pub fn flag(flag: &str) -> Probe
+	{
+		Probe::new(ProbeKind::Flag, flag, "HAVE_FLAG_")
+	}
+
+	///
+	/// Set the name of the result, instead of the one after the subject.
+	///
+	/// The name SHOULD be a valid C macro name in order to be set to the
+	/// configuration headers.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Probe;
+	///
+	/// let probe = Probe::header("sys/epoll.h")
+	/// 	.name("FOO_HAVE_EPOLL");
+	/// ```
+	///
+	pub fn name(mut self, name: &str) -> Self
+	{
+		self.name = name.to_string();
+		self
+	}
+
+	///
+	/// Add a header included before the subject, eg the one declaring the
+	/// type of a size probe.
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::builder::Probe;
+	///
+	/// let probe = Probe::size_of("off_t")
+	/// 	.include("sys/types.h");
+	/// ```
+	///
+	pub fn include(mut self, header: &str) -> Self
+	{
+		self.includes.push(header.to_string());
+		self
+	}
+
+	/// Create the probe named by the prefix and the subject.
+	fn new(kind: ProbeKind, subject: &str, prefix: &str) -> Probe
+	{
+		Probe {
+			kind,
+			subject: subject.to_string(),
+			name: format!("{prefix}{}", macro_name(subject)),
+			includes: Vec::new(),
+		}
+	}
+
+	/// Get the `#include` lines of the probe program.
+	fn include_lines(&self) -> String
+	{
+		self.includes
+			.iter()
+			.map(|header| {format!("#include <{header}>\n")})
+			.collect()
+	}
+
+	///
+	/// Run the probe by `compiler` in `probe_dir`.
+	///
+	/// Returns the result value, or [`None`] if unset.
+	///
+	fn run(&self, compiler: &Tool, probe_dir: &Path) -> Result<Option<String>, MldError>
+	{
+		let found = |found: bool| {found.then(|| {"1".to_string()})};
+		match self.kind {
+			ProbeKind::Header => {
+				let program = format!("{}#include <{}>\nint main(void) {{return 0;}}\n",
+					self.include_lines(), self.subject);
+				Ok(found(self.compile(compiler, probe_dir, &program, false)?))
+			},
+			ProbeKind::Function => {
+				// Declare the function with a wrong prototype as Autoconf does;
+				// only the symbol matters for the link.
+				let program = format!(concat!(
+					"#ifdef __cplusplus\nextern \"C\"\n#endif\n",
+					"char {0}(void);\n",
+					"int main(void) {{return {0}();}}\n"),
+					self.subject);
+				Ok(found(self.compile(compiler, probe_dir, &program, true)?))
+			},
+			ProbeKind::SizeOf => {
+				let program = |bound: u64| {
+					format!("{}typedef char kusabira_probe[(sizeof({}) <= {bound}) ? 1 : -1];\n",
+						self.include_lines(), self.subject)
+				};
+				if !self.compile(compiler, probe_dir, &program(SIZEOF_MAX), false)? {
+					return Ok(None);
+				}
+				// Grow the upper bound first to keep the binary search short for
+				// the small types.
+				let mut lower = 1;
+				let mut upper = 1;
+				while !self.compile(compiler, probe_dir, &program(upper), false)? {
+					lower = upper + 1;
+					upper *= 2;
+				}
+				while lower < upper {
+					let middle = (lower + upper) / 2;
+					if self.compile(compiler, probe_dir, &program(middle), false)? {
+						upper = middle;
+					} else {
+						lower = middle + 1;
+					}
+				}
+				Ok(Some(upper.to_string()))
+			},
+			ProbeKind::Flag => {
+				let program = format!("{}int main(void) {{return 0;}}\n", self.include_lines());
+				Ok(found(self.compile(compiler, probe_dir, &program, false)?))
+			},
+		}
+	}
+
+	///
+	/// Compile the probe program, and link it if `link`.
+	///
+	/// Returns `true` if the compiler succeeds.
+	///
+	fn compile(&self, compiler: &Tool, probe_dir: &Path, program: &str, link: bool)
+		-> Result<bool, MldError>
+	{
+		let stem = probe_dir.join(self.name.to_lowercase());
+		let source_file = stem.with_extension("c");
+		write(&source_file, program)?;
+
+		let mut command = compiler.to_command();
+		let is_like_msvc = compiler.is_like_msvc();
+		if self.kind == ProbeKind::Flag {
+			command.arg(&self.subject)
+				.arg(if is_like_msvc {"/WX"} else {"-Werror"});
+		}
+		match (is_like_msvc, link) {
+			(true, true) => {
+				command.arg(&source_file)
+					.arg(format!("/Fe{}{EXE_SUFFIX}", stem.display()));
+			},
+			(true, false) => {
+				command.arg("/c")
+					.arg(&source_file)
+					.arg(format!("/Fo{}", stem.with_extension("obj").display()));
+			},
+			(false, true) => {
+				command.arg("-o")
+					.arg(format!("{}{EXE_SUFFIX}", stem.display()))
+					.arg(&source_file);
+			},
+			(false, false) => {
+				command.arg("-c")
+					.arg("-o")
+					.arg(stem.with_extension("o"))
+					.arg(&source_file);
+			},
+		}
+		command.current_dir(probe_dir);
+		Ok(command.status()?.success())
+	}
+
+	///
+	/// Emit the result to [`Cargo`](https://doc.rust-lang.org/cargo/) as
+	/// `cargo:rustc-cfg` and `cargo:rustc-check-cfg`.
+	///
+	fn emit_cfg(&self, value: Option<&str>)
+	{
+		let cfg_name = self.name.to_lowercase();
+		if self.kind == ProbeKind::SizeOf {
+			println!("cargo:rustc-check-cfg=cfg({cfg_name}, values(any()))");
+			if let Some(value) = value {
+				println!("cargo:rustc-cfg={cfg_name}=\"{value}\"");
+			}
+		} else {
+			println!("cargo:rustc-check-cfg=cfg({cfg_name})");
+			if value.is_some() {
+				println!("cargo:rustc-cfg={cfg_name}");
+			}
+		}
+	}
+}
+
+impl Display for Probe
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError>
+	{
+		write!(f, "{} {}", self.kind, self.subject)?;
+		for header in &self.includes {
+			write!(f, " <{header}>")?;
+		}
+		Ok(())
+	}
+}
+
+///
+/// Run the probes by the compiler of `build` in `out_dir/probes`.
+///
+/// A result is cached in `out_dir/probes/cache.toml` by the probe and the
+/// compiler command, so the probe reruns when the compiler or its flags
+/// change.
+///
+/// Returns the name and value pairs of the results, in the order of `probes`.
+///
+pub(super) fn run_probes(probes: &[Probe], build: &Build, out_dir: &Path)
+	-> Result<Vec<(String, Option<String>)>, MldError>
+{
+	let mut values = Vec::new();
+	if probes.is_empty() {
+		return Ok(values);
+	}
+
+	let probe_dir = out_dir.join("probes");
+	create_dir_all(&probe_dir)?;
+	let compiler = build.try_get_compiler()?;
+	let compiler_command = format!("{:?}", compiler.to_command());
+	let cache_path = probe_dir.join(CACHE_FILENAME);
+	let cached: Table = read_to_string(&cache_path)
+		.ok()
+		.and_then(|cache| {cache.parse().ok()})
+		.unwrap_or_default();
+	let mut cache = cached.clone();
+
+	for probe in probes {
+		let key = format!("{probe} by {compiler_command}");
+		let value = match cache.get(&key) {
+			Some(Value::String(value)) => Some(value.clone()),
+			Some(Value::Boolean(false)) => None,
+			_ => {
+				let value = probe.run(&compiler, &probe_dir)?;
+				cache.insert(key, value.clone().map_or(Value::Boolean(false), Value::String));
+				value
+			},
+		};
+		eprintln!("Probed {probe}: {}", value.as_deref().unwrap_or("not found"));
+		probe.emit_cfg(value.as_deref());
+		values.push((probe.name.clone(), value));
+	}
+
+	if cache != cached {
+		write(&cache_path, cache.to_string())?;
+	}
+
+	Ok(values)
+}
+
+///
+/// Convert a probe subject into the macro name in the convention of
+/// Autoconf; the letters and digits in the upper case, `*` into `P` and the
+/// others into `_`, without the repeated, leading or trailing `_`.
+///
+fn macro_name(subject: &str) -> String
+{
+	let mut name = String::new();
+	for c in subject.chars() {
+		let c = match c {
+			'*' => 'P',
+			c if c.is_ascii_alphanumeric() => c.to_ascii_uppercase(),
+			_ => '_',
+		};
+		if c != '_' || !(name.is_empty() || name.ends_with('_')) {
+			name.push(c);
+		}
+	}
+	name.trim_end_matches('_').to_string()
+}
@@ -0,0 +1,263 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//!
+//! The system libraries found by `pkg-config` during
+//! [`super::Config::build`].
+//!
+//! Refer to [`super::Config::system_library`] for the usage.
+//!
+
+#![deny(missing_docs)]
+
+use std::env;
+use std::ops::Bound;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[cfg(not(test))]
+use pkg_config::Config as PkgConfig;
+#[cfg(test)]
+use super::tests::busshi::pkg_config_config::Config as PkgConfig;
+use super::super::error::Error as MldError;
+
+///
+/// A system library found by `pkg-config`.
+///
+#[derive(Debug)]
+pub(super) struct SystemLibrary
+{
+	/// The version found.
+	pub(super) version: String,
+	/// The include directories.
+	pub(super) include_dirs: Vec<PathBuf>,
+	/// The macro definitions, sorted by the name.
+	pub(super) defines: Vec<(String, Option<String>)>,
+	/// The [`Cargo`](https://doc.rust-lang.org/cargo/) metadata to link the
+	/// library, emitted after the internal libraries.
+	pub(super) link_metadata: Vec<String>,
+}
+
+///
+/// Find the system library of `name` matching the `version` requirement by
+/// `pkg-config`.
+///
+/// The `cargo:rerun-if-env-changed` metadata on the `pkg-config` environment
+/// variables is emitted immediately, while the link metadata is returned
+/// so that it is emitted after the internal libraries.
+///
+pub(super) fn find_system_library(name: &str, version: &str)
+	-> Result<SystemLibrary, MldError>
+{
+	let library = PkgConfig::new()
+		.range_version(version_bounds(version)?)
+		.cargo_metadata(false)
+		.env_metadata(true)
+		.probe(name)?;
+
+	let mut defines: Vec<(String, Option<String>)> = library.defines
+		.iter()
+		.map(|(name, value)| {(name.clone(), value.clone())})
+		.collect();
+	defines.sort();
+
+	let mut link_metadata = Vec::new();
+	for link_path in &library.link_paths {
+		link_metadata.push(format!("cargo:rustc-link-search=native={}", link_path.display()));
+	}
+	for framework_path in &library.framework_paths {
+		link_metadata.push(format!("cargo:rustc-link-search=framework={}", framework_path.display()));
+	}
+	let statik = is_static(name);
+	for lib in &library.libs {
+		if statik && is_static_available(lib, &library.link_paths) {
+			link_metadata.push(format!("cargo:rustc-link-lib=static={lib}"));
+		} else {
+			link_metadata.push(format!("cargo:rustc-link-lib={lib}"));
+		}
+	}
+	for framework in &library.frameworks {
+		link_metadata.push(format!("cargo:rustc-link-lib=framework={framework}"));
+	}
+	let target = env::var("TARGET").unwrap_or_default();
+	for link_file in &library.link_files {
+		link_metadata.extend(link_file_metadata(&target, link_file));
+	}
+	for ld_args in &library.ld_args {
+		link_metadata.push(format!("cargo:rustc-link-arg=-Wl,{}", ld_args.join(",")));
+	}
+
+	Ok(SystemLibrary {
+		version: library.version.clone(),
+		include_dirs: library.include_paths.clone(),
+		defines,
+		link_metadata,
+	})
+}
+
+///
+/// Tell if the system library of `name` is linked statically.
+///
+/// The environment variables `<NAME>_STATIC`, `<NAME>_DYNAMIC`,
+/// `PKG_CONFIG_ALL_STATIC` and `PKG_CONFIG_ALL_DYNAMIC` are looked up in
+/// this order, following `pkg_config::Config::probe`.
+///
+fn is_static(name: &str) -> bool
+{
+	let name = name.to_ascii_uppercase().replace('-', "_");
+	if env::var_os(format!("{name}_STATIC")).is_some() {
+		true
+	} else if env::var_os(format!("{name}_DYNAMIC")).is_some() {
+		false
+	} else {
+		env::var_os("PKG_CONFIG_ALL_STATIC").is_some()
+	}
+}
+
+///
+/// Tell if the static library `lib` is in any of `link_paths` out of the
+/// system directories, following `pkg_config`; a system library is linked
+/// dynamically even if the static link is asked for.
+///
+fn is_static_available(lib: &str, link_paths: &[PathBuf]) -> bool
+{
+	let system_roots = if cfg!(target_os = "macos") {
+		vec![PathBuf::from("/Library"), PathBuf::from("/System")]
+	} else {
+		vec![env::var_os("PKG_CONFIG_SYSROOT_DIR")
+			.or_else(|| {env::var_os("SYSROOT")})
+			.map(PathBuf::from)
+			.unwrap_or_else(|| {PathBuf::from("/usr")})]
+	};
+	let mut lib_names = vec![format!("lib{lib}.a")];
+	if cfg!(target_os = "windows") {
+		lib_names.push(format!("{lib}.lib"));
+	}
+
+	link_paths.iter().any(|link_path| {
+		lib_names.iter().any(|lib_name| {link_path.join(lib_name).exists()})
+			&& !system_roots.iter().any(|system_root| {link_path.starts_with(system_root)})
+	})
+}
+
+///
+/// Make the [`Cargo`](https://doc.rust-lang.org/cargo/) metadata to link a
+/// library file given by the path in the `pkg-config` output.
+///
+/// The file is split into the `rustc-link-search` directory and the
+/// `rustc-link-lib` name, following `pkg-config`, so that the library is
+/// linked into the dependent packages as well.  The name is extracted by the
+/// same rules as `pkg_config::Library::extract_lib_from_filename`; a file
+/// out of the rules falls back to `rustc-link-arg`.
+///
+pub(super) fn link_file_metadata(target: &str, link_file: &Path) -> Vec<String>
+{
+	let lib_name = link_file.file_name()
+		.and_then(|file_name| {file_name.to_str()})
+		.and_then(|file_name| {
+			let (file_name, suffixes): (Option<&str>, &[&str]) = if target.contains("windows") {
+				let file_name = file_name.strip_prefix("lib")
+					.filter(|_| {target.contains("gnu")})
+					.unwrap_or(file_name);
+				(Some(file_name), &[".dll.a", ".dll", ".lib", ".a"])
+			} else if target.contains("apple") {
+				(file_name.strip_prefix("lib"), &[".a", ".so", ".dylib"])
+			} else {
+				(file_name.strip_prefix("lib"), &[".a", ".so"])
+			};
+			file_name.and_then(|file_name| {
+				suffixes.iter().find_map(|suffix| {file_name.strip_suffix(suffix)})
+			})
+		});
+
+	match (link_file.parent(), lib_name) {
+		(Some(dir), Some(lib_name)) if !lib_name.is_empty() => vec![
+			format!("cargo:rustc-link-search=native={}", dir.display()),
+			format!("cargo:rustc-link-lib={lib_name}"),
+		],
+		_ => vec![format!("cargo:rustc-link-arg={}", link_file.display())],
+	}
+}
+
+///
+/// Parse a version requirement into the lower and upper bounds.
+///
+/// The requirement is a comma-separated list of the constraints `>=V`, `>V`,
+/// `<=V`, `<V` and `=V`.  A bare `V` is the same as `>=V`, following
+/// `system-deps`.  An empty requirement or `*` accepts any version.
+///
+pub(super) fn version_bounds(version: &str)
+	-> Result<(Bound<&str>, Bound<&str>), MldError>
+{
+	let mut lower = Bound::Unbounded;
+	let mut upper = Bound::Unbounded;
+	for constraint in version.split(',').map(str::trim) {
+		if constraint.is_empty() || constraint == "*" {
+			continue;
+		}
+		let (operator, version) = constraint.split_at(
+			constraint.find(|c: char| {!"<>=".contains(c)}).unwrap_or(constraint.len()));
+		let version = version.trim();
+		if version.is_empty() {
+			return Err(MldError::from(
+				format!("version requirement {constraint} MUST have a version")));
+		}
+		match operator {
+			">=" | "" => lower = Bound::Included(version),
+			">" => lower = Bound::Excluded(version),
+			"<=" => upper = Bound::Included(version),
+			"<" => upper = Bound::Excluded(version),
+			"=" | "==" => {
+				lower = Bound::Included(version);
+				upper = Bound::Included(version);
+			},
+			_ => {
+				return Err(MldError::from(
+					format!("version requirement {constraint} MUST have a valid operator")));
+			},
+		}
+	}
+
+	Ok((lower, upper))
+}
@@ -51,6 +51,7 @@ use std::io::{Error as StdIoError, ErrorKind as StdIoErrorKind};
 use std::path::MAIN_SEPARATOR;
 
 use super::*;
+use busshi::cc_build::{CcBuildContext, CcBuildContextAccess};
 
 pub mod busshi;
 
@@ -70,8 +71,11 @@ fn test_default_contents()
 	let config = Config::default();
 
 	assert_eq!(config.out_dir, out_dir);
+	assert_eq!(config.source_root,
+		env::var_os("CARGO_MANIFEST_DIR").map(StdPathBuf::from));
 	assert_eq!(config.input_files.len(), 0);
 	assert!(config.lib_name.is_none());
+	assert!(config.nasm_object_format.is_none());
 	// The ([`Config::cc_build_hook`],
 	// [`Config::bindgen_builder_hook`] and
 	// [`Config::glob_matchoptions_hook`]) hooks are not tested; traits
@@ -96,6 +100,16 @@ fn test_out_dir()
 		out_dir.to_str().expect("non-string on right"));
 }
 
+#[test]
+fn test_source_root_config()
+{
+	let source_root = test_source_root();
+	let config = Config::default()
+		.source_root(&source_root);
+
+	assert_eq!(config.source_root, Some(source_root));
+}
+
 #[test]
 fn test_source_file()
 {
@@ -171,13 +185,196 @@ where IT: Iterator<Item = &'a str>
 	}
 }
 
+#[test]
+fn test_exclude_input_file()
+{
+	let exclude_files = [
+		"src/**/test_*.c",
+		"src/legacy/**",
+	];
+	let config = Config::default()
+		.exclude_input_file(exclude_files[0]);
+	assert_eq!(config.exclude_input_files, [exclude_files[0]]);
+
+	let config = config.add_exclude_input_file(exclude_files[1]);
+	assert_eq!(config.exclude_input_files, exclude_files);
+
+	let config = config.exclude_input_file(exclude_files[1]);
+	assert_eq!(config.exclude_input_files, [exclude_files[1]]);
+}
+
+#[test]
+fn test_exclude_input_files()
+{
+	let exclude_files = [
+		"src/**/test_*.c",
+		"src/legacy/**",
+	];
+	let config = Config::default()
+		.exclude_input_files(exclude_files.into_iter());
+	assert_eq!(config.exclude_input_files, exclude_files);
+
+	let config = config.add_exclude_input_files(exclude_files.into_iter());
+	assert_eq!(config.exclude_input_files.len(), 2 * exclude_files.len());
+
+	let config = config.exclude_input_files(exclude_files.into_iter());
+	assert_eq!(config.exclude_input_files, exclude_files);
+}
+
+#[test]
+fn test_respect_ignore_files()
+{
+	let config = Config::default();
+	assert!(!config.respect_ignore_files);
+
+	let config = config.respect_ignore_files(true);
+	assert!(config.respect_ignore_files);
+}
+
+#[test]
+fn test_expand_braces()
+{
+	use pattern::expand_braces;
+
+	assert_eq!(expand_braces("src/*.c"), ["src/*.c"]);
+	assert_eq!(expand_braces("src/{core,io}/*.c"),
+		["src/core/*.c", "src/io/*.c"]);
+	assert_eq!(expand_braces("src/{core,io}/*.{c,h}"),
+		["src/core/*.c", "src/core/*.h", "src/io/*.c", "src/io/*.h"]);
+	assert_eq!(expand_braces("src/{core,io/{a,b}}/*.c"),
+		["src/core/*.c", "src/io/a/*.c", "src/io/b/*.c"]);
+	assert_eq!(expand_braces("src/{,sub/}*.c"),
+		["src/*.c", "src/sub/*.c"]);
+	assert_eq!(expand_braces("src/{core}/{a,b}.c"),
+		["src/{core}/a.c", "src/{core}/b.c"]);
+	assert_eq!(expand_braces("src/[{,]{a,b}.c"),
+		["src/[{,]a.c", "src/[{,]b.c"]);
+	assert_eq!(expand_braces("src/[]{]{a,b}.c"),
+		["src/[]{]a.c", "src/[]{]b.c"]);
+	assert_eq!(expand_braces("src/{a,b.c"), ["src/{a,b.c"]);
+	assert_eq!(expand_braces("src/[{a,b}.c"), ["src/[a.c", "src/[b.c"]);
+}
+
+#[test]
+fn test_split_patterns()
+{
+	use pattern::split_patterns;
+
+	let (includes, excludes) = split_patterns(
+		["src/{core,io}/*.c", "!src/**/test_*.{c,h}", "src/*.h"].into_iter());
+	assert_eq!(includes, ["src/core/*.c", "src/io/*.c", "src/*.h"]);
+	assert_eq!(excludes, ["src/**/test_*.c", "src/**/test_*.h"]);
+}
+
 #[test]
 fn test_lib_name()
 {
 	let lib_name = "hello_world";
 	let config = Config::default()
 		.lib_name(lib_name);
-	assert_eq!(config.lib_name, Some(lib_name));
+	assert_eq!(config.lib_name, Some(lib_name.to_string()));
+}
+
+#[test]
+fn test_link_kind()
+{
+	let config = Config::default();
+	assert_eq!(config.link_kind, LinkKind::Static);
+
+	let config = config.link_kind(LinkKind::WholeArchive);
+	assert_eq!(config.link_kind, LinkKind::WholeArchive);
+
+	let config = config.link_kind(LinkKind::Shared(1));
+	assert_eq!(config.link_kind, LinkKind::Shared(1));
+	println!("link_kind = {}.", config.link_kind);
+
+	let config = config.link_kind(LinkKind::Objects);
+	assert_eq!(config.link_kind, LinkKind::Objects);
+}
+
+#[test]
+fn test_add_library()
+{
+	let config = Config::default();
+	assert_eq!(config.libraries.len(), 0);
+
+	let config = config
+		.add_library(Library::new("hello_world_plugins")
+			.input_file("src/plugins/*.c")
+			.add_input_files(["src/compat/*.c", "src/compat/*.s"].into_iter())
+			.exclude_input_file("src/plugins/test_*.c")
+			.add_exclude_input_file("src/compat/test_*.c")
+			.link_kind(LinkKind::Unlinked))
+		.add_library(Library::new("hello_world_extras")
+			.exclude_input_files(["src/extras/test_*.c"].into_iter())
+			.add_exclude_input_files(["src/extras/legacy/**", "src/extras/examples/**"].into_iter()));
+	assert_eq!(config.libraries.len(), 2);
+
+	let library = &config.libraries[0];
+	assert_eq!(library.name, "hello_world_plugins");
+	assert_eq!(library.input_files,
+		["src/plugins/*.c", "src/compat/*.c", "src/compat/*.s"]);
+	assert_eq!(library.exclude_input_files,
+		["src/plugins/test_*.c", "src/compat/test_*.c"]);
+	assert_eq!(library.link_kind, LinkKind::Unlinked);
+
+	let library = &config.libraries[1];
+	assert_eq!(library.name, "hello_world_extras");
+	assert_eq!(library.input_files.len(), 0);
+	assert_eq!(library.exclude_input_files,
+		["src/extras/test_*.c", "src/extras/legacy/**", "src/extras/examples/**"]);
+	assert_eq!(library.link_kind, LinkKind::Static);
+
+	let library = Library::new("hello_world_extras")
+		.add_exclude_input_files(["src/extras/legacy/**"].into_iter())
+		.exclude_input_files(["src/extras/test_*.c"].into_iter());
+	assert_eq!(library.exclude_input_files, ["src/extras/test_*.c"]);
+}
+
+#[test]
+fn test_add_source_ext_as()
+{
+	let config = Config::default()
+		.add_source_ext("ci")
+		.add_source_ext("ii")
+		.add_source_ext_as("ino", Language::Cxx)
+		.add_source_ext_as("c", Language::Cxx)
+		.add_header_ext("hhh")
+		.add_header_ext_as("inl", Language::Cxx);
+
+	assert_eq!(config.find_filetype(Some(OsStr::new("ci"))), FileType::Source(Language::C));
+	assert_eq!(config.find_filetype(Some(OsStr::new("ii"))),
+		FileType::Source(Language::PreprocessedCxx));
+	assert_eq!(config.find_filetype(Some(OsStr::new("ino"))), FileType::Source(Language::Cxx));
+	assert_eq!(config.find_filetype(Some(OsStr::new("c"))), FileType::Source(Language::Cxx));
+	assert_eq!(config.find_filetype(Some(OsStr::new("hhh"))), FileType::Header(Language::C));
+	assert_eq!(config.find_filetype(Some(OsStr::new("inl"))), FileType::Header(Language::Cxx));
+	assert_eq!(config.cc_exts.iter().filter(|(ext, _)| {ext == "c"}).count(), 1);
+
+	assert!(Language::Cxx.is_cxx());
+	assert!(!Language::PreprocessedAssembly.is_cxx());
+	println!("language = {}.", Language::PreprocessedC);
+}
+
+#[test]
+fn test_override_file_type()
+{
+	let config = Config::default()
+		.override_file_type("src/*.c", FileType::Source(Language::Cxx))
+		.override_file_type("src/*.h", FileType::Unsupported("h".to_string()));
+
+	assert_eq!(config.file_type_overrides, [
+		("src/*.c".to_string(), FileType::Source(Language::Cxx)),
+		("src/*.h".to_string(), FileType::Unsupported("h".to_string())),
+	]);
+}
+
+#[test]
+fn test_nasm_object_format()
+{
+	let config = Config::default()
+		.nasm_object_format("elf64");
+	assert_eq!(config.nasm_object_format, Some("elf64".to_string()));
 }
 
 #[test]
@@ -192,12 +389,23 @@ fn test_binding_ext()
 // The hook configuration methods are not covered as their own unit tests;
 // refer to [`test_default_contents`] for the detail.
 
+fn test_source_root() -> StdPathBuf
+{
+	[
+		&String::from(MAIN_SEPARATOR),
+		"tmp",
+		"src_root"
+	].into_iter().collect()
+}
+
 fn test_build_setup(mut config: Config, config_lib_name: bool) -> (Config, StdPathBuf)
 {
-	use busshi::glob::{GlobContextAccess, test_glob_with_setup};
+	use busshi::glob::{GlobContextAccess, test_glob_with_setup_in};
 
+	let source_root = test_source_root();
 	let mut glob_ctx = GlobContextAccess::default();
-	test_glob_with_setup(&mut glob_ctx);
+	test_glob_with_setup_in(&mut glob_ctx, &source_root);
+	config = config.source_root(&source_root);
 
 	let out_dir: StdPathBuf =
 	[
@@ -205,6 +413,8 @@ fn test_build_setup(mut config: Config, config_lib_name: bool) -> (Config, StdPa
 		"tmp",
 		"out_dir"
 	].into_iter().collect();
+	// The generated files other than the mocked backend outputs are written.
+	std::fs::create_dir_all(&out_dir).expect("output directory MUST be created");
 	config = config.out_dir(out_dir.as_ref());
 
 	if config_lib_name {
@@ -339,6 +549,62 @@ fn test_build_success_cc_only()
 	assert_eq!(build_results.lib_name, Some("hello_world".to_string()));
 	assert_eq!(build_results.source_files.len(), 2);
 	assert_eq!(build_results.header_bindings.len(), 0);
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 1);
+	assert!(compiled[0].cpp);
+	assert_eq!(compiled[0].files.len(), 2);
+}
+
+#[test]
+fn test_build_success_c_and_cxx()
+{
+	let mut config = Config::default();
+	let out_dir;
+
+	(config, out_dir) = test_build_setup(config, true);
+
+	config = config.input_file("src/**/*.{c,cc,cpp,cxx}")
+		.link_kind(LinkKind::WholeArchive)
+		.cc_build_hook(|build| {build.define("HELLO_WORLD_C", None)})
+		.cxx_build_hook(|build| {build.define("HELLO_WORLD_CXX", None)})
+		.add_cxx_build_hook(|build| {build.std("c++17")});
+	let build_results = config.build().expect("build MUST succeed");
+	assert_eq!(build_results.out_dir, out_dir);
+	assert_eq!(build_results.source_files.len(), 12);
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 2);
+	assert_eq!(compiled[0].output, None);
+	assert!(!compiled[0].cpp);
+	assert_eq!(compiled[0].files.len(), 6);
+	assert_eq!(compiled[0].definitions, [("HELLO_WORLD_C".to_string(), None)]);
+	assert_eq!(compiled[0].link_lib_modifiers.len(), 0);
+	assert_eq!(compiled[1].output, Some("hello_world".to_string()));
+	assert!(compiled[1].cpp);
+	assert_eq!(compiled[1].files.len(), 6);
+	assert_eq!(compiled[1].objects.len(), 6);
+	assert_eq!(compiled[1].definitions, [("HELLO_WORLD_CXX".to_string(), None)]);
+	assert_eq!(compiled[1].link_lib_modifiers, ["+whole-archive"]);
+}
+
+#[test]
+fn test_build_success_c_without_cxx_build_hook()
+{
+	use std::cell::Cell;
+
+	let cxx_build_hooked = Cell::new(false);
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+
+	config
+		.input_file("src/**/*.c")
+		.cxx_build_hook(|build| {cxx_build_hooked.set(true); build})
+		.add_library(Library::new("hello_world_plugin")
+			.input_file("src/sub/*.c")
+			.cxx_build_hook(|build| {cxx_build_hooked.set(true); build}))
+		.build()
+		.expect("build MUST succeed");
+	assert!(!cxx_build_hooked.get());
 }
 
 #[test]
@@ -649,114 +915,2977 @@ fn test_build_fail_glob_pattern_error()
 }
 
 #[test]
-fn test_header_binding()
+fn test_build_success_absolute_paths()
 {
-	let header_binding = HeaderBinding::from((
-		StdPathBuf::from("hello_world_c_exported.h".to_string()),
-		StdPathBuf::from("hello_world_c_exported.in".to_string())));
+	let mut config = Config::default();
+	let _out_dir;
 
-	println!("header_binding = {}.", header_binding);
-	println!("header_binding = {:?}.", header_binding);
+	(config, _out_dir) = test_build_setup(config, true);
+
+	config = config.input_file("./src/sub/../*.[ch]")
+		.exclude_input_file("src/./hello_world_C_*.c")
+		.glob_matchoptions_hook(|mut match_options|
+		{
+			match_options.require_literal_separator = true;
+			match_options
+		});
+	let result = config.build();
+	let build_results = result.expect("build MUST succeed");
+	let source_root = test_source_root();
+	assert_eq!(build_results.source_files,
+		["src/hello_world_c_1.c", "src/hello_world_c_2.c"]
+		.into_iter()
+		.map(|path| {source_root.join(path)})
+		.collect::<Vec<StdPathBuf>>());
+	assert_eq!(build_results.header_bindings.len(), 1);
+	assert_eq!(build_results.header_bindings[0].input_header_file,
+		source_root.join("src/hello_world_export_to_rust.h"));
 }
 
 #[test]
-fn test_build_results()
+fn test_build_success_source_root_subdir()
 {
-	let mut build_results = BuildResults::new();
+	let mut config = Config::default();
+	let _out_dir;
 
-	println!("build_results (1) = {}.", build_results);
-	println!("build_results (1) = {:?}.", build_results);
+	(config, _out_dir) = test_build_setup(config, true);
 
-	build_results.lib_name = Some("hello_world".to_string());
-	build_results.source_files =
-	[
-		"hello_world_c_1.c",
-		"hello_world_c_2.c",
-	].into_iter()
-		.map(|x| {StdPathBuf::from(x.to_string())})
-		.collect();
-	build_results.header_bindings =
-	[
-		("hello_world_c_exported_1.h", "hello_world_c_exported_1.in"),
-		("hello_world_c_exported_2.h", "hello_world_c_exported_2.in"),
-	].into_iter()
-		.map(|x| -> HeaderBinding
-		{
-			let paths = (StdPathBuf::from(x.0.to_string()),
-				StdPathBuf::from(x.1.to_string()));
-			HeaderBinding::from(paths)
-		}).collect();
+	config = config.source_root(&test_source_root().join("src/sub"))
+		.input_files(["*.c", "../../tests/*.c"].into_iter());
+	let result = config.build();
+	let build_results = result.expect("build MUST succeed");
+	assert_eq!(build_results.source_files.len(), 4);
+	assert!(build_results.source_files
+		.iter()
+		.all(|path| {path.is_absolute() && path.starts_with(test_source_root())}));
+}
 
-	println!("build_results (2) = {}.", build_results);
-	println!("build_results (2) = {:?}.", build_results);
+#[test]
+fn test_normalize_path()
+{
+	use pattern::normalize_path;
+
+	let root = String::from(MAIN_SEPARATOR);
+	let path = |components: &[&str]| -> StdPathBuf
+	{
+		components.iter().collect()
+	};
+
+	assert_eq!(normalize_path(&path(&[&root, "a", ".", "b"])),
+		path(&[&root, "a", "b"]));
+	assert_eq!(normalize_path(&path(&[&root, "a", "..", "b"])),
+		path(&[&root, "b"]));
+	assert_eq!(normalize_path(&path(&[&root, "..", "a"])),
+		path(&[&root, "a"]));
+	assert_eq!(normalize_path(&path(&["..", "a", "..", "..", "b"])),
+		path(&["..", "..", "b"]));
+	assert_eq!(normalize_path(&path(&[".", "a", "*.c"])),
+		path(&["a", "*.c"]));
+}
 
-	// std::process::ExitCode does not support PartialEq.
-	build_results.report();
+#[test]
+fn test_resolve_pattern()
+{
+	use pattern::resolve_pattern;
+
+	let source_root = test_source_root();
+	let source_root_str = source_root.to_str().expect("MUST be a valid string");
+
+	let resolve = |source_root: &Path, pattern: &str| -> String
+	{
+		resolve_pattern(source_root, pattern).expect("pattern MUST be resolved")
+	};
+
+	assert_eq!(resolve(&source_root, "src/*.c"),
+		source_root.join("src/*.c").to_str().expect("MUST be a valid string"));
+	assert_eq!(resolve(&source_root, "/usr/include/*.h"),
+		"/usr/include/*.h");
+	assert_eq!(resolve(&StdPathBuf::from("/tmp/[x]"), "*.c"),
+		"/tmp/[[]x[]]/*.c");
+	assert!(resolve(&source_root, "../*.c").ends_with("/tmp/*.c"));
+	assert!(!resolve(&source_root, "./*.c").contains("/./"));
+	assert!(resolve(&source_root, "*.c").starts_with(source_root_str));
+	assert_eq!(resolve(&source_root, "src/*/../*.c"),
+		source_root.join("src/*/../*.c").to_str().expect("MUST be a valid string"));
+	assert_eq!(resolve(&source_root, "src/**/sub/../*.c"),
+		source_root.join("src/**/*.c").to_str().expect("MUST be a valid string"));
 }
 
+#[cfg(unix)]
 #[test]
-fn test_file_type()
+fn test_resolve_pattern_non_utf8_source_root()
 {
-	let config = Config::default();
+	use std::os::unix::ffi::OsStrExt;
+	use pattern::resolve_pattern;
 
-	for e in SOURCE_EXTS.iter() {
-		let path_buf = StdPathBuf::from("hello_world_c_1.".to_string() + e);
+	let source_root = StdPathBuf::from(OsStr::from_bytes(b"/tmp/src_root_\xff"));
+	assert_eq!(discriminant(&(resolve_pattern(&source_root, "*.c").err().expect("MUST be error"))),
+		discriminant(&MldError::from("")));
+}
 
-		let ext = config.find_filetype(path_buf.extension());
-		assert_eq!(ext, FileType::Source);
-		println!("ext (1) = {}.", ext);
-		println!("ext (1) = {:?}.", ext);
-	}
+#[test]
+fn test_build_success_exclude_input_file()
+{
+	let mut config = Config::default();
+	let out_dir;
 
-	for e in HEADER_EXTS.iter() {
-		let path_buf = StdPathBuf::from("hello_world_c_1.".to_string() + e);
+	(config, out_dir) = test_build_setup(config, true);
 
-		let ext = config.find_filetype(path_buf.extension());
-		assert_eq!(ext, FileType::Header);
-		println!("ext (2) = {}.", ext);
-		println!("ext (2) = {:?}.", ext);
-	}
+	config = config.input_file("src/**/*.[ch]")
+		.exclude_input_file("src/sub/**");
+	let result = config.build();
+	let build_results = result.expect("build MUST succeed");
+	assert_eq!(build_results.out_dir, out_dir);
+	assert_eq!(build_results.source_files.len(), 4);
+	assert_eq!(build_results.header_bindings.len(), 1);
+}
 
-	let path_buf = StdPathBuf::from("hello_world_c_1.txt".to_string());
+#[test]
+fn test_build_success_exclude_prefix()
+{
+	let mut config = Config::default();
+	let out_dir;
 
-	let ext = config.find_filetype(path_buf.extension());
-	assert_eq!(ext, FileType::Unsupported("txt".to_string()));
-	println!("ext (3) = {}.", ext);
-	println!("ext (3) = {:?}.", ext);
+	(config, out_dir) = test_build_setup(config, true);
 
-	let mut path_buf = StdPathBuf::from("hello_world_c_1".to_string());
+	config = config.input_files(
+		["src/**/*.c", "!src/**/hello_world_C_*.c"].into_iter());
+	let result = config.build();
+	let build_results = result.expect("build MUST succeed");
+	assert_eq!(build_results.out_dir, out_dir);
+	assert_eq!(build_results.source_files.len(), 4);
+	assert_eq!(build_results.header_bindings.len(), 0);
+}
 
-	let ext = config.find_filetype(path_buf.extension());
-	assert_eq!(ext, FileType::Unsupported("".to_string()));
-	println!("ext (4) = {}.", ext);
-	println!("ext (4) = {:?}.", ext);
+#[test]
+fn test_build_success_braces()
+{
+	let mut config = Config::default();
+	let out_dir;
 
-	path_buf.set_extension(invalid_path_str().as_os_str());
+	(config, out_dir) = test_build_setup(config, true);
 
-	let ext = config.find_filetype(path_buf.extension());
-	assert_eq!(ext, FileType::Unsupported("".to_string()));
-	println!("ext (4) = {}.", ext);
-	println!("ext (4) = {:?}.", ext);
+	config = config.input_file("{src,tests}/**/*.{c,h}")
+		.exclude_input_file("{tests/**/*.h,src/sub/**}");
+	let result = config.build();
+	let build_results = result.expect("build MUST succeed");
+	assert_eq!(build_results.out_dir, out_dir);
+	assert_eq!(build_results.source_files.len(), 6);
+	assert_eq!(build_results.header_bindings.len(), 1);
 }
 
-#[cfg(unix)]
-pub fn invalid_path_str() -> OsString
+#[test]
+fn test_build_success_duplicate_input_files()
 {
-	use std::os::unix::ffi::OsStrExt;
+	let mut config = Config::default();
+	let out_dir;
 
-	let source = [b'b', b'i', 0x80, b'n'];
-	let mut os_str = OsString::new();
-	os_str.push(OsStr::from_bytes(&source));
+	(config, out_dir) = test_build_setup(config, true);
 
-	os_str
+	config = config.input_files(
+		["src/**/*.c", "src/hello_world_c_{1,2}.c"].into_iter());
+	let result = config.build();
+	let build_results = result.expect("build MUST succeed");
+	assert_eq!(build_results.out_dir, out_dir);
+	assert_eq!(build_results.source_files.len(), 6);
 }
 
-#[cfg(windows)]
-pub fn invalid_path_str() -> OsString
+#[test]
+fn test_build_fail_all_excluded()
 {
-	use std::os::windows::prelude::*;
+	let mut config = Config::default();
+	let _out_dir;
 
-	let source = [0x0062, 0x0069, 0xd800, 0x6e];
+	(config, _out_dir) = test_build_setup(config, true);
 
-	OsString::from_wide(&source)
+	config = config.input_file("src/**/*.[ch]")
+		.add_input_file("!src/**");
+	let result = config.build();
+	assert!(result.is_err());
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::MessageError("".to_string())));
+}
+
+#[test]
+fn test_build_fail_exclude_pattern_error()
+{
+	let mut config = Config::default();
+	let _out_dir;
+
+	(config, _out_dir) = test_build_setup(config, true);
+
+	config = config.input_file("src/**/*.[ch]")
+		.exclude_input_file("src/a**/*.c");
+	let result = config.build();
+	assert!(result.is_err());
+	let err = MldError::from(
+		Pattern::new("a**")
+		.err()
+		.expect("pattern MUST be illegal"));
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&err));
+}
+
+#[test]
+fn test_build_success_respect_ignore_files()
+{
+	use std::fs::{create_dir_all, write};
+	use busshi::glob::{GlobContext, GlobContextAccess};
+
+	let repo_dir = env::temp_dir().join("kusabira_test_respect_ignore_files");
+	create_dir_all(repo_dir.join(".git")).expect("repository MUST be created");
+	create_dir_all(repo_dir.join("src")).expect("source directory MUST be created");
+	write(repo_dir.join(".gitignore"), "legacy/\n")
+		.expect("ignore file MUST be written");
+	write(repo_dir.join("src").join(".ignore"), "test_*.c\n!test_keep.c\n")
+		.expect("ignore file MUST be written");
+
+	let (config, out_dir) = test_build_setup(Config::default(), true);
+	let mut glob_ctx = GlobContextAccess::default();
+	glob_ctx.paths_push_from_iter(
+		["src/lib.c", "src/test_lib.c", "src/test_keep.c", "src/legacy/old.c"]
+		.iter()
+		.map(|path| {repo_dir.join(path)}));
+
+	let input_file = format!("{}/src/**/*.c", repo_dir.display());
+	let build_results = config
+		.input_file(&input_file)
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.source_files.len(), 4);
+
+	let build_results = Config::default()
+		.out_dir(out_dir.as_ref())
+		.lib_name("hello_world")
+		.input_file(&input_file)
+		.respect_ignore_files(true)
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.source_files,
+		[repo_dir.join("src/lib.c"), repo_dir.join("src/test_keep.c")]);
+}
+
+fn test_manifest_setup(name: &str, manifest: &str) -> StdPathBuf
+{
+	use std::fs::{create_dir_all, write};
+
+	let manifest_dir = env::temp_dir().join(name);
+	create_dir_all(&manifest_dir).expect("manifest directory MUST be created");
+	let manifest_path = manifest_dir.join("Cargo.toml");
+	write(&manifest_path, manifest).expect("manifest MUST be written");
+
+	manifest_path
+}
+
+#[test]
+fn test_from_manifest_path_contents()
+{
+	let manifest_path = test_manifest_setup("kusabira_test_from_manifest_contents", r#"
+[package]
+name = "hello_world"
+
+[package.metadata.kusabira]
+source-root = "c_src"
+lib-name = "hello_world"
+input-files = ["src/**/*.c", "src/**/*.h"]
+exclude-input-files = ["src/**/test_*.c"]
+respect-ignore-files = true
+source-exts = ["txt"]
+header-exts = ["hxx"]
+binding-ext = "rs"
+combined-binding = "hello_world_bindings"
+vendored-bindings = "bindings"
+vendored-bindings-mode = "force"
+bindgen-cache = true
+parallel-bindgen = true
+abi-layout-tests = true
+std = "c11"
+cxx-std = "c++17"
+include-input-dirs = false
+include-dep-dirs = false
+restrict-bindings-dirs = ["include"]
+
+[package.metadata.kusabira.system-libraries]
+libcw = ">=7.0"
+
+[package.metadata.kusabira.install-headers]
+hello_world = ["include/*.h"]
+
+[package.metadata.kusabira.metadata]
+version = "1.0"
+"#);
+	let config = Config::from_manifest_path(&manifest_path)
+		.expect("manifest MUST be valid");
+
+	let manifest_dir = manifest_path.parent().expect("MUST have parent");
+	assert_eq!(config.source_root, Some(manifest_dir.join("c_src")));
+	assert_eq!(config.lib_name, Some("hello_world".to_string()));
+	assert_source_files(&config, ["src/**/*.c", "src/**/*.h"].into_iter());
+	assert_eq!(config.exclude_input_files, ["src/**/test_*.c"]);
+	assert!(config.respect_ignore_files);
+	assert!(config.cc_exts.iter().any(|(ext, _)| {ext == "txt"}));
+	assert!(config.bindgen_exts.iter().any(|(ext, _)| {ext == "hxx"}));
+	assert_eq!(config.binding_ext, "rs");
+	assert_eq!(config.combined_binding, Some("hello_world_bindings".to_string()));
+	assert_eq!(config.vendored_bindings_dir, Some(manifest_dir.join("c_src/bindings")));
+	assert_eq!(config.vendored_bindings_mode, VendoredBindingsMode::Force);
+	assert!(config.bindgen_cache);
+	assert!(config.parallel_bindgen);
+	assert!(config.abi_layout_tests);
+	assert_eq!(config.std, Some("c11".to_string()));
+	assert_eq!(config.cxx_std, Some("c++17".to_string()));
+	assert!(!config.include_input_dirs);
+	assert!(!config.include_dep_dirs);
+	assert!(config.restrict_bindings);
+	assert_eq!(config.restrict_bindings_dirs, [manifest_dir.join("c_src/include")]);
+	assert_eq!(config.system_libraries, [("libcw".to_string(), ">=7.0".to_string())]);
+	assert_eq!(config.installed_headers,
+		[("include/*.h".to_string(), "hello_world".to_string())]);
+	assert_eq!(config.metadata, [("version".to_string(), "1.0".to_string())]);
+}
+
+#[test]
+fn test_from_manifest_path_build()
+{
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+	let manifest_path = test_manifest_setup("kusabira_test_from_manifest_build", r#"
+[package]
+name = "hello_world"
+
+[package.metadata.kusabira]
+lib-name = "hello_world"
+input-files = ["src/**/*.[ch]"]
+include-dirs = ["include"]
+
+[package.metadata.kusabira.defines]
+HAVE_CONFIG_H = true
+NDEBUG = false
+HELLO_WORLD_VERSION = 2
+HELLO_WORLD_NAME = "hello"
+
+[package.metadata.kusabira.bindgen]
+allowlist-functions = ["hello_.*"]
+layout-tests = false
+clang-args = ["-std=c11"]
+"#);
+	let manifest_dir = manifest_path.parent().expect("MUST have parent");
+	let config = Config::from_manifest_path(&manifest_path)
+		.expect("manifest MUST be valid");
+	let (config, _out_dir) = test_build_setup(config, false);
+
+	let build_results = config
+		.add_cc_build_hook(|build| {build.define("USER_HOOK", None)})
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.lib_name, Some("hello_world".to_string()));
+	assert_eq!(build_results.source_files.len(), 6);
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 1);
+	assert_eq!(compiled[0].includes,
+		[manifest_dir.join("include"),
+		test_source_root().join("src"),
+		test_source_root().join("src/sub")]);
+	assert_eq!(compiled[0].definitions, [
+		("HAVE_CONFIG_H".to_string(), None),
+		("HELLO_WORLD_NAME".to_string(), Some("hello".to_string())),
+		("HELLO_WORLD_VERSION".to_string(), Some("2".to_string())),
+		("USER_HOOK".to_string(), None),
+	]);
+
+	let generated = BindgenBuilderContextAccess::default().generated_get();
+	assert_eq!(generated.len(), build_results.header_bindings.len());
+	let flags = &generated[0];
+	let include_arg = format!("-I{}", manifest_dir.join("include").display());
+	for flag in [
+		"--allowlist-function",
+		"hello_.*",
+		"--no-layout-tests",
+		"-std=c11",
+		include_arg.as_str(),
+		"-DHAVE_CONFIG_H",
+		"-DHELLO_WORLD_VERSION=2",
+		"-DHELLO_WORLD_NAME=hello",
+	] {
+		assert!(flags.iter().any(|arg| {arg == flag}), "{flag} MUST be in {flags:?}");
+	}
+	assert!(!flags.iter().any(|arg| {arg.contains("NDEBUG")}));
+}
+
+#[test]
+fn test_from_manifest_path_parallel_bindgen()
+{
+	let manifest_path = test_manifest_setup("kusabira_test_from_manifest_parallel_bindgen", r#"
+[package.metadata.kusabira]
+lib-name = "hello_world"
+input-files = ["src/**/*.[ch]"]
+parallel-bindgen = true
+
+[package.metadata.kusabira.bindgen]
+allowlist-functions = ["hello_.*"]
+layout-tests = false
+"#);
+	let config = Config::from_manifest_path(&manifest_path)
+		.expect("manifest MUST be valid");
+	assert!(config.parallel_bindgen);
+	assert!(!config.unsync_bindgen_builder_hooks);
+	let (config, _out_dir) = test_build_setup(config, false);
+
+	let build_results = config
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.header_bindings.len(), 2);
+}
+
+#[test]
+fn test_from_manifest_path_libraries()
+{
+	let manifest_path = test_manifest_setup("kusabira_test_from_manifest_libraries", r#"
+[package.metadata.kusabira]
+lib-name = "hello_world"
+link-kind = "whole-archive"
+input-files = ["src/hello_world_c_*.c"]
+
+[[package.metadata.kusabira.libraries]]
+name = "hello_world_plugins"
+input-files = ["src/sub/*.c"]
+exclude-input-files = ["**/hello_world_c_4.c"]
+link-kind = "unlinked"
+include-dirs = ["plugins/include"]
+defines = { HELLO_WORLD_PLUGIN = true }
+"#);
+	let manifest_dir = manifest_path.parent().expect("MUST have parent");
+	let config = Config::from_manifest_path(&manifest_path)
+		.expect("manifest MUST be valid");
+	assert_eq!(config.link_kind, LinkKind::WholeArchive);
+	assert_eq!(config.libraries.len(), 1);
+	assert_eq!(config.libraries[0].name, "hello_world_plugins");
+	assert_eq!(config.libraries[0].link_kind, LinkKind::Unlinked);
+
+	let (config, _out_dir) = test_build_setup(config, false);
+	let build_results = config.build().expect("build MUST succeed");
+	assert_eq!(build_results.libraries.len(), 2);
+	assert_eq!(build_results.libraries[0].source_files.len(), 1);
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled[0].includes,
+		[manifest_dir.join("plugins/include"), test_source_root().join("src/sub")]);
+	assert_eq!(compiled[0].definitions, [("HELLO_WORLD_PLUGIN".to_string(), None)]);
+	assert!(!compiled[0].cargo_metadata);
+	assert_eq!(compiled[1].includes, [test_source_root().join("src")]);
+	assert_eq!(compiled[1].link_lib_modifiers, ["+whole-archive"]);
+}
+
+#[test]
+fn test_from_manifest_path_fail_unknown_key()
+{
+	for (name, manifest) in [
+		("kusabira_test_from_manifest_unknown_key", r#"
+[package.metadata.kusabira]
+lib_name = "hello_world"
+"#),
+		("kusabira_test_from_manifest_unknown_bindgen_key", r#"
+[package.metadata.kusabira.bindgen]
+allowlist-function = ["hello_.*"]
+"#),
+		("kusabira_test_from_manifest_unknown_library_key", r#"
+[[package.metadata.kusabira.libraries]]
+name = "hello_world_plugins"
+lib-name = "hello_world_plugins"
+"#),
+	] {
+		let result = Config::from_manifest_path(&test_manifest_setup(name, manifest));
+		assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+			discriminant(&MldError::MessageError("".to_string())));
+	}
+}
+
+#[test]
+fn test_from_manifest_path_fail_invalid_value()
+{
+	for (name, manifest) in [
+		("kusabira_test_from_manifest_invalid_lib_name", r#"
+[package.metadata.kusabira]
+lib-name = ["hello_world"]
+"#),
+		("kusabira_test_from_manifest_invalid_input_files", r#"
+[package.metadata.kusabira]
+input-files = ["src/**/*.c", 1]
+"#),
+		("kusabira_test_from_manifest_invalid_define", r#"
+[package.metadata.kusabira.defines]
+NDEBUG = 1.0
+"#),
+		("kusabira_test_from_manifest_invalid_system_library", r#"
+[package.metadata.kusabira.system-libraries]
+libcw = 7
+"#),
+		("kusabira_test_from_manifest_invalid_bindgen_bool", r#"
+[package.metadata.kusabira.bindgen]
+layout-tests = "no"
+"#),
+		("kusabira_test_from_manifest_invalid_link_kind", r#"
+[package.metadata.kusabira]
+link-kind = "dynamic"
+"#),
+		("kusabira_test_from_manifest_invalid_vendored_bindings_mode", r#"
+[package.metadata.kusabira]
+vendored-bindings-mode = "always"
+"#),
+		("kusabira_test_from_manifest_invalid_library_name", r#"
+[[package.metadata.kusabira.libraries]]
+input-files = ["src/sub/*.c"]
+"#),
+		("kusabira_test_from_manifest_invalid_soversion", r#"
+[package.metadata.kusabira]
+link-kind = "shared"
+soversion = -1
+"#),
+		("kusabira_test_from_manifest_invalid_soversion_static", r#"
+[[package.metadata.kusabira.libraries]]
+name = "hello_world_plugins"
+soversion = 1
+"#),
+	] {
+		let result = Config::from_manifest_path(&test_manifest_setup(name, manifest));
+		assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+			discriminant(&MldError::MessageError("".to_string())));
+	}
+}
+
+#[test]
+fn test_from_manifest_path_fail_no_metadata()
+{
+	let manifest_path = test_manifest_setup("kusabira_test_from_manifest_no_metadata", r#"
+[package]
+name = "hello_world"
+"#);
+	let result = Config::from_manifest_path(&manifest_path);
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::MessageError("".to_string())));
+}
+
+#[test]
+fn test_from_manifest_path_fail_toml_error()
+{
+	let manifest_path = test_manifest_setup("kusabira_test_from_manifest_toml_error",
+		"[package.metadata.kusabira\n");
+	let result = Config::from_manifest_path(&manifest_path);
+	let err = MldError::from("[".parse::<toml::Table>()
+		.err()
+		.expect("toml MUST be illegal"));
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&err));
+}
+
+#[test]
+fn test_from_manifest_path_fail_no_manifest()
+{
+	let result = Config::from_manifest_path(
+		&env::temp_dir().join("kusabira_test_from_manifest_nonexist/Cargo.toml"));
+	let err = MldError::from(StdIoError::new(StdIoErrorKind::NotFound, "MUST be error"));
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&err));
+}
+
+#[test]
+fn test_build_success_libraries()
+{
+	let (config, out_dir) = test_build_setup(Config::default(), true);
+
+	let build_results = config
+		.input_file("src/hello_world_c_*.c")
+		.add_library(Library::new("hello_world_plugins")
+			.input_files(["src/sub/*.c", "src/sub/*.h"].into_iter())
+			.link_kind(LinkKind::WholeArchive)
+			.cc_build_hook(|build| {build.define("HELLO_WORLD_PLUGIN", None)}))
+		.add_library(Library::new("hello_world_tests")
+			.input_file("tests/*.c")
+			.link_kind(LinkKind::Unlinked))
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.out_dir, out_dir);
+	assert_eq!(build_results.lib_name, Some("hello_world".to_string()));
+	assert_eq!(build_results.source_files.len(), 2);
+	assert_eq!(build_results.header_bindings.len(), 1);
+	assert_eq!(build_results.libraries
+		.iter()
+		.map(|library| {(library.lib_name.as_str(), library.link_kind, library.source_files.len())})
+		.collect::<Vec<_>>(),
+		[
+			("hello_world_plugins", LinkKind::WholeArchive, 2),
+			("hello_world_tests", LinkKind::Unlinked, 2),
+			("hello_world", LinkKind::Static, 2),
+		]);
+	println!("build_results = {}.", build_results);
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 3);
+	assert_eq!(compiled[0].output, Some("hello_world_plugins".to_string()));
+	assert_eq!(compiled[0].link_lib_modifiers, ["+whole-archive"]);
+	assert_eq!(compiled[0].definitions, [("HELLO_WORLD_PLUGIN".to_string(), None)]);
+	assert!(compiled[0].cargo_metadata);
+	assert_eq!(compiled[1].output, Some("hello_world_tests".to_string()));
+	assert!(!compiled[1].cargo_metadata);
+	assert_eq!(compiled[1].definitions.len(), 0);
+	assert_eq!(compiled[2].output, Some("hello_world".to_string()));
+	assert_eq!(compiled[2].link_lib_modifiers.len(), 0);
+	assert_eq!(compiled[2].files.len(), 2);
+}
+
+#[test]
+fn test_build_success_library_overlap()
+{
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+
+	let build_results = config
+		.input_file("src/**/*.c")
+		.add_library(Library::new("hello_world_plugins")
+			.input_file("src/sub/*.c"))
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.libraries[0].source_files,
+		[test_source_root().join("src/sub/hello_world_c_3.c"),
+		test_source_root().join("src/sub/hello_world_c_4.c")]);
+	assert!(!build_results.libraries[1].source_files.is_empty());
+	assert!(build_results.libraries[1].source_files
+		.iter()
+		.all(|source_file| {!build_results.libraries[0].source_files.contains(source_file)}));
+}
+
+#[test]
+fn test_build_success_library_only()
+{
+	let (config, _out_dir) = test_build_setup(Config::default(), false);
+
+	let build_results = config
+		.add_library(Library::new("hello_world_plugins")
+			.input_file("src/sub/*.c"))
+		.build()
+		.expect("build MUST succeed");
+	assert!(build_results.lib_name.is_none());
+	assert_eq!(build_results.source_files.len(), 0);
+	assert_eq!(build_results.libraries.len(), 1);
+	assert_eq!(build_results.libraries[0].source_files.len(), 2);
+}
+
+#[test]
+fn test_build_success_library_exclude()
+{
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+
+	let build_results = config
+		.input_file("src/**/hello_world_c_*.c")
+		.exclude_input_file("**/hello_world_c_1.c")
+		.add_library(Library::new("hello_world_plugins")
+			.input_files(["src/hello_world_c_*.c", "src/sub/*.c"].into_iter())
+			.exclude_input_file("**/hello_world_c_4.c"))
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.libraries[0].source_files,
+		[
+			test_source_root().join("src/hello_world_c_2.c"),
+			test_source_root().join("src/sub/hello_world_c_3.c"),
+		]);
+	// The sources of the library are not compiled again.
+	assert_eq!(build_results.source_files,
+		[test_source_root().join("src/sub/hello_world_c_4.c")]);
+}
+
+#[test]
+fn test_build_fail_library_duplicate_name()
+{
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+
+	let result = config
+		.input_file("src/hello_world_c_*.c")
+		.add_library(Library::new("hello_world_plugins")
+			.input_file("src/sub/*.c"))
+		.add_library(Library::new("hello_world_plugins")
+			.input_file("tests/*.c"))
+		.build();
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::MessageError("".to_string())));
+}
+
+#[test]
+fn test_build_fail_library_default_name()
+{
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+
+	let result = config
+		.input_file("src/hello_world_c_*.c")
+		.add_library(Library::new("hello_world")
+			.input_file("src/sub/*.c"))
+		.build();
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::MessageError("".to_string())));
+}
+
+#[test]
+fn test_build_fail_library_no_sources()
+{
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+
+	let result = config
+		.input_file("src/hello_world_c_*.c")
+		.add_library(Library::new("hello_world_plugins")
+			.input_file("src/sub/*.h"))
+		.build();
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::MessageError("".to_string())));
+}
+
+#[test]
+fn test_build_success_override_file_type()
+{
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+
+	let build_results = config
+		.input_file("src/**/*.{c,h}")
+		.override_file_type("src/sub/*.{c,h}", FileType::Source(Language::C))
+		.override_file_type("src/sub/*_3.c", FileType::Source(Language::Cxx))
+		.override_file_type("src/hello_world_export_to_rust.h", FileType::Header(Language::Cxx))
+		.override_file_type("src/hello_world_C_*.c", FileType::Unsupported("c".to_string()))
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.source_files.len(), 5);
+	assert_eq!(build_results.header_bindings.len(), 1);
+
+	// The sources of the unconventional extensions are compiled separately.
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 4);
+	assert!(compiled[0].cpp);
+	assert_eq!(compiled[0].flags, ["-xc++"]);
+	assert_eq!(compiled[0].files,
+		[test_source_root().join("src/sub/hello_world_c_3.c")]);
+	assert!(!compiled[1].cpp);
+	assert_eq!(compiled[1].flags, ["-xc"]);
+	assert_eq!(compiled[1].files,
+		[test_source_root().join("src/sub/hello_world_internal.h")]);
+	assert_eq!(compiled[2].files.len(), 3);
+	assert!(compiled[2].flags.is_empty());
+	assert!(compiled[3].cpp);
+	assert!(compiled[3].files.is_empty());
+	assert_eq!(compiled[3].objects.len(), 5);
+
+	let generated = BindgenBuilderContextAccess::default().generated_get();
+	assert_eq!(generated.len(), 1);
+	assert!(generated[0].iter().any(|arg| {arg == "-xc++"}));
+}
+
+#[test]
+fn test_build_success_override_file_type_cpp_as_c()
+{
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+
+	let build_results = config
+		.input_file("src/hello_world_cpp_9.cpp")
+		.override_file_type("src/*.cpp", FileType::Source(Language::C))
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.source_files,
+		[test_source_root().join("src/hello_world_cpp_9.cpp")]);
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 2);
+	assert!(!compiled[0].cpp);
+	assert_eq!(compiled[0].flags, ["-xc"]);
+	assert_eq!(compiled[0].files,
+		[test_source_root().join("src/hello_world_cpp_9.cpp")]);
+	assert!(!compiled[1].cpp);
+	assert!(compiled[1].files.is_empty());
+	assert_eq!(compiled[1].objects.len(), 1);
+	assert_eq!(compiled[1].output, Some("hello_world".to_string()));
+}
+
+#[test]
+fn test_build_fail_overlapped_exts()
+{
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+
+	let result = config
+		.input_file("src/**/*.[ch]")
+		.add_header_ext("c")
+		.build();
+	assert_eq!(result.err().expect("MUST be error").to_string(),
+		"MessageError: extensions [c] MUST NOT be both source and header extensions");
+
+	// The extensions are checked before the input files are expanded.
+	let result = Config::default()
+		.add_header_ext("c")
+		.add_header_ext("cc")
+		.add_header_ext("cpp")
+		.build();
+	assert_eq!(result.err().expect("MUST be error").to_string(),
+		"MessageError: extensions [c, cc, cpp] MUST NOT be both source and header extensions");
+}
+
+#[test]
+fn test_str_iter_to_string()
+{
+	assert_eq!(str_iter_to_string(Vec::<&str>::new().iter()), "[]");
+	assert_eq!(str_iter_to_string(["a"].iter()), "[a]");
+	assert_eq!(str_iter_to_string(["a", "b", "c"].iter()), "[a, b, c]");
+}
+
+#[test]
+fn test_header_binding()
+{
+	let header_binding = HeaderBinding::from((
+		StdPathBuf::from("hello_world_c_exported.h".to_string()),
+		StdPathBuf::from("hello_world_c_exported.in".to_string())));
+
+	println!("header_binding = {}.", header_binding);
+	println!("header_binding = {:?}.", header_binding);
+}
+
+#[test]
+fn test_build_results()
+{
+	let mut build_results = BuildResults::new();
+
+	println!("build_results (1) = {}.", build_results);
+	println!("build_results (1) = {:?}.", build_results);
+
+	build_results.lib_name = Some("hello_world".to_string());
+	build_results.source_files =
+	[
+		"hello_world_c_1.c",
+		"hello_world_c_2.c",
+	].into_iter()
+		.map(|x| {StdPathBuf::from(x.to_string())})
+		.collect();
+	build_results.header_bindings =
+	[
+		("hello_world_c_exported_1.h", "hello_world_c_exported_1.in"),
+		("hello_world_c_exported_2.h", "hello_world_c_exported_2.in"),
+	].into_iter()
+		.map(|x| -> HeaderBinding
+		{
+			let paths = (StdPathBuf::from(x.0.to_string()),
+				StdPathBuf::from(x.1.to_string()));
+			HeaderBinding::from(paths)
+		}).collect();
+
+	println!("build_results (2) = {}.", build_results);
+	println!("build_results (2) = {:?}.", build_results);
+
+	// std::process::ExitCode does not support PartialEq.
+	build_results.report();
+}
+
+#[test]
+fn test_file_type()
+{
+	let config = Config::default();
+
+	for (e, language) in SOURCE_EXTS.iter() {
+		let path_buf = StdPathBuf::from("hello_world_c_1.".to_string() + e);
+
+		let ext = config.find_filetype(path_buf.extension());
+		assert_eq!(ext, FileType::Source(*language));
+		println!("ext (1) = {}.", ext);
+		println!("ext (1) = {:?}.", ext);
+	}
+
+	for (e, language) in HEADER_EXTS.iter() {
+		let path_buf = StdPathBuf::from("hello_world_c_1.".to_string() + e);
+
+		let ext = config.find_filetype(path_buf.extension());
+		assert_eq!(ext, FileType::Header(*language));
+		println!("ext (2) = {}.", ext);
+		println!("ext (2) = {:?}.", ext);
+	}
+
+	for (e, file_type) in [
+		("c", FileType::Source(Language::C)),
+		("C", FileType::Source(Language::Cxx)),
+		("i", FileType::Source(Language::PreprocessedC)),
+		("ii", FileType::Source(Language::PreprocessedCxx)),
+		("s", FileType::Source(Language::Assembly)),
+		("S", FileType::Source(Language::PreprocessedAssembly)),
+		("h", FileType::Header(Language::C)),
+		("hpp", FileType::Header(Language::Cxx)),
+		("HH", FileType::Unsupported("HH".to_string())),
+	] {
+		let path_buf = StdPathBuf::from("hello_world_c_1.".to_string() + e);
+
+		assert_eq!(config.find_filetype(path_buf.extension()), file_type);
+	}
+
+	let path_buf = StdPathBuf::from("hello_world_c_1.txt".to_string());
+
+	let ext = config.find_filetype(path_buf.extension());
+	assert_eq!(ext, FileType::Unsupported("txt".to_string()));
+	println!("ext (3) = {}.", ext);
+	println!("ext (3) = {:?}.", ext);
+
+	let mut path_buf = StdPathBuf::from("hello_world_c_1".to_string());
+
+	let ext = config.find_filetype(path_buf.extension());
+	assert_eq!(ext, FileType::Unsupported("".to_string()));
+	println!("ext (4) = {}.", ext);
+	println!("ext (4) = {:?}.", ext);
+
+	path_buf.set_extension(invalid_path_str().as_os_str());
+
+	let ext = config.find_filetype(path_buf.extension());
+	assert_eq!(ext, FileType::Unsupported("".to_string()));
+	println!("ext (4) = {}.", ext);
+	println!("ext (4) = {:?}.", ext);
+}
+
+#[cfg(unix)]
+pub fn invalid_path_str() -> OsString
+{
+	use std::os::unix::ffi::OsStrExt;
+
+	let source = [b'b', b'i', 0x80, b'n'];
+	let mut os_str = OsString::new();
+	os_str.push(OsStr::from_bytes(&source));
+
+	os_str
+}
+
+#[cfg(windows)]
+pub fn invalid_path_str() -> OsString
+{
+	use std::os::windows::prelude::*;
+
+	let source = [0x0062, 0x0069, 0xd800, 0x6e];
+
+	OsString::from_wide(&source)
+}
+
+fn test_nasm_setup<'a>(config: Config<'a>, nasm_files: &[&str]) -> (Config<'a>, StdPathBuf)
+{
+	use busshi::glob::{GlobContext, GlobContextAccess};
+
+	let (config, out_dir) = test_build_setup(config, true);
+	let source_root = test_source_root();
+	GlobContextAccess::default().paths_push_from_iter(
+		nasm_files
+		.iter()
+		.map(|path| {source_root.join(path)}));
+
+	(config, out_dir)
+}
+
+#[test]
+fn test_build_success_nasm()
+{
+	use busshi::nasm_build::{NasmBuildContext, NasmBuildContextAccess};
+
+	let (config, out_dir) = test_nasm_setup(Config::default(),
+		&["src/x86/hello_world_nasm.asm", "src/x86/sub/hello_world_nasm_2.nasm"]);
+
+	let build_results = config
+		.input_file("src/**/*.{c,asm,nasm}")
+		.nasm_object_format("elf64")
+		.nasm_build_hook(|build| {build.define("HELLO_WORLD_NASM", None)})
+		.add_nasm_build_hook(|build| {build.flag("-g")})
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.source_files.len(), 8);
+
+	let source_root = test_source_root();
+	let nasm_out_dir = out_dir.join("nasm").join("hello_world");
+	let assembled = NasmBuildContextAccess::default().compiled_get();
+	assert_eq!(assembled.len(), 1);
+	assert_eq!(assembled[0].out_dir, Some(nasm_out_dir.clone()));
+	assert_eq!(assembled[0].files,
+		[source_root.join("src/x86/hello_world_nasm.asm"),
+		source_root.join("src/x86/sub/hello_world_nasm_2.nasm")]);
+	assert_eq!(assembled[0].flags,
+		["-felf64".to_string(),
+		format!("-I{}/", source_root.join("src/x86").display()),
+		format!("-I{}/", source_root.join("src/x86/sub").display()),
+		"-DHELLO_WORLD_NASM".to_string(),
+		"-g".to_string()]);
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 1);
+	assert_eq!(compiled[0].output, Some("hello_world".to_string()));
+	assert_eq!(compiled[0].files.len(), 6);
+	assert_eq!(compiled[0].objects,
+		[nasm_out_dir.join("hello_world_nasm.o"),
+		nasm_out_dir.join("hello_world_nasm_2.o")]);
+}
+
+#[test]
+fn test_build_success_nasm_and_cxx()
+{
+	use busshi::nasm_build::{NasmBuildContext, NasmBuildContextAccess};
+
+	let (config, out_dir) = test_nasm_setup(Config::default(),
+		&["src/x86/hello_world_nasm.asm"]);
+
+	config
+		.input_file("src/**/*.{cc,asm}")
+		.build()
+		.expect("build MUST succeed");
+
+	let assembled = NasmBuildContextAccess::default().compiled_get();
+	assert_eq!(assembled.len(), 1);
+	assert_eq!(assembled[0].flags.len(), 1);
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 1);
+	assert!(compiled[0].cpp);
+	assert_eq!(compiled[0].objects,
+		[out_dir.join("nasm").join("hello_world").join("hello_world_nasm.o")]);
+}
+
+#[test]
+fn test_build_success_nasm_only()
+{
+	let (config, _out_dir) = test_nasm_setup(Config::default(),
+		&["src/x86/hello_world_nasm.asm"]);
+
+	let build_results = config
+		.input_file("src/**/*.asm")
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.source_files.len(), 1);
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 1);
+	assert_eq!(compiled[0].files.len(), 0);
+	assert_eq!(compiled[0].objects.len(), 1);
+}
+
+#[test]
+fn test_build_success_library_nasm_hook()
+{
+	use busshi::nasm_build::{NasmBuildContext, NasmBuildContextAccess};
+
+	let (config, _out_dir) = test_nasm_setup(Config::default(),
+		&["src/x86/hello_world_nasm.asm"]);
+
+	config
+		.input_file("src/*.c")
+		.nasm_build_hook(|build| {build.define("HELLO_WORLD", None)})
+		.add_library(Library::new("hello_world_nasm")
+			.input_file("src/x86/*.asm")
+			.nasm_build_hook(|build| {build.define("HELLO_WORLD_NASM", None)})
+			.add_nasm_build_hook(|build| {build.flag("-g")}))
+		.build()
+		.expect("build MUST succeed");
+
+	let assembled = NasmBuildContextAccess::default().compiled_get();
+	assert_eq!(assembled.len(), 1);
+	assert_eq!(assembled[0].flags[1..], ["-DHELLO_WORLD_NASM", "-g"]);
+}
+
+#[test]
+fn test_build_fail_nasm_error()
+{
+	use busshi::nasm_build::{NasmBuildContext, NasmBuildContextAccess};
+
+	let (config, _out_dir) = test_nasm_setup(Config::default(),
+		&["src/x86/hello_world_nasm.asm"]);
+	NasmBuildContextAccess::default().emulate_error_set(true);
+
+	let result = config
+		.input_file("src/**/*.{c,asm}")
+		.build();
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::MessageError("".to_string())));
+	assert_eq!(CcBuildContextAccess::default().compiled_get().len(), 0);
+}
+
+#[test]
+fn test_build_fail_nasm_object_name()
+{
+	let (config, _out_dir) = test_nasm_setup(Config::default(),
+		&["src/x86/hello_world_nasm.asm", "src/x86/sub/hello_world_nasm.nasm"]);
+
+	let result = config
+		.input_file("src/x86/**/*.{asm,nasm}")
+		.build();
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::MessageError("".to_string())));
+}
+
+#[test]
+fn test_from_manifest_path_shared()
+{
+	let manifest_path = test_manifest_setup("kusabira_test_from_manifest_shared", r#"
+[package.metadata.kusabira]
+link-kind = "shared"
+
+[[package.metadata.kusabira.libraries]]
+name = "hello_world_plugins"
+link-kind = "shared"
+soversion = 2
+
+[[package.metadata.kusabira.libraries]]
+name = "hello_world_constructors"
+link-kind = "objects"
+"#);
+	let config = Config::from_manifest_path(&manifest_path)
+		.expect("manifest MUST be valid");
+	assert_eq!(config.link_kind, LinkKind::Shared(0));
+	assert_eq!(config.libraries[0].link_kind, LinkKind::Shared(2));
+	assert_eq!(config.libraries[1].link_kind, LinkKind::Objects);
+}
+
+#[test]
+fn test_build_success_shared()
+{
+	use busshi::std_process_command::{CommandContext, CommandContextAccess};
+
+	env::set_var(ENV_KEY_TARGET, "x86_64-unknown-linux-gnu");
+	let (config, out_dir) = test_build_setup(Config::default(), true);
+
+	let build_results = config
+		.input_file("src/**/*.c")
+		.link_kind(LinkKind::Shared(1))
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.libraries[0].link_kind, LinkKind::Shared(1));
+	assert_eq!(build_results.libraries[0].shared_library, Some(out_dir.join("libhello_world.so.1")));
+	assert!(build_results.libraries[0].to_string().ends_with(
+		&format!(", shared_library: {})", out_dir.join("libhello_world.so.1").display())));
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 1);
+	assert_eq!(compiled[0].output, None);
+	assert_eq!(compiled[0].pic, Some(true));
+
+	let executed = CommandContextAccess::default().executed_get();
+	assert_eq!(executed.len(), 1);
+	assert_eq!(executed[0].program, "cc");
+	let mut args: Vec<OsString> = [
+		"-shared",
+		"-Wl,-soname,libhello_world.so.1",
+		"-o",
+	].iter().map(OsString::from).collect();
+	args.push(out_dir.join("libhello_world.so.1").into_os_string());
+	assert_eq!(executed[0].args[..4], args);
+	assert_eq!(executed[0].args.len(), 4 + compiled[0].files.len());
+}
+
+#[test]
+fn test_shared_library_args()
+{
+	assert_eq!(shared_library_args("hello_world", 1, "x86_64-unknown-linux-gnu")
+		.expect("shared library MUST be supported"),
+		("libhello_world.so.1".to_string(),
+		vec!["-shared".to_string(), "-Wl,-soname,libhello_world.so.1".to_string()]));
+	assert_eq!(shared_library_args("hello_world", 2, "aarch64-apple-darwin")
+		.expect("shared library MUST be supported"),
+		("libhello_world.2.dylib".to_string(),
+		vec!["-dynamiclib".to_string(), "-Wl,-install_name,@rpath/libhello_world.2.dylib".to_string()]));
+	assert_eq!(discriminant(&(shared_library_args("hello_world", 1, "x86_64-pc-windows-gnu")
+		.err().expect("MUST be error"))),
+		discriminant(&MldError::from("")));
+}
+
+#[test]
+fn test_build_success_shared_cxx()
+{
+	use busshi::std_process_command::{CommandContext, CommandContextAccess};
+
+	env::set_var(ENV_KEY_TARGET, "x86_64-unknown-linux-gnu");
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+
+	config
+		.input_file("src/**/*.{c,cc}")
+		.link_kind(LinkKind::Shared(0))
+		.build()
+		.expect("build MUST succeed");
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 2);
+	assert!(compiled.iter().all(|build| {build.output.is_none() && build.pic == Some(true)}));
+	assert!(compiled[1].cpp);
+
+	let executed = CommandContextAccess::default().executed_get();
+	assert_eq!(executed.len(), 1);
+	assert_eq!(executed[0].program, "c++");
+	assert_eq!(executed[0].args.len(), 4 + compiled[0].files.len() + compiled[1].files.len());
+}
+
+#[test]
+fn test_build_fail_shared_link_error()
+{
+	use busshi::std_process_command::{CommandContext, CommandContextAccess};
+
+	env::set_var(ENV_KEY_TARGET, "x86_64-unknown-linux-gnu");
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	CommandContextAccess::default().emulate_failure_set(true);
+
+	let result = config
+		.input_file("src/**/*.c")
+		.link_kind(LinkKind::Shared(1))
+		.build();
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::MessageError("".to_string())));
+}
+
+#[test]
+fn test_build_success_objects()
+{
+	let (config, out_dir) = test_nasm_setup(Config::default(),
+		&["src/x86/hello_world_nasm.asm"]);
+	env::set_var(ENV_KEY_TARGET, "x86_64-unknown-linux-gnu");
+
+	let build_results = config
+		.input_file("src/**/*.{c,cc,asm}")
+		.link_kind(LinkKind::Objects)
+		.build()
+		.expect("build MUST succeed");
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 2);
+	assert!(compiled.iter().all(|build| {build.output.is_none()}));
+	assert!(compiled[1].cpp);
+
+	let objects = &build_results.libraries[0].objects;
+	assert_eq!(objects.len(), 1 + compiled[0].files.len() + compiled[1].files.len());
+	assert_eq!(objects[0],
+		out_dir.join("nasm").join("hello_world").join("hello_world_nasm.o"));
+	println!("build_results = {}.", build_results);
+}
+
+#[test]
+fn test_objects_link_metadata()
+{
+	let objects = [
+		StdPathBuf::from("/tmp/out_dir/hello_world_c_1.o"),
+		StdPathBuf::from("/tmp/out_dir/hello_world_c_2.o"),
+	];
+	assert_eq!(objects_link_metadata(&objects), [
+		"cargo:rustc-link-arg=/tmp/out_dir/hello_world_c_1.o",
+		"cargo:rustc-link-arg=/tmp/out_dir/hello_world_c_2.o",
+	]);
+	assert!(objects_link_metadata(&[]).is_empty());
+}
+
+#[test]
+fn test_cxx_stdlib_link_metadata()
+{
+	let link_lib = |target: &str| {
+		cxx_stdlib_link_metadata(target)
+			.into_iter()
+			.find(|link_metadata| {link_metadata.starts_with("cargo:rustc-link-lib=")})
+	};
+	assert_eq!(link_lib("x86_64-pc-windows-msvc"), None);
+	assert_eq!(link_lib("x86_64-apple-darwin"), Some("cargo:rustc-link-lib=c++".to_string()));
+	assert_eq!(link_lib("aarch64-linux-android"), Some("cargo:rustc-link-lib=c++_shared".to_string()));
+	assert_eq!(link_lib("x86_64-unknown-freebsd"), Some("cargo:rustc-link-lib=c++".to_string()));
+
+	env::set_var("CXXSTDLIB_riscv64gc-unknown-linux-musl", "c++");
+	env::set_var("CXXSTDLIB_STATIC_riscv64gc_unknown_linux_musl", "1");
+	let link_metadata = cxx_stdlib_link_metadata("riscv64gc-unknown-linux-musl");
+	assert_eq!(link_metadata, [
+		"cargo:rerun-if-env-changed=CXXSTDLIB_riscv64gc-unknown-linux-musl",
+		"cargo:rerun-if-env-changed=CXXSTDLIB_STATIC_riscv64gc-unknown-linux-musl",
+		"cargo:rerun-if-env-changed=CXXSTDLIB_STATIC_riscv64gc_unknown_linux_musl",
+		"cargo:rustc-link-lib=static:-bundle=c++",
+	]);
+	env::set_var("CXXSTDLIB_riscv64gc-unknown-linux-musl", "");
+	assert_eq!(link_lib("riscv64gc-unknown-linux-musl"), None);
+}
+
+#[test]
+fn test_build_success_objects_cleared()
+{
+	let (config, _out_dir) = test_nasm_setup(Config::default(),
+		&["src/x86/hello_world_nasm.asm"]);
+
+	let build_results = config
+		.input_file("src/**/*.{c,asm}")
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.libraries[0].objects.len(), 0);
+}
+
+fn test_host_tool_setup<'a>(config: Config<'a>) -> (Config<'a>, StdPathBuf)
+{
+	use std::env::set_var;
+
+	set_var(ENV_KEY_HOST, "x86_64-unknown-linux-gnu");
+	let (config, out_dir) = test_build_setup(config, true);
+	// The mock does not run the host tool.
+	for output_file in ["tables.c", "tables.h"] {
+		std::fs::write(out_dir.join(output_file), "").expect("output file MUST be written");
+	}
+
+	(config, out_dir)
+}
+
+#[test]
+fn test_build_success_host_tool()
+{
+	use std::env::consts::EXE_SUFFIX;
+	use busshi::std_process_command::{CommandContext, CommandContextAccess};
+
+	let (config, out_dir) = test_host_tool_setup(Config::default());
+
+	let build_results = config
+		.input_file("src/hello_world_c_1.c")
+		.add_host_tool(HostTool::new("gen_tables")
+			.source_file("tools/gen_tables.c")
+			.cc_build_hook(|build| {build.define("GEN_TABLES", None)})
+			.args(["tables.c"].into_iter())
+			.add_arg("tables.h")
+			.output_files(["tables.c"].into_iter())
+			.add_output_file("tables.h"))
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.source_files,
+		[test_source_root().join("src/hello_world_c_1.c"), out_dir.join("tables.c")]);
+	assert_eq!(build_results.header_bindings.len(), 1);
+
+	let tool_dir = out_dir.join("host").join("gen_tables");
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 2);
+	assert_eq!(compiled[0].output, None);
+	assert_eq!(compiled[0].target, Some("x86_64-unknown-linux-gnu".to_string()));
+	assert_eq!(compiled[0].host, Some("x86_64-unknown-linux-gnu".to_string()));
+	assert!(!compiled[0].cargo_metadata);
+	assert_eq!(compiled[0].files, [test_source_root().join("tools/gen_tables.c")]);
+	assert_eq!(compiled[0].definitions, [("GEN_TABLES".to_string(), None)]);
+	assert_eq!(compiled[1].output, Some("hello_world".to_string()));
+	assert_eq!(compiled[1].target, None);
+
+	let executable = tool_dir.join(format!("gen_tables{EXE_SUFFIX}"));
+	let executed = CommandContextAccess::default().executed_get();
+	assert_eq!(executed.len(), 2);
+	assert_eq!(executed[0].program, "cc");
+	assert_eq!(executed[0].args,
+		[OsString::from("-o"),
+		executable.clone().into_os_string(),
+		tool_dir.join("gen_tables.o").into_os_string()]);
+	assert_eq!(executed[1].program, executable.into_os_string());
+	assert_eq!(executed[1].args, ["tables.c", "tables.h"]);
+	assert_eq!(executed[1].current_dir, Some(out_dir));
+}
+
+#[test]
+fn test_build_fail_host_tool_exit()
+{
+	use busshi::std_process_command::{CommandContext, CommandContextAccess};
+
+	let (config, _out_dir) = test_host_tool_setup(Config::default());
+	CommandContextAccess::default().emulate_failure_set(true);
+
+	let result = config
+		.add_host_tool(HostTool::new("gen_tables")
+			.source_file("tools/gen_tables.c")
+			.output_files(["tables.c"].into_iter()))
+		.build();
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::MessageError("".to_string())));
+	assert_eq!(CommandContextAccess::default().executed_get().len(), 1);
+}
+
+#[test]
+fn test_build_fail_host_tool_missing_output()
+{
+	let (config, _out_dir) = test_host_tool_setup(Config::default());
+
+	let result = config
+		.add_host_tool(HostTool::new("gen_tables")
+			.source_file("tools/gen_tables.c")
+			.output_files(["tables.c", "tables_missing.c"].into_iter()))
+		.build();
+	let err = result.err().expect("MUST be error");
+	assert!(err.to_string().contains("tables_missing.c"));
+}
+
+#[test]
+fn test_build_fail_host_tool_no_sources()
+{
+	let (config, _out_dir) = test_host_tool_setup(Config::default());
+
+	let result = config
+		.input_file("src/**/*.c")
+		.add_host_tool(HostTool::new("gen_tables")
+			.output_files(["tables.c"].into_iter()))
+		.build();
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::MessageError("".to_string())));
+}
+
+#[test]
+fn test_build_fail_host_tool_non_source()
+{
+	let (config, _out_dir) = test_host_tool_setup(Config::default());
+
+	let result = config
+		.input_file("src/**/*.c")
+		.add_host_tool(HostTool::new("gen_tables")
+			.source_file("tools/gen_tables.h"))
+		.build();
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::MessageError("".to_string())));
+	assert_eq!(CcBuildContextAccess::default().compiled_get().len(), 0);
+}
+
+#[test]
+fn test_build_success_generate_source()
+{
+	let (config, out_dir) = test_host_tool_setup(Config::default());
+	let config_h = out_dir.join("generated").join("config.h");
+	let config_h_expected = config_h.clone();
+
+	let build_results = config
+		.input_file("src/hello_world_c_1.c")
+		.generate_source("version", |out_dir: &Path|
+		{
+			assert_eq!(out_dir, Path::new("/tmp/out_dir"));
+			Ok::<_, MldError>(StdPathBuf::from("version.c"))
+		})
+		.add_host_tool(HostTool::new("gen_tables")
+			.source_file("tools/gen_tables.c")
+			.output_files(["tables.c"].into_iter()))
+		.generate_source("config", move |_out_dir: &Path|
+		{
+			Ok::<_, MldError>(config_h)
+		})
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.source_files,
+		[test_source_root().join("src/hello_world_c_1.c"),
+		out_dir.join("version.c"),
+		out_dir.join("tables.c")]);
+	assert_eq!(build_results.header_bindings.len(), 1);
+	assert_eq!(build_results.header_bindings[0].input_header_file, config_h_expected);
+}
+
+#[test]
+fn test_build_fail_generate_source()
+{
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+
+	let result = config
+		.input_file("src/**/*.c")
+		.generate_source("version", |_out_dir: &Path|
+		{
+			Err(StdIoError::new(StdIoErrorKind::NotFound, "version.txt"))
+		})
+		.build();
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::from(StdIoError::new(StdIoErrorKind::NotFound, ""))));
+	assert_eq!(CcBuildContextAccess::default().compiled_get().len(), 0);
+}
+
+#[test]
+fn test_config_header_render_template()
+{
+	use config_header::render_template;
+
+	let values = [
+		("HAVE_FOO".to_string(), Some("1".to_string())),
+		("HAVE_BAR".to_string(), Some("OFF".to_string())),
+		("HAVE_BAZ".to_string(), Some("baz-NOTFOUND".to_string())),
+		("FOO_VERSION".to_string(), Some("\"1.0\"".to_string())),
+		("HAVE_BAR".to_string(), None),
+	];
+	let template = "\
+#cmakedefine HAVE_FOO
+#cmakedefine HAVE_BAR 1
+# cmakedefine HAVE_BAZ
+#cmakedefine HAVE_QUX
+#cmakedefine01 HAVE_FOO
+#cmakedefine01 HAVE_BAR
+#cmakedefine FOO_VERSION @FOO_VERSION@
+#define FOO_VERSION_ALT ${FOO_VERSION}
+#define FOO_UNSET @FOO_UNSET@
+foo@@bar ${ } user@example.com
+#cmakedefinefoo
+";
+	assert_eq!(render_template(template, &values), "\
+#define HAVE_FOO
+/* #undef HAVE_BAR */
+/* #undef HAVE_BAZ */
+/* #undef HAVE_QUX */
+#define HAVE_FOO 1
+#define HAVE_BAR 0
+#define FOO_VERSION \"1.0\"
+#define FOO_VERSION_ALT \"1.0\"
+#define FOO_UNSET 
+foo@@bar ${ } user@example.com
+#cmakedefinefoo
+");
+}
+
+#[test]
+fn test_config_header_render_values()
+{
+	use config_header::render_values;
+
+	let values = [
+		("HAVE_FOO".to_string(), Some("1".to_string())),
+		("HAVE_BAR".to_string(), None),
+		("FOO_EMPTY".to_string(), Some("".to_string())),
+		("HAVE_FOO".to_string(), None),
+	];
+	assert_eq!(render_values("foo/config.h", &values), "\
+/* Generated by kusabira; do not edit. */
+#ifndef FOO_CONFIG_H
+#define FOO_CONFIG_H
+
+/* #undef HAVE_FOO */
+/* #undef HAVE_BAR */
+#define FOO_EMPTY
+
+#endif /* FOO_CONFIG_H */
+");
+}
+
+#[test]
+fn test_build_success_config_header()
+{
+	use std::env::set_var;
+	use std::fs::{create_dir_all, read_to_string, remove_dir_all, write};
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	let out_dir = env::temp_dir().join("kusabira_test_config_header");
+	let _ = remove_dir_all(&out_dir);
+	create_dir_all(&out_dir).expect("output directory MUST be created");
+	let template = out_dir.join("config.h.in");
+	write(&template, "#cmakedefine WORDS_BIGENDIAN\n#define FOO_PTR_BITS @TARGET_POINTER_WIDTH@\n")
+		.expect("template MUST be written");
+	set_var("CARGO_CFG_TARGET_POINTER_WIDTH", "64");
+	set_var("CARGO_CFG_TARGET_ENDIAN", "little");
+	set_var("CARGO_CFG_TARGET_OS", "linux");
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let build_results = config
+		.out_dir(&out_dir)
+		.input_file("src/**/*.[ch]")
+		.add_config_header(ConfigHeader::new("config.h")
+			.template(template.to_str().expect("MUST be valid"))
+			.target_properties())
+		.add_config_header(ConfigHeader::new("foo/foo_config.h")
+			.target_properties()
+			.set("FOO_VERSION", "\"1.0\""))
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.config_headers,
+		[out_dir.join("config.h"), out_dir.join("foo/foo_config.h")]);
+	assert_eq!(read_to_string(out_dir.join("config.h")).expect("MUST be generated"),
+		"/* #undef WORDS_BIGENDIAN */\n#define FOO_PTR_BITS 64\n");
+	let foo_config_h = read_to_string(out_dir.join("foo/foo_config.h"))
+		.expect("MUST be generated");
+	assert!(foo_config_h.contains("#define SIZEOF_VOID_P 8\n"));
+	assert!(foo_config_h.contains("#define TARGET_OS_LINUX 1\n"));
+	assert!(foo_config_h.contains("#define FOO_VERSION \"1.0\"\n"));
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled[0].includes[0], out_dir);
+
+	let generated = BindgenBuilderContextAccess::default().generated_get();
+	assert!(generated[0].iter().any(|arg| {*arg == format!("-I{}", out_dir.display())}));
+}
+
+#[test]
+fn test_build_fail_config_header_no_template()
+{
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+
+	let result = config
+		.input_file("src/**/*.c")
+		.add_config_header(ConfigHeader::new("config.h")
+			.template("src/no_such_config.h.in"))
+		.build();
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::from(StdIoError::new(StdIoErrorKind::NotFound, ""))));
+}
+
+#[test]
+fn test_probe_name()
+{
+	assert_eq!(Probe::header("sys/epoll.h").name, "HAVE_SYS_EPOLL_H");
+	assert_eq!(Probe::function("pthread_setname_np").name, "HAVE_PTHREAD_SETNAME_NP");
+	assert_eq!(Probe::size_of("long double").name, "SIZEOF_LONG_DOUBLE");
+	assert_eq!(Probe::size_of("void *").name, "SIZEOF_VOID_P");
+	assert_eq!(Probe::size_of("unsigned __int128").name, "SIZEOF_UNSIGNED_INT128");
+	assert_eq!(Probe::flag("-mavx2").name, "HAVE_FLAG_MAVX2");
+	assert_eq!(Probe::flag("-std=c11").name, "HAVE_FLAG_STD_C11");
+	assert_eq!(Probe::header("sys/epoll.h").name("FOO_HAVE_EPOLL").name, "FOO_HAVE_EPOLL");
+}
+
+#[test]
+fn test_build_success_probes()
+{
+	use std::fs::{create_dir_all, read_to_string, remove_dir_all};
+	use busshi::std_process_command::{CommandContext, CommandContextAccess};
+
+	let out_dir = env::temp_dir().join("kusabira_test_probes");
+	let _ = remove_dir_all(&out_dir);
+	create_dir_all(&out_dir).expect("output directory MUST be created");
+	let probes = || {
+		[
+			Probe::header("sys/epoll.h"),
+			Probe::function("pthread_setname_np"),
+			Probe::size_of("off_t").include("sys/types.h"),
+			Probe::flag("-mavx2"),
+		]
+	};
+
+	let (mut config, _out_dir) = test_build_setup(Config::default(), true);
+	for probe in probes() {
+		config = config.add_probe(probe);
+	}
+	let build_results = config
+		.out_dir(&out_dir)
+		.input_file("src/**/*.c")
+		.cc_build_hook(|build| {build.define("FOO", None)})
+		.add_config_header(ConfigHeader::new("config.h")
+			.probes())
+		.build()
+		.expect("build MUST succeed");
+	let found = Some("1".to_string());
+	assert_eq!(build_results.probes,
+		[("HAVE_SYS_EPOLL_H".to_string(), found.clone()),
+		("HAVE_PTHREAD_SETNAME_NP".to_string(), found.clone()),
+		("SIZEOF_OFF_T".to_string(), found.clone()),
+		("HAVE_FLAG_MAVX2".to_string(), found.clone())]);
+	let config_h = read_to_string(out_dir.join("config.h")).expect("MUST be generated");
+	assert!(config_h.contains("#define HAVE_SYS_EPOLL_H 1\n"));
+	assert!(config_h.contains("#define SIZEOF_OFF_T 1\n"));
+
+	let probe_dir = out_dir.join("probes");
+	let executed = CommandContextAccess::default().executed_get();
+	assert_eq!(executed.len(), 5);
+	assert_eq!(executed[0].program, "cc");
+	assert_eq!(executed[0].args,
+		[OsString::from("-c"),
+		OsString::from("-o"),
+		probe_dir.join("have_sys_epoll_h.o").into_os_string(),
+		probe_dir.join("have_sys_epoll_h.c").into_os_string()]);
+	assert_eq!(executed[0].current_dir, Some(probe_dir.clone()));
+	assert_eq!(executed[1].args[0], "-o");
+	assert!(read_to_string(probe_dir.join("sizeof_off_t.c"))
+		.expect("MUST be written")
+		.starts_with("#include <sys/types.h>\n"));
+	assert_eq!(executed[4].args[..2], [OsString::from("-mavx2"), OsString::from("-Werror")]);
+
+	// The compiler is configured by the hook of the default library.
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled[0].definitions, [("FOO".to_string(), None)]);
+
+	// The cached results are reused.
+	let mut config = Config::default()
+		.out_dir(&out_dir)
+		.lib_name("hello_world");
+	for probe in probes() {
+		config = config.add_probe(probe);
+	}
+	assert!(config.build().is_err());
+	assert_eq!(CommandContextAccess::default().executed_get().len(), 5);
+}
+
+#[test]
+fn test_build_success_probes_not_found()
+{
+	use std::fs::{create_dir_all, read_to_string, remove_dir_all};
+	use busshi::std_process_command::{CommandContext, CommandContextAccess};
+
+	let out_dir = env::temp_dir().join("kusabira_test_probes_not_found");
+	let _ = remove_dir_all(&out_dir);
+	create_dir_all(&out_dir).expect("output directory MUST be created");
+	CommandContextAccess::default().emulate_failure_set(true);
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let build_results = config
+		.out_dir(&out_dir)
+		.input_file("src/**/*.c")
+		.add_probe(Probe::header("sys/epoll.h"))
+		.add_probe(Probe::size_of("long double"))
+		.add_config_header(ConfigHeader::new("config.h")
+			.probes()
+			.set_bool("SIZEOF_LONG_DOUBLE", true))
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.probes,
+		[("HAVE_SYS_EPOLL_H".to_string(), None),
+		("SIZEOF_LONG_DOUBLE".to_string(), None)]);
+	let config_h = read_to_string(out_dir.join("config.h")).expect("MUST be generated");
+	assert!(config_h.contains("/* #undef HAVE_SYS_EPOLL_H */\n"));
+	assert!(config_h.contains("#define SIZEOF_LONG_DOUBLE 1\n"));
+	assert_eq!(CommandContextAccess::default().executed_get().len(), 2);
+}
+
+#[test]
+fn test_build_success_include_dirs_defines()
+{
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let build_results = config
+		.input_file("src/*.{c,h,cc}")
+		.add_input_file("src/sub/*.h")
+		.include_dir(Path::new("include"))
+		.define("HAVE_CONFIG_H", None)
+		.define("FOO_MAX_PLUGINS", Some("16"))
+		.std("c11")
+		.cxx_std("c++17")
+		.cc_build_hook(|build| {build.include("/usr/include/foo")})
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.lib_name, Some("hello_world".to_string()));
+
+	let definitions = [
+		("HAVE_CONFIG_H".to_string(), None),
+		("FOO_MAX_PLUGINS".to_string(), Some("16".to_string())),
+	];
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 2);
+	assert_eq!(compiled[0].includes,
+		[test_source_root().join("include"),
+		StdPathBuf::from("/usr/include/foo"),
+		test_source_root().join("src"),
+		test_source_root().join("src/sub")]);
+	assert_eq!(compiled[0].definitions, definitions);
+	assert_eq!(compiled[0].std, Some("c11".to_string()));
+	assert_eq!(compiled[1].includes,
+		[test_source_root().join("include"),
+		test_source_root().join("src"),
+		test_source_root().join("src/sub")]);
+	assert_eq!(compiled[1].definitions, definitions);
+	assert_eq!(compiled[1].std, Some("c++17".to_string()));
+
+	let generated = BindgenBuilderContextAccess::default().generated_get();
+	assert_eq!(generated.len(), 2);
+	let flags = &generated[0];
+	let position = |flag: &str|
+	{
+		flags.iter().position(|arg| {arg == flag}).expect("flag MUST be passed")
+	};
+	assert!(position("-std=c11")
+		< position(&format!("-I{}", test_source_root().join("include").display())));
+	position("-DHAVE_CONFIG_H");
+	position("-DFOO_MAX_PLUGINS=16");
+	position(&format!("-I{}", test_source_root().join("src/sub").display()));
+}
+
+#[test]
+fn test_build_success_no_include_input_dirs()
+{
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	config
+		.input_file("src/*.[ch]")
+		.include_input_dirs(false)
+		.build()
+		.expect("build MUST succeed");
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled[0].includes.len(), 0);
+
+	let generated = BindgenBuilderContextAccess::default().generated_get();
+	assert!(!generated[0].iter().any(|arg| {arg.starts_with("-I")}));
+}
+
+#[test]
+fn test_system_library_version_bounds()
+{
+	use std::ops::Bound;
+	use system_library::version_bounds;
+
+	assert_eq!(version_bounds("").expect("MUST be valid"), (Bound::Unbounded, Bound::Unbounded));
+	assert_eq!(version_bounds("*").expect("MUST be valid"), (Bound::Unbounded, Bound::Unbounded));
+	assert_eq!(version_bounds("7.0").expect("MUST be valid"),
+		(Bound::Included("7.0"), Bound::Unbounded));
+	assert_eq!(version_bounds(">=7.0, <8").expect("MUST be valid"),
+		(Bound::Included("7.0"), Bound::Excluded("8")));
+	assert_eq!(version_bounds("> 7.0,<= 7.5").expect("MUST be valid"),
+		(Bound::Excluded("7.0"), Bound::Included("7.5")));
+	assert_eq!(version_bounds("=7.1").expect("MUST be valid"),
+		(Bound::Included("7.1"), Bound::Included("7.1")));
+	assert!(version_bounds(">=").is_err());
+	assert!(version_bounds("=>7.0").is_err());
+}
+
+#[test]
+fn test_system_library_link_file_metadata()
+{
+	use system_library::link_file_metadata;
+
+	assert_eq!(link_file_metadata("x86_64-unknown-linux-gnu", Path::new("/opt/libcw/lib/libcw.a")), [
+		"cargo:rustc-link-search=native=/opt/libcw/lib",
+		"cargo:rustc-link-lib=cw",
+	]);
+	assert_eq!(link_file_metadata("aarch64-apple-darwin", Path::new("/opt/libcw/lib/libcw.dylib")), [
+		"cargo:rustc-link-search=native=/opt/libcw/lib",
+		"cargo:rustc-link-lib=cw",
+	]);
+	assert_eq!(link_file_metadata("x86_64-pc-windows-gnu", Path::new("/opt/libcw/lib/libcw.dll.a")), [
+		"cargo:rustc-link-search=native=/opt/libcw/lib",
+		"cargo:rustc-link-lib=cw",
+	]);
+	assert_eq!(link_file_metadata("x86_64-pc-windows-msvc", Path::new("/opt/libcw/lib/libcw.lib")), [
+		"cargo:rustc-link-search=native=/opt/libcw/lib",
+		"cargo:rustc-link-lib=libcw",
+	]);
+	assert_eq!(link_file_metadata("x86_64-unknown-linux-gnu", Path::new("/opt/libcw/lib/cw.so.7")), [
+		"cargo:rustc-link-arg=/opt/libcw/lib/cw.so.7",
+	]);
+}
+
+#[test]
+fn test_build_success_system_library()
+{
+	use std::collections::HashMap;
+	use std::env::set_var;
+	use std::fs::{create_dir_all, remove_dir_all, write};
+	use std::ops::Bound;
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+	use busshi::pkg_config_config::{Library as PkgConfigLibrary, PkgConfigContext, PkgConfigContextAccess};
+	use system_library::find_system_library;
+
+	PkgConfigContextAccess::default().library_set("libcw", PkgConfigLibrary {
+		libs: vec!["cw".to_string()],
+		link_paths: vec![StdPathBuf::from("/opt/libcw/lib")],
+		include_paths: vec![StdPathBuf::from("/opt/libcw/include")],
+		defines: HashMap::from([
+			("LIBCW_THREADS".to_string(), None),
+			("LIBCW_API".to_string(), Some("7".to_string())),
+		]),
+		version: "7.1.0".to_string(),
+		..PkgConfigLibrary::default()
+	});
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let build_results = config
+		.input_file("src/*.[ch]")
+		.include_dir(Path::new("include"))
+		.system_library("libcw", ">=7.0")
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.system_libraries, [("libcw".to_string(), "7.1.0".to_string())]);
+
+	let probed = PkgConfigContextAccess::default().probed_get();
+	assert_eq!(probed.len(), 1);
+	assert_eq!(probed[0].name, Some("libcw".to_string()));
+	assert_eq!(probed[0].min_version, Bound::Included("7.0".to_string()));
+	assert_eq!(probed[0].max_version, Bound::Unbounded);
+	assert!(!probed[0].cargo_metadata);
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled[0].includes[..2],
+		[test_source_root().join("include"), StdPathBuf::from("/opt/libcw/include")]);
+	assert_eq!(compiled[0].definitions,
+		[("LIBCW_API".to_string(), Some("7".to_string())),
+		("LIBCW_THREADS".to_string(), None)]);
+
+	let generated = BindgenBuilderContextAccess::default().generated_get();
+	for flag in ["-I/opt/libcw/include", "-DLIBCW_API=7", "-DLIBCW_THREADS"] {
+		assert!(generated[0].iter().any(|arg| {arg == flag}), "{flag} MUST be passed");
+	}
+
+	// A static probe links the static libraries out of the system directories.
+	let static_lib_dir = env::temp_dir().join("kusabira_test_build_success_system_library/lib");
+	let _ = remove_dir_all(&static_lib_dir);
+	create_dir_all(&static_lib_dir).expect("library directory MUST be created");
+	write(static_lib_dir.join("libcw.a"), "").expect("static library MUST be written");
+	PkgConfigContextAccess::default().library_set("libcw-static", PkgConfigLibrary {
+		libs: vec!["cw".to_string(), "m".to_string()],
+		link_paths: vec![static_lib_dir.clone(), StdPathBuf::from("/usr/lib")],
+		version: "7.1.0".to_string(),
+		..PkgConfigLibrary::default()
+	});
+	assert_eq!(find_system_library("libcw-static", ">=7.0").expect("MUST be found").link_metadata, [
+		format!("cargo:rustc-link-search=native={}", static_lib_dir.display()),
+		"cargo:rustc-link-search=native=/usr/lib".to_string(),
+		"cargo:rustc-link-lib=cw".to_string(),
+		"cargo:rustc-link-lib=m".to_string(),
+	]);
+	set_var("LIBCW_STATIC_STATIC", "1");
+	assert_eq!(find_system_library("libcw-static", ">=7.0").expect("MUST be found").link_metadata, [
+		format!("cargo:rustc-link-search=native={}", static_lib_dir.display()),
+		"cargo:rustc-link-search=native=/usr/lib".to_string(),
+		"cargo:rustc-link-lib=static=cw".to_string(),
+		"cargo:rustc-link-lib=m".to_string(),
+	]);
+}
+
+#[test]
+fn test_build_fail_system_library_not_found()
+{
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let result = config
+		.input_file("src/*.[ch]")
+		.system_library("libcw", "7.0")
+		.build();
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::from(pkg_config::Error::EnvNoPkgConfig("".to_string()))));
+	assert_eq!(CcBuildContextAccess::default().compiled_get().len(), 0);
+}
+
+#[test]
+fn test_dep_include_dirs()
+{
+	let vars = [
+		("DEP_ZLIB_INCLUDE", "/opt/zlib/include"),
+		("PATH", "/usr/bin"),
+		("DEP_FOO_ROOT", "/opt/foo"),
+		("DEP__INCLUDE", "/opt/none/include"),
+		("DEP_FOO_INCLUDE", "/opt/foo/include:/opt/foo/include/compat"),
+		("DEP_BAR_INCLUDE", ""),
+	];
+	let include_dirs = dep_include_dirs(vars
+		.iter()
+		.map(|(key, value)| {(OsString::from(key), OsString::from(value))}));
+	assert_eq!(include_dirs, [
+		StdPathBuf::from("/opt/foo/include"),
+		StdPathBuf::from("/opt/foo/include/compat"),
+		StdPathBuf::from("/opt/zlib/include"),
+	]);
+}
+
+#[test]
+fn test_build_success_install_headers()
+{
+	use std::fs::{create_dir_all, read_to_string, remove_dir_all, write};
+	use busshi::glob::{GlobContext, GlobContextAccess};
+
+	let test_dir = env::temp_dir().join("kusabira_test_install_headers");
+	let _ = remove_dir_all(&test_dir);
+	create_dir_all(test_dir.join("include")).expect("include directory MUST be created");
+	create_dir_all(test_dir.join("out_dir")).expect("output directory MUST be created");
+	write(test_dir.join("include/foo.h"), "int foo(void);\n")
+		.expect("header MUST be written");
+	write(test_dir.join("include/foo_compat.h"), "#include <foo/foo.h>\n")
+		.expect("header MUST be written");
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let mut glob_ctx = GlobContextAccess::default();
+	glob_ctx.paths_push_from_iter(
+		["include/foo.h", "include/foo_compat.h"]
+		.iter()
+		.map(|path| {test_dir.join(path)}));
+
+	let out_dir = test_dir.join("out_dir");
+	let build_results = config
+		.out_dir(&out_dir)
+		.input_file("src/*.[ch]")
+		.install_header(&format!("{}/include/foo{{,_compat}}.h", test_dir.display()), "foo")
+		.metadata("version", "1.0")
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.installed_headers, [
+		out_dir.join("include/foo/foo.h"),
+		out_dir.join("include/foo/foo_compat.h"),
+	]);
+	assert_eq!(read_to_string(out_dir.join("include/foo/foo.h")).expect("MUST be installed"),
+		"int foo(void);\n");
+}
+
+#[test]
+fn test_build_fail_install_headers()
+{
+	let (_config, out_dir) = test_build_setup(Config::default(), true);
+	for (pattern, dest_dir) in [
+		("src/no_such_*.h", "foo"),
+		("src/*.h", "../foo"),
+		("src/*.h", "/usr/include"),
+	] {
+		let result = Config::default()
+			.source_root(&test_source_root())
+			.out_dir(out_dir.as_ref())
+			.lib_name("hello_world")
+			.input_file("src/*.[ch]")
+			.install_header(pattern, dest_dir)
+			.build();
+		assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+			discriminant(&MldError::from("")));
+		assert_eq!(CcBuildContextAccess::default().compiled_get().len(), 0);
+	}
+}
+
+#[test]
+fn test_build_fail_metadata()
+{
+	let (_config, out_dir) = test_build_setup(Config::default(), true);
+	for (key, value) in [
+		("", "1.0"),
+		("version=1", "1.0"),
+		("version", "1.0\n2.0"),
+		("rustc-link-lib", "foo"),
+		("rerun-if-changed", "src/foo.c"),
+	] {
+		let result = Config::default()
+			.source_root(&test_source_root())
+			.out_dir(out_dir.as_ref())
+			.lib_name("hello_world")
+			.input_file("src/*.[ch]")
+			.metadata(key, value)
+			.build();
+		assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+			discriminant(&MldError::from("")));
+	}
+}
+
+#[test]
+fn test_build_success_combined_binding()
+{
+	use std::fs::{create_dir_all, read_to_string, remove_dir_all};
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	let out_dir = env::temp_dir().join("kusabira_test_combined_binding");
+	let _ = remove_dir_all(&out_dir);
+	create_dir_all(&out_dir).expect("output directory MUST be created");
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let build_results = config
+		.out_dir(&out_dir)
+		.input_file("src/**/*.{c,h,hh}")
+		.combined_binding("hello_world_bindings")
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.header_bindings.len(), 0);
+	let combined_binding = build_results.combined_binding.expect("MUST be combined");
+	assert_eq!(combined_binding.input_header_file, out_dir.join("hello_world_bindings.h"));
+	assert_eq!(combined_binding.rust_binding_file, out_dir.join("hello_world_bindings.in"));
+
+	let source_root = test_source_root();
+	assert_eq!(read_to_string(out_dir.join("hello_world_bindings.h")).expect("MUST be generated"),
+		format!("#include \"{}\"\n#include \"{}\"\n#include \"{}\"\n",
+			source_root.join("src/hello_world_export_to_rust.h").display(),
+			source_root.join("src/sub/hello_world_internal.h").display(),
+			source_root.join("src/sub/hello_world_internal_hh.hh").display()));
+
+	let generated = BindgenBuilderContextAccess::default().generated_get();
+	assert_eq!(generated.len(), 1);
+	assert!(generated[0].iter().any(|arg| {*arg == "-xc++"}));
+	assert!(generated[0].iter().any(|arg| {
+		*arg == format!("-I{}", source_root.join("src/sub").display())
+	}));
+}
+
+#[test]
+fn test_build_success_combined_binding_no_headers()
+{
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let build_results = config
+		.input_file("src/*.c")
+		.combined_binding("hello_world_bindings")
+		.build()
+		.expect("build MUST succeed");
+	assert!(build_results.combined_binding.is_none());
+}
+
+#[test]
+fn test_bindings_mod_module_name()
+{
+	for (binding_file, name) in [
+		("/tmp/out_dir/hello_world_export_to_rust.in", "hello_world_export_to_rust"),
+		("/tmp/out_dir/hello-world.v2.in", "hello_world_v2"),
+		("/tmp/out_dir/2d.in", "_2d"),
+		("/tmp/out_dir/type.in", "type_"),
+		("/tmp/out_dir/_.in", "__"),
+	] {
+		assert_eq!(bindings_mod::module_name(Path::new(binding_file)), name);
+	}
+}
+
+#[test]
+fn test_bindings_mod_check_module_names()
+{
+	let header_bindings = [
+		HeaderBinding::from((StdPathBuf::from("/tmp/src_root/src/foo-bar.h"),
+			StdPathBuf::from("/tmp/out_dir/foo-bar.in"))),
+		HeaderBinding::from((StdPathBuf::from("/tmp/src_root/src/foo.h"),
+			StdPathBuf::from("/tmp/out_dir/foo.in"))),
+		HeaderBinding::from((StdPathBuf::from("/tmp/src_root/src/foo_bar.h"),
+			StdPathBuf::from("/tmp/out_dir/foo_bar.in"))),
+	];
+	assert!(bindings_mod::check_module_names(header_bindings[..2].iter()).is_ok());
+	let result = bindings_mod::check_module_names(header_bindings.iter());
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::from("")));
+}
+
+#[test]
+fn test_build_success_bindings_mod()
+{
+	use std::fs::{create_dir_all, read_to_string, remove_dir_all};
+
+	let out_dir = env::temp_dir().join("kusabira_test_bindings_mod");
+	let _ = remove_dir_all(&out_dir);
+	create_dir_all(&out_dir).expect("output directory MUST be created");
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let build_results = config
+		.out_dir(&out_dir)
+		.input_file("src/**/*.{c,h}")
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.bindings_mod, Some(out_dir.join(BINDINGS_MOD_FILENAME)));
+
+	let bindings_mod = read_to_string(out_dir.join(BINDINGS_MOD_FILENAME))
+		.expect("MUST be generated");
+	assert!(bindings_mod.contains(
+		"/// The bindings of `hello_world_internal.h`.\npub mod hello_world_internal\n{\n\t#![allow(non_upper_case_globals)]\n"));
+	assert!(bindings_mod.contains(&format!("\tinclude!({:?});\n",
+		out_dir.join("hello_world_export_to_rust.in").display().to_string())));
+	assert!(bindings_mod.contains("pub mod hello_world_export_to_rust\n"));
+}
+
+#[test]
+fn test_build_success_header_bindgen_builder_hook()
+{
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	let (config, out_dir) = test_build_setup(Config::default(), true);
+	let mut hooked = Vec::new();
+	let build_results = config
+		.input_file("src/**/*.h")
+		.bindgen_builder_hook(|builder| {builder.generate_comments(false)})
+		.header_bindgen_builder_hook(|builder, _header_binding| {builder.derive_debug(false)})
+		.header_bindgen_builder_hook(|builder, header_binding|
+		{
+			hooked.push(header_binding.rust_binding_file.clone());
+			builder
+		})
+		.add_header_bindgen_builder_hook(|builder, header_binding|
+		{
+			if header_binding.input_header_file.ends_with("sub/hello_world_internal.h") {
+				builder.newtype_enum("hello_world_status")
+			} else {
+				builder
+			}
+		})
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.header_bindings.len(), 2);
+	assert_eq!(hooked, [
+		out_dir.join("hello_world_export_to_rust.in"),
+		out_dir.join("hello_world_internal.in"),
+	]);
+
+	let generated = BindgenBuilderContextAccess::default().generated_get();
+	assert!(generated[0].iter().any(|arg| {arg == "--no-doc-comments"}));
+	assert!(!generated[0].iter().any(|arg| {arg == "--newtype-enum"}));
+	assert!(!generated[0].iter().any(|arg| {arg == "--no-derive-debug"}));
+	assert!(generated[1].iter().any(|arg| {arg == "--no-doc-comments"}));
+	assert!(generated[1].iter().any(|arg| {arg == "--newtype-enum"}));
+	assert!(generated[1].iter().any(|arg| {arg == "hello_world_status"}));
+}
+
+#[test]
+fn test_escape_regex()
+{
+	assert_eq!(escape_regex("/tmp/src_root/hello_world.h"), "/tmp/src_root/hello_world\\.h");
+	assert_eq!(escape_regex("C:\\foo (x86)\\c++\\[a-z]"),
+		"C:\\\\foo \\(x86\\)\\\\c\\+\\+\\\\\\[a\\-z\\]");
+}
+
+#[test]
+fn test_build_success_restrict_bindings()
+{
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let source_root = test_source_root();
+	config
+		.input_file("src/**/*.h")
+		.restrict_bindings_dir(Path::new("include"))
+		.bindgen_builder_hook(|builder| {builder.allowlist_file("/usr/include/stdint\\.h")})
+		.build()
+		.expect("build MUST succeed");
+
+	let generated = BindgenBuilderContextAccess::default().generated_get();
+	assert_eq!(generated.len(), 2);
+	let allowlisted_files: Vec<&String> = generated[0]
+		.windows(2)
+		.filter(|args| {args[0] == "--allowlist-file"})
+		.map(|args| {&args[1]})
+		.collect();
+	assert_eq!(allowlisted_files, [
+		&escape_regex(&source_root.join("src/hello_world_export_to_rust.h").to_string_lossy()),
+		&format!("{}/.*", escape_regex(&source_root.join("include").to_string_lossy())),
+		&"/usr/include/stdint\\.h".to_string(),
+	]);
+	assert!(!generated[1].iter().any(|arg| {
+		*arg == escape_regex(&source_root.join("src/hello_world_export_to_rust.h").to_string_lossy())
+	}));
+}
+
+#[test]
+fn test_build_success_restrict_bindings_parent_source_root()
+{
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let source_root = test_source_root();
+	config
+		.source_root(&source_root.join("src/.."))
+		.input_file("src/*.h")
+		.include_dir(Path::new("include"))
+		.restrict_bindings_dir(Path::new("include"))
+		.build()
+		.expect("build MUST succeed");
+
+	let generated = BindgenBuilderContextAccess::default().generated_get();
+	assert!(generated[0].iter().any(|arg| {
+		*arg == format!("-I{}", source_root.join("include").display())
+	}));
+	assert!(generated[0].iter().any(|arg| {
+		*arg == format!("{}/.*", escape_regex(&source_root.join("include").to_string_lossy()))
+	}));
+	assert!(generated[0].iter().any(|arg| {
+		*arg == escape_regex(&source_root.join("src/hello_world_export_to_rust.h").to_string_lossy())
+	}));
+}
+
+#[test]
+fn test_build_success_restrict_bindings_combined()
+{
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	let (config, out_dir) = test_build_setup(Config::default(), true);
+	let source_root = test_source_root();
+	config
+		.input_file("src/**/*.h")
+		.combined_binding("hello_world_bindings")
+		.restrict_bindings(true)
+		.build()
+		.expect("build MUST succeed");
+
+	let generated = BindgenBuilderContextAccess::default().generated_get();
+	assert_eq!(generated.len(), 1);
+	for header in ["src/hello_world_export_to_rust.h", "src/sub/hello_world_internal.h"] {
+		assert!(generated[0].iter().any(|arg| {
+			*arg == escape_regex(&source_root.join(header).to_string_lossy())
+		}));
+	}
+	assert!(!generated[0].iter().any(|arg| {
+		*arg == escape_regex(&out_dir.join("hello_world_bindings.h").to_string_lossy())
+	}));
+}
+
+fn test_vendored_bindings_setup(name: &str) -> (StdPathBuf, StdPathBuf)
+{
+	use std::env::set_var;
+	use std::fs::{create_dir_all, remove_dir_all, write};
+
+	set_var(ENV_KEY_TARGET, "x86_64-unknown-linux-gnu");
+	let test_dir = env::temp_dir().join(name);
+	let _ = remove_dir_all(&test_dir);
+	let vendored_dir = test_dir.join("bindings/x86_64-unknown-linux-gnu");
+	create_dir_all(&vendored_dir).expect("vendored bindings directory MUST be created");
+	create_dir_all(test_dir.join("out_dir")).expect("output directory MUST be created");
+	write(vendored_dir.join("hello_world_export_to_rust.in"), "pub const HELLO_WORLD: u32 = 1;\n")
+		.expect("vendored binding MUST be written");
+
+	(test_dir.join("bindings"), test_dir.join("out_dir"))
+}
+
+#[test]
+fn test_build_success_vendored_bindings_libclang_missing()
+{
+	use std::fs::read_to_string;
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	let (vendored_bindings_dir, out_dir) =
+		test_vendored_bindings_setup("kusabira_test_vendored_bindings_libclang_missing");
+	BindgenBuilderContextAccess::default().emulate_libclang_missing_set(true);
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let build_results = config
+		.out_dir(&out_dir)
+		.input_file("src/*.c")
+		.input_file("src/hello_world_export_to_rust.h")
+		.vendored_bindings(&vendored_bindings_dir)
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.header_bindings[0].vendored_binding_file,
+		Some(vendored_bindings_dir.join("x86_64-unknown-linux-gnu/hello_world_export_to_rust.in")));
+	assert_eq!(read_to_string(out_dir.join("hello_world_export_to_rust.in"))
+		.expect("MUST be copied"), "pub const HELLO_WORLD: u32 = 1;\n");
+}
+
+#[test]
+fn test_build_success_vendored_bindings_generated()
+{
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	let (vendored_bindings_dir, out_dir) =
+		test_vendored_bindings_setup("kusabira_test_vendored_bindings_generated");
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let build_results = config
+		.out_dir(&out_dir)
+		.input_file("src/*.c")
+		.input_file("src/hello_world_export_to_rust.h")
+		.vendored_bindings(&vendored_bindings_dir)
+		.build()
+		.expect("build MUST succeed");
+	assert!(build_results.header_bindings[0].vendored_binding_file.is_none());
+	assert_eq!(BindgenBuilderContextAccess::default().generated_get().len(), 1);
+}
+
+#[test]
+fn test_build_success_vendored_bindings_forced()
+{
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	let (vendored_bindings_dir, out_dir) =
+		test_vendored_bindings_setup("kusabira_test_vendored_bindings_forced");
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let build_results = config
+		.out_dir(&out_dir)
+		.input_file("src/*.c")
+		.input_file("src/hello_world_export_to_rust.h")
+		.vendored_bindings(&vendored_bindings_dir)
+		.vendored_bindings_mode(VendoredBindingsMode::Force)
+		.build()
+		.expect("build MUST succeed");
+	assert!(build_results.header_bindings[0].vendored_binding_file.is_some());
+	assert_eq!(BindgenBuilderContextAccess::default().generated_get().len(), 0);
+}
+
+#[test]
+fn test_build_fail_vendored_bindings_missing()
+{
+	let (vendored_bindings_dir, out_dir) =
+		test_vendored_bindings_setup("kusabira_test_vendored_bindings_missing");
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let result = config
+		.out_dir(&out_dir)
+		.input_file("src/**/*.[ch]")
+		.vendored_bindings(&vendored_bindings_dir)
+		.vendored_bindings_mode(VendoredBindingsMode::Force)
+		.build();
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::from("")));
+}
+
+#[test]
+#[should_panic(expected = "Unable to find libclang")]
+fn test_build_fail_vendored_bindings_missing_libclang_missing()
+{
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	let (vendored_bindings_dir, out_dir) =
+		test_vendored_bindings_setup("kusabira_test_vendored_bindings_missing_libclang_missing");
+	BindgenBuilderContextAccess::default().emulate_libclang_missing_set(true);
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let _ = config
+		.out_dir(&out_dir)
+		.input_file("src/**/*.[ch]")
+		.vendored_bindings(&vendored_bindings_dir)
+		.build();
+}
+
+#[test]
+#[should_panic(expected = "bindgen panicked")]
+fn test_build_fail_vendored_bindings_generate_panic()
+{
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	let (vendored_bindings_dir, out_dir) =
+		test_vendored_bindings_setup("kusabira_test_vendored_bindings_generate_panic");
+	BindgenBuilderContextAccess::default().emulate_generate_panic_set(true);
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let _ = config
+		.out_dir(&out_dir)
+		.input_file("src/*.c")
+		.input_file("src/hello_world_export_to_rust.h")
+		.vendored_bindings(&vendored_bindings_dir)
+		.build();
+}
+
+#[test]
+fn test_vendored_bindings_mode_from_env_value()
+{
+	for (value, mode) in [
+		("", None),
+		("0", None),
+		("1", Some(VendoredBindingsMode::Force)),
+		("force", Some(VendoredBindingsMode::Force)),
+		("fallback", Some(VendoredBindingsMode::Fallback)),
+		("verify", Some(VendoredBindingsMode::Verify)),
+		("update", Some(VendoredBindingsMode::Update)),
+	] {
+		assert_eq!(VendoredBindingsMode::from_env_value(value).expect("MUST be valid"), mode);
+	}
+	for value in ["true", "yes", "Force", "VERIFY", " update", "2"] {
+		let result = VendoredBindingsMode::from_env_value(value);
+		assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+			discriminant(&MldError::from("")));
+	}
+}
+
+#[test]
+fn test_vendored_bindings_diff()
+{
+	let old = "a\nb\nc\nd\ne\n";
+	let new = "a\nB\nc\nd\ne\nf\n";
+	assert_eq!(vendored_bindings::diff(old, new, "old.in", "new.in"),
+		"--- old.in\n+++ new.in\n@@ -2,1 +2,1 @@\n-b\n+B\n@@ -6,0 +6,1 @@\n+f");
+	assert_eq!(vendored_bindings::diff(old, old, "old.in", "new.in"),
+		"--- old.in\n+++ new.in");
+}
+
+#[test]
+fn test_build_success_vendored_bindings_verify()
+{
+	use std::fs::write;
+
+	let (vendored_bindings_dir, out_dir) =
+		test_vendored_bindings_setup("kusabira_test_vendored_bindings_verify");
+	// The mock does not write the generated binding file.
+	write(out_dir.join("hello_world_export_to_rust.in"), "pub const HELLO_WORLD: u32 = 1;\n")
+		.expect("binding MUST be written");
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let build_results = config
+		.out_dir(&out_dir)
+		.input_file("src/*.c")
+		.input_file("src/hello_world_export_to_rust.h")
+		.vendored_bindings(&vendored_bindings_dir)
+		.vendored_bindings_mode(VendoredBindingsMode::Verify)
+		.build()
+		.expect("build MUST succeed");
+	assert!(build_results.header_bindings[0].vendored_binding_file.is_none());
+}
+
+#[test]
+fn test_build_fail_vendored_bindings_verify()
+{
+	use std::fs::write;
+
+	let (vendored_bindings_dir, out_dir) =
+		test_vendored_bindings_setup("kusabira_test_vendored_bindings_verify_stale");
+	write(out_dir.join("hello_world_export_to_rust.in"), "pub const HELLO_WORLD: u32 = 2;\n")
+		.expect("binding MUST be written");
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let err = config
+		.out_dir(&out_dir)
+		.input_file("src/*.c")
+		.input_file("src/hello_world_export_to_rust.h")
+		.vendored_bindings(&vendored_bindings_dir)
+		.vendored_bindings_mode(VendoredBindingsMode::Verify)
+		.build()
+		.err()
+		.expect("MUST be error");
+	assert!(err.to_string().contains(
+		"@@ -1,1 +1,1 @@\n-pub const HELLO_WORLD: u32 = 1;\n+pub const HELLO_WORLD: u32 = 2;"));
+}
+
+#[test]
+fn test_build_fail_vendored_bindings_verify_missing()
+{
+	use std::fs::{remove_file, write};
+
+	let (vendored_bindings_dir, out_dir) =
+		test_vendored_bindings_setup("kusabira_test_vendored_bindings_verify_missing");
+	let vendored_binding_file = vendored_bindings_dir
+		.join("x86_64-unknown-linux-gnu/hello_world_export_to_rust.in");
+	remove_file(&vendored_binding_file).expect("vendored binding MUST be removed");
+	write(out_dir.join("hello_world_export_to_rust.in"), "pub const HELLO_WORLD: u32 = 1;\n")
+		.expect("binding MUST be written");
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let err = config
+		.out_dir(&out_dir)
+		.input_file("src/*.c")
+		.input_file("src/hello_world_export_to_rust.h")
+		.vendored_bindings(&vendored_bindings_dir)
+		.vendored_bindings_mode(VendoredBindingsMode::Verify)
+		.build()
+		.err()
+		.expect("MUST be error");
+	assert_eq!(discriminant(&err), discriminant(&MldError::from("")));
+	assert_eq!(err.to_string(), format!(
+		"MessageError: vendored binding {} MUST exist; set {ENV_KEY_VENDORED_BINDINGS}=update to create it",
+		vendored_binding_file.display()));
+}
+
+#[test]
+fn test_build_success_vendored_bindings_update()
+{
+	use std::fs::{read_to_string, write};
+
+	let (vendored_bindings_dir, out_dir) =
+		test_vendored_bindings_setup("kusabira_test_vendored_bindings_update");
+	write(out_dir.join("hello_world_export_to_rust.in"), "pub const HELLO_WORLD: u32 = 2;\n")
+		.expect("binding MUST be written");
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	config
+		.out_dir(&out_dir)
+		.input_file("src/*.c")
+		.input_file("src/hello_world_export_to_rust.h")
+		.vendored_bindings(&vendored_bindings_dir)
+		.vendored_bindings_mode(VendoredBindingsMode::Update)
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(read_to_string(vendored_bindings_dir
+		.join("x86_64-unknown-linux-gnu/hello_world_export_to_rust.in"))
+		.expect("MUST be updated"), "pub const HELLO_WORLD: u32 = 2;\n");
+}
+
+#[test]
+fn test_bindgen_cache_parse_bindgen_version()
+{
+	use bindgen_cache::parse_bindgen_version;
+
+	assert_eq!(parse_bindgen_version("/* automatically generated by rust-bindgen 0.68.1 */\n\npub const X: u32 = 1;\n"),
+		Some("0.68.1".to_string()));
+	assert_eq!(parse_bindgen_version("/* automatically generated by rust-bindgen  */\n"), None);
+	assert_eq!(parse_bindgen_version("pub const X: u32 = 1;\n"), None);
+	assert_eq!(parse_bindgen_version(""), None);
+}
+
+#[test]
+fn test_bindgen_cache_restore()
+{
+	use std::fs::{create_dir_all, read_to_string, remove_dir_all, write};
+	use bindgen_cache::{BindgenCache, Dependencies};
+
+	let test_dir = env::temp_dir().join("kusabira_test_bindgen_cache_restore");
+	let _ = remove_dir_all(&test_dir);
+	create_dir_all(&test_dir).expect("test directory MUST be created");
+	let header = test_dir.join("hello.h");
+	let binding_file = test_dir.join("hello.in");
+	let restored_file = test_dir.join("hello_restored.in");
+	write(&header, "int hello(void);\n").expect("header MUST be written");
+	write(&binding_file, "extern \"C\" {}\n").expect("binding MUST be written");
+
+	let flags = ["hello.h".to_string()];
+	let bindgen_cache = BindgenCache::new(&test_dir.join("cache"), &flags, Some("x86_64-unknown-linux-gnu"))
+		.expect("bindgen version MUST be known");
+	assert!(bindgen_cache.restore(&restored_file).expect("restore MUST succeed").is_none());
+	let dependencies = Dependencies {
+		files: vec![header.clone()],
+		env_vars: vec!["KUSABIRA_TEST_BINDGEN_CACHE_RESTORE".to_string()],
+	};
+	bindgen_cache.store(&binding_file, &dependencies).expect("store MUST succeed");
+
+	let restored = bindgen_cache.restore(&restored_file)
+		.expect("restore MUST succeed")
+		.expect("MUST hit");
+	assert_eq!(restored.files, [header.clone()]);
+	assert_eq!(restored.env_vars, ["KUSABIRA_TEST_BINDGEN_CACHE_RESTORE"]);
+	assert_eq!(read_to_string(&restored_file).expect("MUST be restored"), "extern \"C\" {}\n");
+
+	// Other flags or target select another manifest.
+	assert!(BindgenCache::new(&test_dir.join("cache"), &flags, Some("aarch64-unknown-linux-gnu"))
+		.expect("bindgen version MUST be known")
+		.restore(&restored_file)
+		.expect("restore MUST succeed")
+		.is_none());
+	assert!(BindgenCache::new(&test_dir.join("cache"), &["-DHELLO".to_string()], Some("x86_64-unknown-linux-gnu"))
+		.expect("bindgen version MUST be known")
+		.restore(&restored_file)
+		.expect("restore MUST succeed")
+		.is_none());
+
+	// A dependency change misses.
+	write(&header, "int hello(int);\n").expect("header MUST be written");
+	assert!(bindgen_cache.restore(&restored_file).expect("restore MUST succeed").is_none());
+	write(&header, "int hello(void);\n").expect("header MUST be written");
+	assert!(bindgen_cache.restore(&restored_file).expect("restore MUST succeed").is_some());
+	env::set_var("KUSABIRA_TEST_BINDGEN_CACHE_RESTORE", "1");
+	assert!(bindgen_cache.restore(&restored_file).expect("restore MUST succeed").is_none());
+}
+
+#[test]
+fn test_build_success_bindgen_cache()
+{
+	use std::fs::{create_dir_all, remove_dir_all, write};
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	// The target is hashed; set the same value as the other tests.
+	env::set_var(ENV_KEY_TARGET, "x86_64-unknown-linux-gnu");
+	let out_dir = env::temp_dir().join("kusabira_test_bindgen_cache");
+	let _ = remove_dir_all(&out_dir);
+	create_dir_all(&out_dir).expect("output directory MUST be created");
+	// The mock does not write the generated binding file.
+	write(out_dir.join("hello_world_export_to_rust.in"), "pub const HELLO_WORLD: u32 = 1;\n")
+		.expect("binding MUST be written");
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let build_results = config
+		.out_dir(&out_dir)
+		.input_file("src/*.c")
+		.input_file("src/hello_world_export_to_rust.h")
+		.bindgen_cache(true)
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.header_bindings[0].bindgen_cache_hit, Some(false));
+	assert!(build_results.bindgen_cache_hits.is_empty());
+	assert_eq!(build_results.bindgen_cache_misses, [out_dir.join("hello_world_export_to_rust.in")]);
+	assert!(out_dir.join(BINDGEN_CACHE_DIR).is_dir());
+	assert_eq!(BindgenBuilderContextAccess::default().generated_get().len(), 1);
+
+	let build_results = Config::default()
+		.source_root(&test_source_root())
+		.out_dir(&out_dir)
+		.lib_name("hello_world")
+		.input_file("src/*.c")
+		.input_file("src/hello_world_export_to_rust.h")
+		.bindgen_cache(true)
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.header_bindings[0].bindgen_cache_hit, Some(true));
+	assert_eq!(build_results.bindgen_cache_hits, [out_dir.join("hello_world_export_to_rust.in")]);
+	assert!(build_results.bindgen_cache_misses.is_empty());
+	assert_eq!(BindgenBuilderContextAccess::default().generated_get().len(), 1);
+}
+
+#[test]
+fn test_build_success_sync_bindgen_builder_hook()
+{
+	use busshi::bindgen_builder::{BindgenBuilderContext, BindgenBuilderContextAccess};
+
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let build_results = config
+		.input_file("src/**/*.h")
+		.header_bindgen_builder_hook(|builder, _header_binding| {builder.derive_debug(false)})
+		.sync_bindgen_builder_hook(|builder, _header_binding| {builder.generate_comments(false)})
+		.add_sync_bindgen_builder_hook(|builder, header_binding|
+		{
+			if header_binding.input_header_file.ends_with("sub/hello_world_internal.h") {
+				builder.newtype_enum("hello_world_status")
+			} else {
+				builder
+			}
+		})
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.header_bindings.len(), 2);
+
+	let generated = BindgenBuilderContextAccess::default().generated_get();
+	for flags in &generated {
+		assert!(flags.iter().any(|arg| {arg == "--no-derive-debug"}));
+		assert!(flags.iter().any(|arg| {arg == "--no-doc-comments"}));
+	}
+	assert!(!generated[0].iter().any(|arg| {arg == "--newtype-enum"}));
+	assert!(generated[1].iter().any(|arg| {arg == "--newtype-enum"}));
+}
+
+#[test]
+fn test_build_success_parallel_bindgen()
+{
+	use std::sync::Mutex;
+
+	let hooked = Mutex::new(Vec::new());
+	let (config, out_dir) = test_build_setup(Config::default(), true);
+	let build_results = config
+		.input_file("src/**/*.[ch]")
+		.parallel_bindgen(true)
+		.sync_bindgen_builder_hook(|builder, header_binding|
+		{
+			hooked.lock()
+				.expect("MUST NOT be poisoned")
+				.push(header_binding.rust_binding_file.clone());
+			builder
+		})
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.source_files.len(), 6);
+	assert_eq!(build_results.header_bindings
+		.iter()
+		.map(|header_binding| {header_binding.rust_binding_file.clone()})
+		.collect::<Vec<_>>(), [
+		out_dir.join("hello_world_export_to_rust.in"),
+		out_dir.join("hello_world_internal.in"),
+	]);
+	let mut hooked = hooked.into_inner().expect("MUST NOT be poisoned");
+	hooked.sort();
+	assert_eq!(hooked, [
+		out_dir.join("hello_world_export_to_rust.in"),
+		out_dir.join("hello_world_internal.in"),
+	]);
+}
+
+#[test]
+fn test_build_fail_parallel_bindgen_unsync_hook()
+{
+	let (config, _out_dir) = test_build_setup(Config::default(), true);
+	let result = config
+		.input_file("src/**/*.[ch]")
+		.parallel_bindgen(true)
+		.add_bindgen_builder_hook(|builder| {builder})
+		.build();
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::from("")));
+}
+
+#[test]
+fn test_parallel_run_while()
+{
+	let (doubled, main_result) = parallel::run_while(
+		(0..16).collect(),
+		|job: usize| {job * 2},
+		|| {"main"});
+	assert_eq!(doubled, (0..16).map(|job| {job * 2}).collect::<Vec<_>>());
+	assert_eq!(main_result, "main");
+
+	let (none, main_result) = parallel::run_while(Vec::<usize>::new(), |job| {job}, || {1});
+	assert!(none.is_empty());
+	assert_eq!(main_result, 1);
+}
+
+/// The binding file of [`TEST_ABI_LAYOUT_HEADER`] written by bindgen.
+static TEST_ABI_LAYOUT_BINDING: &str = r#"
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct hello_world_point {
+    pub x: ::std::os::raw::c_int,
+    pub r#type: ::std::os::raw::c_int,
+    pub _bitfield_align_1: [u8; 0],
+    pub _bitfield_1: __BindgenBitfieldUnit<[u8; 1usize]>,
+    pub callback: ::std::option::Option<unsafe extern "C" fn(arg1: ::std::os::raw::c_int) -> ::std::os::raw::c_int>,
+}
+pub type hello_world_point_t = hello_world_point;
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union hello_world_value { pub i: ::std::os::raw::c_int, pub type_: f64, }
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct hello_world_opaque { _unused: [u8; 0], }
+pub const hello_world_status_HELLO_WORLD_OK: hello_world_status = 0;
+pub type hello_world_status = ::std::os::raw::c_uint;
+#[repr(C)]
+pub struct hello_world_undeclared { pub x: u8, }
+"#;
+
+/// The header file bound by [`TEST_ABI_LAYOUT_BINDING`].
+static TEST_ABI_LAYOUT_HEADER: &str = r#"
+#include <stddef.h>
+/* struct hello_world_undeclared { char x; }; */
+typedef struct hello_world_point {
+	int x;
+	int type;
+	unsigned flag: 1;
+	int (*callback)(int);
+} hello_world_point_t;
+typedef union {
+	int i;
+	double type;
+} hello_world_value;
+struct hello_world_opaque;
+typedef enum {
+	HELLO_WORLD_OK,
+} hello_world_status;
+"#;
+
+#[test]
+fn test_abi_layout_parse_binding()
+{
+	use abi_layout::{TypeLayout, parse_binding};
+
+	let layouts = parse_binding(TEST_ABI_LAYOUT_BINDING, &[TEST_ABI_LAYOUT_HEADER.to_string()])
+		.expect("binding MUST be parsed");
+	assert_eq!(layouts, [
+		TypeLayout {
+			rust_name: "hello_world_point".to_string(),
+			c_name: "struct hello_world_point".to_string(),
+			fields: vec![
+				("x".to_string(), "x".to_string()),
+				("r#type".to_string(), "type".to_string()),
+				("callback".to_string(), "callback".to_string()),
+			],
+		},
+		TypeLayout {
+			rust_name: "hello_world_value".to_string(),
+			c_name: "hello_world_value".to_string(),
+			fields: vec![
+				("i".to_string(), "i".to_string()),
+				("type_".to_string(), "type".to_string()),
+			],
+		},
+		TypeLayout {
+			rust_name: "hello_world_status".to_string(),
+			c_name: "hello_world_status".to_string(),
+			fields: Vec::new(),
+		},
+	]);
+
+	assert!(parse_binding(TEST_ABI_LAYOUT_BINDING, &[]).expect("binding MUST be parsed").is_empty());
+
+	// The enums by the other bindgen styles, and a generic struct.
+	let header = "enum hello_world_rustified { A }; enum hello_world_newtype { B }; struct hello_world_generic;";
+	let binding = "#[repr(u32)]\npub enum hello_world_rustified { A = 0, }\n\
+		#[repr(transparent)]\npub struct hello_world_newtype(pub ::std::os::raw::c_uint);\n\
+		#[repr(C)]\npub struct hello_world_generic<T> { pub x: T, }\n";
+	let layouts = parse_binding(binding, &[header.to_string()]).expect("binding MUST be parsed");
+	assert_eq!(layouts, [
+		TypeLayout {
+			rust_name: "hello_world_rustified".to_string(),
+			c_name: "enum hello_world_rustified".to_string(),
+			fields: Vec::new(),
+		},
+		TypeLayout {
+			rust_name: "hello_world_newtype".to_string(),
+			c_name: "enum hello_world_newtype".to_string(),
+			fields: Vec::new(),
+		},
+	]);
+
+	// A bound struct without the fields to check.
+	let result = parse_binding(
+		"#[repr(C)]\npub struct hello_world_point;\n",
+		&[TEST_ABI_LAYOUT_HEADER.to_string()]);
+	assert_eq!(result.err().expect("MUST be error").to_string(),
+		"MessageError: binding of struct hello_world_point MUST have the fields for the ABI layout tests");
+	let result = parse_binding("pub struct hello_world_point {", &[TEST_ABI_LAYOUT_HEADER.to_string()]);
+	assert_eq!(discriminant(&(result.err().expect("MUST be error"))),
+		discriminant(&MldError::from("")));
+}
+
+#[test]
+fn test_abi_layout_render()
+{
+	use abi_layout::{parse_binding, render_checker, render_tests};
+
+	let layouts = parse_binding(TEST_ABI_LAYOUT_BINDING, &[TEST_ABI_LAYOUT_HEADER.to_string()])
+		.expect("binding MUST be parsed");
+
+	let checker = render_checker(Path::new("/tmp/src_root/src/hello_world.h"), &layouts);
+	assert!(checker.contains("#include \"/tmp/src_root/src/hello_world.h\"\n"));
+	assert!(checker.contains(
+		"\tprintf(\"size hello_world_point %lu\\n\", (unsigned long)sizeof(struct hello_world_point));\n"));
+	assert!(checker.contains(
+		"\tprintf(\"align hello_world_status %lu\\n\", (unsigned long)KUSABIRA_ALIGNOF(hello_world_status));\n"));
+	assert!(checker.contains(
+		"\tprintf(\"offset hello_world_value type_ %lu\\n\", (unsigned long)offsetof(hello_world_value, type));\n"));
+
+	let tests = render_tests(Path::new("/tmp/out_dir/abi_layout/hello_world/hello_world"), &layouts);
+	assert!(tests.contains("::std::process::Command::new(\"/tmp/out_dir/abi_layout/hello_world/hello_world\")"));
+	assert!(tests.contains("#[test]\nfn kusabira_abi_layout_hello_world_point()\n"));
+	assert!(tests.contains(
+		"::std::ptr::addr_of!((*ptr).r#type) as usize - ptr as usize}, kusabira_abi_layout(\"offset hello_world_point r#type\")"));
+	assert!(tests.contains("::std::mem::size_of::<hello_world_status>(), kusabira_abi_layout(\"size hello_world_status\")"));
+}
+
+#[test]
+fn test_abi_layout_read_headers()
+{
+	use std::fs::{create_dir_all, remove_dir_all, write};
+	use abi_layout::read_headers;
+
+	let header_dir = env::temp_dir().join("kusabira_test_abi_layout_read_headers");
+	let _ = remove_dir_all(&header_dir);
+	create_dir_all(header_dir.join("sub")).expect("header directory MUST be created");
+	write(header_dir.join("hello_world.h"),
+		"#include <stdio.h>\n#include \"sub/hello_world_internal.h\"\n# include \"missing.h\"\n")
+		.expect("header MUST be written");
+	write(header_dir.join("sub/hello_world_internal.h"), "#include \"../hello_world.h\"\nint x;\n")
+		.expect("header MUST be written");
+
+	let headers = read_headers(&header_dir.join("hello_world.h")).expect("headers MUST be read");
+	assert_eq!(headers.len(), 2);
+	assert!(headers[1].ends_with("int x;\n"));
+	assert!(read_headers(&header_dir.join("missing.h")).is_err());
+}
+
+#[test]
+fn test_generate_abi_layout_tests()
+{
+	use std::env::consts::EXE_SUFFIX;
+	use std::fs::{create_dir_all, read_to_string, remove_dir_all, write};
+	use busshi::std_process_command::{CommandContext, CommandContextAccess};
+
+	let out_dir = env::temp_dir().join("kusabira_test_abi_layout");
+	let _ = remove_dir_all(&out_dir);
+	create_dir_all(&out_dir).expect("output directory MUST be created");
+	write(out_dir.join("hello_world.h"), TEST_ABI_LAYOUT_HEADER).expect("header MUST be written");
+	write(out_dir.join("hello_world.in"), TEST_ABI_LAYOUT_BINDING).expect("binding MUST be written");
+
+	let config = Config::default().out_dir(&out_dir);
+	let mut header_binding = HeaderBinding::from((out_dir.join("hello_world.h"), out_dir.join("hello_world.in")));
+	let mut build = Build::default();
+	build.define("HELLO_WORLD", None);
+	config.generate_abi_layout_tests(&mut header_binding, &build, false)
+		.expect("ABI layout tests MUST be generated");
+
+	let abi_layout_dir = out_dir.join(ABI_LAYOUT_DIR);
+	let checker = abi_layout_dir.join("hello_world").join(format!("hello_world{EXE_SUFFIX}"));
+	assert_eq!(header_binding.abi_layout_checker, Some(checker.clone()));
+	assert_eq!(header_binding.abi_layout_test_file, Some(abi_layout_dir.join("hello_world.rs")));
+	assert!(read_to_string(abi_layout_dir.join("hello_world.c"))
+		.expect("checker MUST be written")
+		.contains("sizeof(struct hello_world_point)"));
+	assert!(read_to_string(abi_layout_dir.join("hello_world.rs"))
+		.expect("tests MUST be written")
+		.contains(&format!("{:?}", checker.to_string_lossy())));
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 1);
+	assert!(!compiled[0].cargo_metadata);
+	assert_eq!(compiled[0].files, [abi_layout_dir.join("hello_world.c")]);
+	assert_eq!(compiled[0].includes, [out_dir.clone()]);
+	assert_eq!(compiled[0].definitions, [("HELLO_WORLD".to_string(), None)]);
+
+	let executed = CommandContextAccess::default().executed_get();
+	assert_eq!(executed.len(), 1);
+	assert_eq!(executed[0].program, "cc");
+	assert_eq!(executed[0].args,
+		[OsString::from("-o"),
+		checker.into_os_string(),
+		abi_layout_dir.join("hello_world").join("hello_world.o").into_os_string()]);
+
+	let bindings_mod = bindings_mod::render(&[&header_binding]);
+	assert!(bindings_mod.contains(&format!(
+		"\t#[cfg(test)]\n\tmod abi_layout_tests\n\t{{\n\t\tuse super::*;\n\n\t\tinclude!({:?});\n\t}}\n}}\n",
+		abi_layout_dir.join("hello_world.rs").to_string_lossy())));
+
+	// Nothing to check.
+	write(out_dir.join("hello_world.in"), "pub const HELLO_WORLD: u32 = 1;\n").expect("binding MUST be written");
+	let mut header_binding = HeaderBinding::from((out_dir.join("hello_world.h"), out_dir.join("hello_world.in")));
+	config.generate_abi_layout_tests(&mut header_binding, &build, false)
+		.expect("ABI layout tests MUST be generated");
+	assert_eq!(header_binding.abi_layout_test_file, None);
+}
+
+#[test]
+fn test_build_success_abi_layout_tests_cxx_build_hook()
+{
+	use std::fs::{create_dir_all, read_to_string, remove_dir_all, write};
+	use busshi::glob::{GlobContextAccess, test_glob_with_setup_in};
+
+	let test_dir = env::temp_dir().join("kusabira_test_abi_layout_cxx");
+	let _ = remove_dir_all(&test_dir);
+	let source_root = test_dir.join("src_root");
+	let out_dir = test_dir.join("out_dir");
+	create_dir_all(source_root.join("src/sub")).expect("source directory MUST be created");
+	create_dir_all(&out_dir).expect("output directory MUST be created");
+	write(source_root.join("src/sub/hello_world_internal_hh.hh"), TEST_ABI_LAYOUT_HEADER)
+		.expect("header MUST be written");
+	write(out_dir.join("hello_world_internal_hh.in"), TEST_ABI_LAYOUT_BINDING)
+		.expect("binding MUST be written");
+
+	let mut glob_ctx = GlobContextAccess::default();
+	test_glob_with_setup_in(&mut glob_ctx, &source_root);
+	let build_results = Config::default()
+		.source_root(&source_root)
+		.out_dir(&out_dir)
+		.input_file("src/**/*.hh")
+		.abi_layout_tests(true)
+		.cxx_build_hook(|build| {build.define("HELLO_WORLD_CXX", None)})
+		.build()
+		.expect("build MUST succeed");
+	assert_eq!(build_results.header_bindings.len(), 1);
+	let checker_source = out_dir.join(ABI_LAYOUT_DIR).join("hello_world_internal_hh.cpp");
+	assert!(read_to_string(&checker_source)
+		.expect("checker MUST be written")
+		.contains("sizeof(struct hello_world_point)"));
+
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 1);
+	assert!(compiled[0].cpp);
+	assert_eq!(compiled[0].files, [checker_source]);
+	assert_eq!(compiled[0].definitions, [("HELLO_WORLD_CXX".to_string(), None)]);
 }
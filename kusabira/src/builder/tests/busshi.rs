@@ -63,4 +63,7 @@
 pub mod bindgen_builder;
 pub mod cc_build;
 pub mod glob;
+pub mod nasm_build;
+pub mod pkg_config_config;
 pub mod std_path_path_buf;
+pub mod std_process_command;
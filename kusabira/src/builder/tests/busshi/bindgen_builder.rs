@@ -42,13 +42,14 @@
 // SOFTWARE.
 //
 
-use bindgen::{Builder as BindgenBuilder, BindgenError, CargoCallbacks};
+use bindgen::{Builder as BindgenBuilder, BindgenError, CargoCallbacks, ClangVersion};
 use bindgen::callbacks::ParseCallbacks;
 use std::boxed::Box;
 use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::convert::{AsRef, Into};
 use std::default::Default;
+use std::fmt::{Display, Error as FmtError, Formatter};
 use std::io::{Error as StdIoError, ErrorKind as StdIoErrorKind, Result as StdIoResult};
 use std::path::{Path, PathBuf};
 
@@ -59,6 +60,12 @@ where Self: Default
 	fn emulate_generate_error_get(&self) -> bool;
 	fn emulate_write_error_set(&mut self, emulate_generate_error: bool) -> &mut Self;
 	fn emulate_write_error_get(&self) -> bool;
+	fn emulate_libclang_missing_set(&mut self, emulate_libclang_missing: bool) -> &mut Self;
+	fn emulate_libclang_missing_get(&self) -> bool;
+	fn emulate_generate_panic_set(&mut self, emulate_generate_panic: bool) -> &mut Self;
+	fn emulate_generate_panic_get(&self) -> bool;
+	fn generated_push(&mut self, flags: Vec<String>) -> &mut Self;
+	fn generated_get(&self) -> Vec<Vec<String>>;
 }
 
 #[derive(Debug)]
@@ -66,6 +73,9 @@ struct BindgenBuilderContextTLS
 {
 	emulate_generate_error: bool,
 	emulate_write_error: bool,
+	emulate_libclang_missing: bool,
+	emulate_generate_panic: bool,
+	generated: Vec<Vec<String>>,
 }
 
 impl Default for BindgenBuilderContextTLS
@@ -75,6 +85,9 @@ impl Default for BindgenBuilderContextTLS
 		BindgenBuilderContextTLS {
 			emulate_generate_error: false,
 			emulate_write_error: false,
+			emulate_libclang_missing: false,
+			emulate_generate_panic: false,
+			generated: Vec::new(),
 		}
 	}
 }
@@ -102,6 +115,39 @@ impl BindgenBuilderContext for BindgenBuilderContextTLS
 	{
 		self.emulate_write_error
 	}
+
+	fn emulate_libclang_missing_set(&mut self, emulate_libclang_missing: bool) -> &mut Self
+	{
+		self.emulate_libclang_missing = emulate_libclang_missing;
+		self
+	}
+
+	fn emulate_libclang_missing_get(&self) -> bool
+	{
+		self.emulate_libclang_missing
+	}
+
+	fn emulate_generate_panic_set(&mut self, emulate_generate_panic: bool) -> &mut Self
+	{
+		self.emulate_generate_panic = emulate_generate_panic;
+		self
+	}
+
+	fn emulate_generate_panic_get(&self) -> bool
+	{
+		self.emulate_generate_panic
+	}
+
+	fn generated_push(&mut self, flags: Vec<String>) -> &mut Self
+	{
+		self.generated.push(flags);
+		self
+	}
+
+	fn generated_get(&self) -> Vec<Vec<String>>
+	{
+		self.generated.clone()
+	}
 }
 
 thread_local!
@@ -158,6 +204,70 @@ impl BindgenBuilderContext for BindgenBuilderContextAccess
 			ctx.borrow().emulate_write_error_get()
 		})
 	}
+
+	fn emulate_libclang_missing_set(&mut self, emulate_libclang_missing: bool) -> &mut Self
+	{
+		BINDGEN_BUILDER_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow_mut().emulate_libclang_missing_set(emulate_libclang_missing);
+		});
+		self
+	}
+
+	fn emulate_libclang_missing_get(&self) -> bool
+	{
+		BINDGEN_BUILDER_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow().emulate_libclang_missing_get()
+		})
+	}
+
+	fn emulate_generate_panic_set(&mut self, emulate_generate_panic: bool) -> &mut Self
+	{
+		BINDGEN_BUILDER_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow_mut().emulate_generate_panic_set(emulate_generate_panic);
+		});
+		self
+	}
+
+	fn emulate_generate_panic_get(&self) -> bool
+	{
+		BINDGEN_BUILDER_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow().emulate_generate_panic_get()
+		})
+	}
+
+	fn generated_push(&mut self, flags: Vec<String>) -> &mut Self
+	{
+		BINDGEN_BUILDER_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow_mut().generated_push(flags);
+		});
+		self
+	}
+
+	fn generated_get(&self) -> Vec<Vec<String>>
+	{
+		BINDGEN_BUILDER_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow().generated_get()
+		})
+	}
+}
+
+pub fn clang_version() -> ClangVersion
+{
+	if BindgenBuilderContextAccess::default().emulate_libclang_missing_get() {
+		// bindgen panics if libclang cannot be loaded.
+		panic!("Unable to find libclang: emulated by mock");
+	}
+
+	ClangVersion {
+		parsed: Some((16, 0)),
+		full: "clang version 16.0.0 (emulated by mock)".to_string(),
+	}
 }
 
 #[derive(Debug)]
@@ -165,6 +275,7 @@ pub struct Builder
 {
 	builder: BindgenBuilder,
 	header: Option<String>,
+	header_contents: bool,
 	parse_callbacks: VecDeque<Box<dyn ParseCallbacks>>,
 	generate_block: bool,
 	generate_comments: bool,
@@ -188,6 +299,13 @@ impl Builder
 		self
 	}
 
+	pub fn header_contents(mut self, name: &str, contents: &str) -> Builder
+	{
+		self.builder = self.builder.header_contents(name, contents);
+		self.header_contents = true;
+		self
+	}
+
 	pub fn parse_callbacks(mut self, cb: Box<dyn ParseCallbacks>) -> Self
 	{
 		self.parse_callbacks.push_back(cb);
@@ -210,13 +328,133 @@ impl Builder
 		self
 	}
 
+	pub fn allowlist_function<T: AsRef<str>>(mut self, arg: T) -> Self
+	{
+		self.builder = self.builder.allowlist_function(arg);
+		self
+	}
+
+	pub fn allowlist_type<T: AsRef<str>>(mut self, arg: T) -> Self
+	{
+		self.builder = self.builder.allowlist_type(arg);
+		self
+	}
+
+	pub fn allowlist_var<T: AsRef<str>>(mut self, arg: T) -> Self
+	{
+		self.builder = self.builder.allowlist_var(arg);
+		self
+	}
+
+	pub fn allowlist_file<T: AsRef<str>>(mut self, arg: T) -> Self
+	{
+		self.builder = self.builder.allowlist_file(arg);
+		self
+	}
+
+	pub fn blocklist_function<T: AsRef<str>>(mut self, arg: T) -> Self
+	{
+		self.builder = self.builder.blocklist_function(arg);
+		self
+	}
+
+	pub fn blocklist_type<T: AsRef<str>>(mut self, arg: T) -> Self
+	{
+		self.builder = self.builder.blocklist_type(arg);
+		self
+	}
+
+	pub fn blocklist_item<T: AsRef<str>>(mut self, arg: T) -> Self
+	{
+		self.builder = self.builder.blocklist_item(arg);
+		self
+	}
+
+	pub fn blocklist_file<T: AsRef<str>>(mut self, arg: T) -> Self
+	{
+		self.builder = self.builder.blocklist_file(arg);
+		self
+	}
+
+	pub fn opaque_type<T: AsRef<str>>(mut self, arg: T) -> Self
+	{
+		self.builder = self.builder.opaque_type(arg);
+		self
+	}
+
+	pub fn rustified_enum<T: AsRef<str>>(mut self, arg: T) -> Self
+	{
+		self.builder = self.builder.rustified_enum(arg);
+		self
+	}
+
+	pub fn newtype_enum<T: AsRef<str>>(mut self, arg: T) -> Self
+	{
+		self.builder = self.builder.newtype_enum(arg);
+		self
+	}
+
+	pub fn constified_enum_module<T: AsRef<str>>(mut self, arg: T) -> Self
+	{
+		self.builder = self.builder.constified_enum_module(arg);
+		self
+	}
+
+	pub fn bitfield_enum<T: AsRef<str>>(mut self, arg: T) -> Self
+	{
+		self.builder = self.builder.bitfield_enum(arg);
+		self
+	}
+
+	pub fn clang_arg<T: Into<String>>(mut self, arg: T) -> Self
+	{
+		self.builder = self.builder.clang_arg(arg);
+		self
+	}
+
+	pub fn layout_tests(mut self, doit: bool) -> Self
+	{
+		self.builder = self.builder.layout_tests(doit);
+		self
+	}
+
+	pub fn derive_default(mut self, doit: bool) -> Self
+	{
+		self.builder = self.builder.derive_default(doit);
+		self
+	}
+
+	pub fn derive_debug(mut self, doit: bool) -> Self
+	{
+		self.builder = self.builder.derive_debug(doit);
+		self
+	}
+
+	pub fn command_line_flags(&self) -> Vec<String>
+	{
+		self.builder.command_line_flags()
+	}
+
 	pub fn generate(mut self) -> Result<Bindings, BindgenError>
 	{
 		while let Some(cb) = self.parse_callbacks.pop_front() {
 			self.builder = self.builder.parse_callbacks(cb);
 		}
 
-		let bindgen_builder_ctx = BindgenBuilderContextAccess::default();
+		if self.header.is_none() && self.header_contents {
+			// The probe of the bindgen version is neither emulated nor recorded.
+			return Ok(Bindings::new(self));
+		}
+
+		let mut bindgen_builder_ctx = BindgenBuilderContextAccess::default();
+		if bindgen_builder_ctx.emulate_libclang_missing_get() {
+			// bindgen panics if libclang cannot be loaded.
+			panic!("Unable to find libclang: emulated by mock");
+		}
+		if bindgen_builder_ctx.emulate_generate_panic_get() {
+			panic!("bindgen panicked: emulated by mock");
+		}
+		bindgen_builder_ctx.generated_push(self.command_line_flags());
 		let emulate_error = bindgen_builder_ctx.emulate_generate_error_get();
 
 		if emulate_error {
@@ -234,6 +472,7 @@ impl Default for Builder
 		Builder {
 			builder: BindgenBuilder::default(),
 			header: None,
+			header_contents: false,
 			parse_callbacks: VecDeque::new(),
 			generate_block: false,
 			generate_comments: false,
@@ -241,6 +480,14 @@ impl Default for Builder
 	}
 }
 
+impl Display for Bindings
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError>
+	{
+		write!(f, "/* automatically generated by rust-bindgen 0.68.1 */\n\n")
+	}
+}
+
 impl Bindings
 {
 	fn new(builder: Builder) -> Self
@@ -318,6 +565,10 @@ fn test_struct_bindgen_builder_context()
 	assert_eq!(bindgen_builder_ctx.emulate_generate_error_get(), false);
 	assert_eq!(bindgen_builder_ctx.emulate_write_error_get(), false);
 
+	assert_eq!(bindgen_builder_ctx.generated_get().len(), 0);
+	bindgen_builder_ctx.generated_push(vec!["--no-layout-tests".to_string()]);
+	assert_eq!(bindgen_builder_ctx.generated_get(), [["--no-layout-tests"]]);
+
 	BINDGEN_BUILDER_CONTEXT_TLS.with(|ctx|
 	{
 		println!("bindgen_builder_ctx (TLS) = {:?}.", ctx.borrow());
@@ -339,9 +590,16 @@ fn test_struct_builder()
 	builder = builder.parse_callbacks(Box::new(CargoCallbacks));
 	assert_eq!(builder.parse_callbacks.len(), 1);
 
+	builder = builder.allowlist_function("hello_world_.*")
+		.clang_arg("-DHELLO_WORLD");
+	let flags = builder.command_line_flags();
+	assert!(flags.windows(2).any(|w| {w == ["--allowlist-function", "hello_world_.*"]}));
+	assert!(flags.iter().any(|flag| {flag == "-DHELLO_WORLD"}));
+
 	println!("builder = {:?}.", builder);
 
 	let bindings = builder.generate().unwrap();
+	assert_eq!(BindgenBuilderContextAccess::default().generated_get(), [flags]);
 	let mut binding_pathbuf = PathBuf::new();
 	binding_pathbuf.push(header_filename);
 	binding_pathbuf.set_extension("in");
@@ -351,4 +609,20 @@ fn test_struct_builder()
 
 	println!("bindings = {:?}.", bindings);
 }
+
+#[test]
+#[should_panic(expected = "Unable to find libclang")]
+fn test_clang_version_libclang_missing()
+{
+	BindgenBuilderContextAccess::default().emulate_libclang_missing_set(true);
+	let _ = clang_version();
+}
+
+#[test]
+#[should_panic(expected = "Unable to find libclang")]
+fn test_struct_bindgen_builder_libclang_missing()
+{
+	BindgenBuilderContextAccess::default().emulate_libclang_missing_set(true);
+	let _ = Builder::default().header("hello_world_c_exported_to_rush.h").generate();
+}
 }
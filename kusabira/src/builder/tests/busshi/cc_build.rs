@@ -53,18 +53,22 @@ use std::thread_local;
 use std::vec::Vec;
 
 use super::super::ENV_KEY_OUT_DIR;
+use super::std_process_command::Command;
 
 pub trait CcBuildContext
 where Self: Default
 {
 	fn emulate_error_set(&mut self, emulate_error: bool) -> &mut Self;
 	fn emulate_error_get(&self) -> bool;
+	fn compiled_push(&mut self, build: Build) -> &mut Self;
+	fn compiled_get(&self) -> Vec<Build>;
 }
 
 #[derive(Debug)]
 struct CcBuildContextTLS
 {
 	emulate_error: bool,
+	compiled: Vec<Build>,
 }
 
 impl Default for CcBuildContextTLS
@@ -73,6 +77,7 @@ impl Default for CcBuildContextTLS
 	{
 		CcBuildContextTLS {
 			emulate_error: false,
+			compiled: Vec::new(),
 		}
 	}
 }
@@ -89,6 +94,17 @@ impl CcBuildContext for CcBuildContextTLS
 	{
 		self.emulate_error
 	}
+
+	fn compiled_push(&mut self, build: Build) -> &mut Self
+	{
+		self.compiled.push(build);
+		self
+	}
+
+	fn compiled_get(&self) -> Vec<Build>
+	{
+		self.compiled.clone()
+	}
 }
 
 thread_local!
@@ -128,6 +144,23 @@ impl CcBuildContext for CcBuildContextAccess
 			ctx.borrow().emulate_error_get()
 		})
 	}
+
+	fn compiled_push(&mut self, build: Build) -> &mut Self
+	{
+		CC_BUILD_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow_mut().compiled_push(build);
+		});
+		self
+	}
+
+	fn compiled_get(&self) -> Vec<Build>
+	{
+		CC_BUILD_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow().compiled_get()
+		})
+	}
 }
 
 #[derive(Clone, Debug)]
@@ -135,11 +168,22 @@ pub struct Build
 {
 	build: CcBuild,
 	out_dir: PathBuf,
-	files: Vec<PathBuf>,
+	pub files: Vec<PathBuf>,
+	pub flags: Vec<String>,
 	warnings: bool,
 	extra_warnings: bool,
 	warnings_into_errors: bool,
-	std: Option<String>,
+	pub std: Option<String>,
+	pub includes: Vec<PathBuf>,
+	pub definitions: Vec<(String, Option<String>)>,
+	pub output: Option<String>,
+	pub link_lib_modifiers: Vec<String>,
+	pub cargo_metadata: bool,
+	pub cpp: bool,
+	pub pic: Option<bool>,
+	pub target: Option<String>,
+	pub host: Option<String>,
+	pub objects: Vec<PathBuf>,
 }
 
 impl Build
@@ -162,6 +206,13 @@ impl Build
 		self
 	}
 
+	pub fn flag(&mut self, flag: &str) -> &mut Build
+	{
+		self.build.flag(flag);
+		self.flags.push(flag.to_string());
+		self
+	}
+
 	pub fn warnings(&mut self, b: bool) -> &mut Build
 	{
 		self.build.warnings(b);
@@ -190,11 +241,80 @@ impl Build
 		self
 	}
 
-	pub fn try_compile(&self, _output: &str) -> Result<(), CcError>
+	pub fn include<P: AsRef<Path>>(&mut self, dir: P) -> &mut Build
 	{
-		let cc_build_ctx = CcBuildContextAccess::default();
+		self.build.include(dir.as_ref());
+		self.includes.push(dir.as_ref().to_path_buf());
+		self
+	}
+
+	pub fn define<'a, V: Into<Option<&'a str>>>(&mut self, var: &str, val: V) -> &mut Build
+	{
+		let val = val.into();
+
+		self.build.define(var, val);
+		self.definitions.push((var.to_string(), val.map(String::from)));
+		self
+	}
+
+	pub fn link_lib_modifier(&mut self, link_lib_modifier: &str) -> &mut Build
+	{
+		self.build.link_lib_modifier(link_lib_modifier);
+		self.link_lib_modifiers.push(link_lib_modifier.to_string());
+		self
+	}
+
+	pub fn cargo_metadata(&mut self, cargo_metadata: bool) -> &mut Build
+	{
+		self.build.cargo_metadata(cargo_metadata);
+		self.cargo_metadata = cargo_metadata;
+		self
+	}
+
+	pub fn cpp(&mut self, cpp: bool) -> &mut Build
+	{
+		self.build.cpp(cpp);
+		self.cpp = cpp;
+		self
+	}
+
+	pub fn pic(&mut self, pic: bool) -> &mut Build
+	{
+		self.build.pic(pic);
+		self.pic = Some(pic);
+		self
+	}
+
+	pub fn target(&mut self, target: &str) -> &mut Build
+	{
+		self.build.target(target);
+		self.target = Some(target.to_string());
+		self
+	}
+
+	pub fn host(&mut self, host: &str) -> &mut Build
+	{
+		self.build.host(host);
+		self.host = Some(host.to_string());
+		self
+	}
+
+	pub fn object<P: AsRef<Path>>(&mut self, obj: P) -> &mut Build
+	{
+		self.build.object(obj.as_ref());
+		self.objects.push(obj.as_ref().to_path_buf());
+		self
+	}
+
+	pub fn try_compile(&self, output: &str) -> Result<(), CcError>
+	{
+		let mut cc_build_ctx = CcBuildContextAccess::default();
 		let emulate_error = cc_build_ctx.emulate_error_get();
 
+		let mut compiled = self.clone();
+		compiled.output = Some(output.to_string());
+		cc_build_ctx.compiled_push(compiled);
+
 		if emulate_error {
 			Err(CcError::from(
 				StdIoError::new(
@@ -205,6 +325,49 @@ impl Build
 			Ok(())
 		}
 	}
+
+	pub fn try_get_compiler(&self) -> Result<Tool, CcError>
+	{
+		let cc_build_ctx = CcBuildContextAccess::default();
+
+		if cc_build_ctx.emulate_error_get() {
+			Err(CcError::from(
+				StdIoError::new(
+					StdIoErrorKind::Other,
+					"emulated by mock"
+				)))
+		} else {
+			Ok(Tool {
+				program: PathBuf::from(if self.cpp {"c++"} else {"cc"}),
+			})
+		}
+	}
+
+	pub fn try_compile_intermediates(&self) -> Result<Vec<PathBuf>, CcError>
+	{
+		let mut cc_build_ctx = CcBuildContextAccess::default();
+		let emulate_error = cc_build_ctx.emulate_error_get();
+
+		cc_build_ctx.compiled_push(self.clone());
+
+		if emulate_error {
+			Err(CcError::from(
+				StdIoError::new(
+					StdIoErrorKind::Other,
+					"emulated by mock"
+				)))
+		} else {
+			Ok(self.files
+				.iter()
+				.map(|file|
+				{
+					let mut object = self.out_dir.join(file.file_name().unwrap_or_default());
+					object.set_extension("o");
+					object
+				})
+				.collect())
+		}
+	}
 }
 
 impl Default for Build
@@ -215,14 +378,44 @@ impl Default for Build
 			build: CcBuild::default(),
 			out_dir: PathBuf::from(env::var(&ENV_KEY_OUT_DIR).unwrap_or(".".to_string())),
 			files: Vec::new(),
+			flags: Vec::new(),
 			warnings: false,
 			extra_warnings: false,
 			warnings_into_errors: false,
 			std: None,
+			includes: Vec::new(),
+			definitions: Vec::new(),
+			output: None,
+			link_lib_modifiers: Vec::new(),
+			cargo_metadata: true,
+			cpp: false,
+			pic: None,
+			target: None,
+			host: None,
+			objects: Vec::new(),
 		}
 	}
 }
 
+#[derive(Clone, Debug)]
+pub struct Tool
+{
+	pub program: PathBuf,
+}
+
+impl Tool
+{
+	pub fn to_command(&self) -> Command
+	{
+		Command::new(&self.program)
+	}
+
+	pub fn is_like_msvc(&self) -> bool
+	{
+		false
+	}
+}
+
 mod tests {
 
 use super::*;
@@ -251,6 +444,10 @@ fn test_struct_cc_build_context()
 	});
 	assert_eq!(cc_build_ctx.emulate_error_get(), false);
 
+	assert_eq!(cc_build_ctx.compiled_get().len(), 0);
+	cc_build_ctx.compiled_push(Build::default());
+	assert_eq!(cc_build_ctx.compiled_get().len(), 1);
+
 	CC_BUILD_CONTEXT_TLS.with(|ctx|
 	{
 		println!("cc_build_ctx (TLS) = {:?}.", ctx.borrow());
@@ -289,6 +486,48 @@ fn test_struct_build()
 	assert_eq!(build.extra_warnings, true);
 	assert_eq!(build.warnings_into_errors, true);
 
+	build.include("include");
+	build.define("FOO", None);
+	build.define("BAR", "1");
+	assert_eq!(build.includes, [PathBuf::from("include")]);
+	assert_eq!(build.definitions,
+		[("FOO".to_string(), None), ("BAR".to_string(), Some("1".to_string()))]);
+
+	assert!(build.cargo_metadata);
+	build.link_lib_modifier("+whole-archive");
+	build.cargo_metadata(false);
+	assert_eq!(build.link_lib_modifiers, ["+whole-archive"]);
+	assert!(!build.cargo_metadata);
+
+	assert!(!build.cpp);
+	build.cpp(true);
+	assert!(build.cpp);
+	build.object("hello_world_c_0.o");
+	assert_eq!(build.objects, [PathBuf::from("hello_world_c_0.o")]);
+	assert_eq!(build.pic, None);
+	build.pic(true);
+	assert_eq!(build.pic, Some(true));
+	build.target("x86_64-unknown-linux-gnu");
+	build.host("x86_64-unknown-linux-gnu");
+	assert_eq!(build.target, Some("x86_64-unknown-linux-gnu".to_string()));
+	assert_eq!(build.host, Some("x86_64-unknown-linux-gnu".to_string()));
+
+	let compiler = build.try_get_compiler().expect("try_get_compiler MUST succeed");
+	assert_eq!(compiler.program, PathBuf::from("c++"));
+	assert!(!compiler.is_like_msvc());
+	assert_eq!(compiler.to_command().program, "c++");
+
+	let objects = build.try_compile_intermediates()
+		.expect("try_compile_intermediates MUST succeed");
+	assert_eq!(objects,
+		[PathBuf::from("./hello_world_c_1.o"), PathBuf::from("./hello_world_exported_to_rust.o")]);
+	assert_eq!(CcBuildContextAccess::default().compiled_get()[0].output, None);
+
+	build.try_compile("hello_world").expect("try_compile MUST succeed");
+	let compiled = CcBuildContextAccess::default().compiled_get();
+	assert_eq!(compiled.len(), 2);
+	assert_eq!(compiled[1].output, Some("hello_world".to_string()));
+
 	let build_cloned = build.clone();
 	println!("build_cloned = {:?}.", build_cloned);
 }
@@ -254,6 +254,11 @@ pub fn glob_with(pattern: &str, options: MatchOptions) -> Result<Paths, PatternE
 }
 
 pub fn test_glob_with_setup(glob_ctx: &mut GlobContextAccess)
+{
+	test_glob_with_setup_in(glob_ctx, Path::new(""));
+}
+
+pub fn test_glob_with_setup_in(glob_ctx: &mut GlobContextAccess, root: &Path)
 {
 	let glob_subject_paths = [
 		"src/hello_world_c_1.c",
@@ -284,7 +289,8 @@ pub fn test_glob_with_setup(glob_ctx: &mut GlobContextAccess)
 		"tests/sub/hello_world_tests.h",
 	];
 
-	glob_ctx.paths_push_from_iter(glob_subject_paths.iter());
+	glob_ctx.paths_push_from_iter(
+		glob_subject_paths.iter().map(|path| {root.join(path)}));
 	GLOB_CONTEXT_TLS.with(move |ctx|
 	{
 		assert_eq!(ctx.borrow_mut().paths.len(), glob_subject_paths.len());
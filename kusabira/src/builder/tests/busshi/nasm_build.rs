@@ -0,0 +1,294 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use nasm_rs::Build as NasmBuild;
+use std::cell::RefCell;
+use std::convert::AsRef;
+use std::default::Default;
+use std::path::{Path, PathBuf};
+use std::thread_local;
+use std::vec::Vec;
+
+pub trait NasmBuildContext
+where Self: Default
+{
+	fn emulate_error_set(&mut self, emulate_error: bool) -> &mut Self;
+	fn emulate_error_get(&self) -> bool;
+	fn compiled_push(&mut self, build: Build) -> &mut Self;
+	fn compiled_get(&self) -> Vec<Build>;
+}
+
+#[derive(Debug)]
+struct NasmBuildContextTLS
+{
+	emulate_error: bool,
+	compiled: Vec<Build>,
+}
+
+impl Default for NasmBuildContextTLS
+{
+	fn default() -> Self
+	{
+		NasmBuildContextTLS {
+			emulate_error: false,
+			compiled: Vec::new(),
+		}
+	}
+}
+
+impl NasmBuildContext for NasmBuildContextTLS
+{
+	fn emulate_error_set(&mut self, emulate_error: bool) -> &mut Self
+	{
+		self.emulate_error = emulate_error;
+		self
+	}
+
+	fn emulate_error_get(&self) -> bool
+	{
+		self.emulate_error
+	}
+
+	fn compiled_push(&mut self, build: Build) -> &mut Self
+	{
+		self.compiled.push(build);
+		self
+	}
+
+	fn compiled_get(&self) -> Vec<Build>
+	{
+		self.compiled.clone()
+	}
+}
+
+thread_local!
+{
+	static NASM_BUILD_CONTEXT_TLS: RefCell<NasmBuildContextTLS> =
+		RefCell::new(NasmBuildContextTLS::default());
+}
+
+#[derive(Debug)]
+pub struct NasmBuildContextAccess
+{
+}
+
+impl Default for NasmBuildContextAccess
+{
+	fn default() -> Self
+	{
+		NasmBuildContextAccess {}
+	}
+}
+
+impl NasmBuildContext for NasmBuildContextAccess
+{
+	fn emulate_error_set(&mut self, emulate_error: bool) -> &mut Self
+	{
+		NASM_BUILD_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow_mut().emulate_error_set(emulate_error);
+		});
+		self
+	}
+
+	fn emulate_error_get(&self) -> bool
+	{
+		NASM_BUILD_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow().emulate_error_get()
+		})
+	}
+
+	fn compiled_push(&mut self, build: Build) -> &mut Self
+	{
+		NASM_BUILD_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow_mut().compiled_push(build);
+		});
+		self
+	}
+
+	fn compiled_get(&self) -> Vec<Build>
+	{
+		NASM_BUILD_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow().compiled_get()
+		})
+	}
+}
+
+// nasm_rs::Build implements neither Clone nor Debug, so the mock only records
+// the configuration.
+#[derive(Clone, Debug)]
+pub struct Build
+{
+	pub out_dir: Option<PathBuf>,
+	pub files: Vec<PathBuf>,
+	pub flags: Vec<String>,
+}
+
+impl Build
+{
+	pub fn new() -> Self
+	{
+		// Make sure that the real one is available.
+		let _ = NasmBuild::new();
+
+		Build {
+			out_dir: None,
+			files: Vec::new(),
+			flags: Vec::new(),
+		}
+	}
+
+	pub fn out_dir<P: AsRef<Path>>(&mut self, out_dir: P) -> &mut Self
+	{
+		self.out_dir = Some(out_dir.as_ref().to_path_buf());
+		self
+	}
+
+	pub fn file<P: AsRef<Path>>(&mut self, p: P) -> &mut Self
+	{
+		self.files.push(p.as_ref().to_path_buf());
+		self
+	}
+
+	pub fn include<P: AsRef<Path>>(&mut self, dir: P) -> &mut Self
+	{
+		let mut flag = format!("-I{}", dir.as_ref().display());
+		if !flag.ends_with('/') {
+			flag += "/";
+		}
+		self.flags.push(flag);
+		self
+	}
+
+	pub fn define<'a, V: Into<Option<&'a str>>>(&mut self, var: &str, val: V) -> &mut Self
+	{
+		let flag = match val.into() {
+			Some(val) => format!("-D{}={}", var, val),
+			None => format!("-D{}", var),
+		};
+		self.flags.push(flag);
+		self
+	}
+
+	pub fn flag(&mut self, flag: &str) -> &mut Self
+	{
+		self.flags.push(flag.to_string());
+		self
+	}
+
+	pub fn compile_objects(&mut self) -> Result<Vec<PathBuf>, String>
+	{
+		let mut nasm_build_ctx = NasmBuildContextAccess::default();
+		let emulate_error = nasm_build_ctx.emulate_error_get();
+
+		nasm_build_ctx.compiled_push(self.clone());
+
+		if emulate_error {
+			Err("emulated by mock".to_string())
+		} else {
+			let out_dir = self.out_dir.clone().unwrap_or_default();
+			Ok(self.files
+				.iter()
+				.map(|file|
+				{
+					out_dir.join(file.file_name().unwrap_or_default()).with_extension("o")
+				})
+				.collect())
+		}
+	}
+}
+
+mod tests {
+
+use super::*;
+
+#[test]
+fn test_struct_nasm_build_context()
+{
+	let mut nasm_build_ctx = NasmBuildContextAccess::default();
+	NASM_BUILD_CONTEXT_TLS.with(|ctx|
+	{
+		assert_eq!(ctx.borrow().emulate_error, false);
+	});
+	assert_eq!(nasm_build_ctx.emulate_error_get(), false);
+
+	nasm_build_ctx.emulate_error_set(true);
+	assert_eq!(nasm_build_ctx.emulate_error_get(), true);
+	nasm_build_ctx.emulate_error_set(false);
+	assert_eq!(nasm_build_ctx.emulate_error_get(), false);
+
+	assert_eq!(nasm_build_ctx.compiled_get().len(), 0);
+	nasm_build_ctx.compiled_push(Build::new());
+	assert_eq!(nasm_build_ctx.compiled_get().len(), 1);
+
+	println!("nasm_build_ctx (Access) = {:?}.", nasm_build_ctx);
+}
+
+#[test]
+fn test_struct_build()
+{
+	let mut build = Build::new();
+
+	build.out_dir("out")
+		.file("hello_world_nasm_1.asm")
+		.include("include")
+		.define("FOO", None)
+		.define("BAR", "1")
+		.flag("-felf64");
+	assert_eq!(build.out_dir, Some(PathBuf::from("out")));
+	assert_eq!(build.files, [PathBuf::from("hello_world_nasm_1.asm")]);
+	assert_eq!(build.flags, ["-Iinclude/", "-DFOO", "-DBAR=1", "-felf64"]);
+
+	let objects = build.compile_objects().expect("compile_objects MUST succeed");
+	assert_eq!(objects, [PathBuf::from("out/hello_world_nasm_1.o")]);
+	assert_eq!(NasmBuildContextAccess::default().compiled_get().len(), 1);
+
+	NasmBuildContextAccess::default().emulate_error_set(true);
+	assert!(build.compile_objects().is_err());
+}
+
+}
@@ -0,0 +1,281 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use pkg_config::Error as PkgConfigError;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::default::Default;
+use std::io::{Error as StdIoError, ErrorKind as StdIoErrorKind};
+use std::ops::{Bound, RangeBounds};
+use std::path::PathBuf;
+use std::thread_local;
+use std::vec::Vec;
+
+pub trait PkgConfigContext
+where Self: Default
+{
+	fn library_set(&mut self, name: &str, library: Library) -> &mut Self;
+	fn library_get(&self, name: &str) -> Option<Library>;
+	fn probed_push(&mut self, config: Config) -> &mut Self;
+	fn probed_get(&self) -> Vec<Config>;
+}
+
+#[derive(Debug)]
+struct PkgConfigContextTLS
+{
+	libraries: HashMap<String, Library>,
+	probed: Vec<Config>,
+}
+
+impl Default for PkgConfigContextTLS
+{
+	fn default() -> Self
+	{
+		PkgConfigContextTLS {
+			libraries: HashMap::new(),
+			probed: Vec::new(),
+		}
+	}
+}
+
+impl PkgConfigContext for PkgConfigContextTLS
+{
+	fn library_set(&mut self, name: &str, library: Library) -> &mut Self
+	{
+		self.libraries.insert(name.to_string(), library);
+		self
+	}
+
+	fn library_get(&self, name: &str) -> Option<Library>
+	{
+		self.libraries.get(name).cloned()
+	}
+
+	fn probed_push(&mut self, config: Config) -> &mut Self
+	{
+		self.probed.push(config);
+		self
+	}
+
+	fn probed_get(&self) -> Vec<Config>
+	{
+		self.probed.clone()
+	}
+}
+
+thread_local!
+{
+	static PKG_CONFIG_CONTEXT_TLS: RefCell<PkgConfigContextTLS> =
+		RefCell::new(PkgConfigContextTLS::default());
+}
+
+#[derive(Debug)]
+pub struct PkgConfigContextAccess
+{
+}
+
+impl Default for PkgConfigContextAccess
+{
+	fn default() -> Self
+	{
+		PkgConfigContextAccess {}
+	}
+}
+
+impl PkgConfigContext for PkgConfigContextAccess
+{
+	fn library_set(&mut self, name: &str, library: Library) -> &mut Self
+	{
+		PKG_CONFIG_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow_mut().library_set(name, library);
+		});
+		self
+	}
+
+	fn library_get(&self, name: &str) -> Option<Library>
+	{
+		PKG_CONFIG_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow().library_get(name)
+		})
+	}
+
+	fn probed_push(&mut self, config: Config) -> &mut Self
+	{
+		PKG_CONFIG_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow_mut().probed_push(config);
+		});
+		self
+	}
+
+	fn probed_get(&self) -> Vec<Config>
+	{
+		PKG_CONFIG_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow().probed_get()
+		})
+	}
+}
+
+// pkg_config::Library cannot be created out of pkg_config, so the mock
+// returns its own one with the same fields.
+#[derive(Clone, Debug, Default)]
+pub struct Library
+{
+	pub libs: Vec<String>,
+	pub link_paths: Vec<PathBuf>,
+	pub link_files: Vec<PathBuf>,
+	pub frameworks: Vec<String>,
+	pub framework_paths: Vec<PathBuf>,
+	pub include_paths: Vec<PathBuf>,
+	pub ld_args: Vec<Vec<String>>,
+	pub defines: HashMap<String, Option<String>>,
+	pub version: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct Config
+{
+	pub name: Option<String>,
+	pub min_version: Bound<String>,
+	pub max_version: Bound<String>,
+	pub cargo_metadata: bool,
+	pub env_metadata: bool,
+}
+
+impl Config
+{
+	pub fn new() -> Config
+	{
+		Config {
+			name: None,
+			min_version: Bound::Unbounded,
+			max_version: Bound::Unbounded,
+			cargo_metadata: true,
+			env_metadata: false,
+		}
+	}
+
+	pub fn range_version<'a, R: RangeBounds<&'a str>>(&mut self, range: R) -> &mut Config
+	{
+		self.min_version = range.start_bound().map(|version| {version.to_string()});
+		self.max_version = range.end_bound().map(|version| {version.to_string()});
+		self
+	}
+
+	pub fn cargo_metadata(&mut self, cargo_metadata: bool) -> &mut Config
+	{
+		self.cargo_metadata = cargo_metadata;
+		self
+	}
+
+	pub fn env_metadata(&mut self, env_metadata: bool) -> &mut Config
+	{
+		self.env_metadata = env_metadata;
+		self
+	}
+
+	pub fn probe(&self, name: &str) -> Result<Library, PkgConfigError>
+	{
+		let mut pkg_config_ctx = PkgConfigContextAccess::default();
+		let mut probed = self.clone();
+		probed.name = Some(name.to_string());
+		pkg_config_ctx.probed_push(probed);
+
+		pkg_config_ctx.library_get(name).ok_or_else(||
+		{
+			PkgConfigError::Command {
+				command: format!("pkg-config {name}"),
+				cause: StdIoError::new(StdIoErrorKind::NotFound, "emulated by mock"),
+			}
+		})
+	}
+}
+
+mod tests {
+
+use super::*;
+
+#[test]
+fn test_struct_pkg_config_context()
+{
+	let mut pkg_config_ctx = PkgConfigContextAccess::default();
+	assert!(pkg_config_ctx.library_get("libfoo").is_none());
+	pkg_config_ctx.library_set("libfoo", Library::default());
+	assert!(pkg_config_ctx.library_get("libfoo").is_some());
+
+	assert_eq!(pkg_config_ctx.probed_get().len(), 0);
+	pkg_config_ctx.probed_push(Config::new());
+	assert_eq!(pkg_config_ctx.probed_get().len(), 1);
+
+	println!("pkg_config_ctx (Access) = {:?}.", pkg_config_ctx);
+}
+
+#[test]
+fn test_struct_config()
+{
+	PkgConfigContextAccess::default().library_set("libfoo", Library {
+		version: "1.2.3".to_string(),
+		..Library::default()
+	});
+
+	let library = Config::new()
+		.range_version("1.0".."2.0")
+		.cargo_metadata(false)
+		.probe("libfoo")
+		.expect("libfoo MUST be found");
+	assert_eq!(library.version, "1.2.3");
+	assert!(Config::new().probe("libbar").is_err());
+
+	let probed = PkgConfigContextAccess::default().probed_get();
+	assert_eq!(probed.len(), 2);
+	assert_eq!(probed[0].name, Some("libfoo".to_string()));
+	assert_eq!(probed[0].min_version, Bound::Included("1.0".to_string()));
+	assert_eq!(probed[0].max_version, Bound::Excluded("2.0".to_string()));
+	assert!(!probed[0].cargo_metadata);
+}
+
+}
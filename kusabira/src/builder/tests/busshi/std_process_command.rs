@@ -0,0 +1,295 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+use std::cell::RefCell;
+use std::convert::AsRef;
+use std::default::Default;
+use std::ffi::{OsStr, OsString};
+use std::fmt::{Display, Error as FmtError, Formatter};
+use std::io::Error as StdIoError;
+use std::path::{Path, PathBuf};
+use std::thread_local;
+use std::vec::Vec;
+
+pub trait CommandContext
+where Self: Default
+{
+	fn emulate_failure_set(&mut self, emulate_failure: bool) -> &mut Self;
+	fn emulate_failure_get(&self) -> bool;
+	fn executed_push(&mut self, command: Command) -> &mut Self;
+	fn executed_get(&self) -> Vec<Command>;
+}
+
+#[derive(Debug)]
+struct CommandContextTLS
+{
+	emulate_failure: bool,
+	executed: Vec<Command>,
+}
+
+impl Default for CommandContextTLS
+{
+	fn default() -> Self
+	{
+		CommandContextTLS {
+			emulate_failure: false,
+			executed: Vec::new(),
+		}
+	}
+}
+
+impl CommandContext for CommandContextTLS
+{
+	fn emulate_failure_set(&mut self, emulate_failure: bool) -> &mut Self
+	{
+		self.emulate_failure = emulate_failure;
+		self
+	}
+
+	fn emulate_failure_get(&self) -> bool
+	{
+		self.emulate_failure
+	}
+
+	fn executed_push(&mut self, command: Command) -> &mut Self
+	{
+		self.executed.push(command);
+		self
+	}
+
+	fn executed_get(&self) -> Vec<Command>
+	{
+		self.executed.clone()
+	}
+}
+
+thread_local!
+{
+	static COMMAND_CONTEXT_TLS: RefCell<CommandContextTLS> =
+		RefCell::new(CommandContextTLS::default());
+}
+
+#[derive(Debug)]
+pub struct CommandContextAccess
+{
+}
+
+impl Default for CommandContextAccess
+{
+	fn default() -> Self
+	{
+		CommandContextAccess {}
+	}
+}
+
+impl CommandContext for CommandContextAccess
+{
+	fn emulate_failure_set(&mut self, emulate_failure: bool) -> &mut Self
+	{
+		COMMAND_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow_mut().emulate_failure_set(emulate_failure);
+		});
+		self
+	}
+
+	fn emulate_failure_get(&self) -> bool
+	{
+		COMMAND_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow().emulate_failure_get()
+		})
+	}
+
+	fn executed_push(&mut self, command: Command) -> &mut Self
+	{
+		COMMAND_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow_mut().executed_push(command);
+		});
+		self
+	}
+
+	fn executed_get(&self) -> Vec<Command>
+	{
+		COMMAND_CONTEXT_TLS.with(|ctx|
+		{
+			ctx.borrow().executed_get()
+		})
+	}
+}
+
+// The mock never spawns a process; the executed commands are recorded instead.
+#[derive(Clone, Debug)]
+pub struct Command
+{
+	pub program: OsString,
+	pub args: Vec<OsString>,
+	pub current_dir: Option<PathBuf>,
+}
+
+impl Command
+{
+	pub fn new<S: AsRef<OsStr>>(program: S) -> Command
+	{
+		Command {
+			program: program.as_ref().to_os_string(),
+			args: Vec::new(),
+			current_dir: None,
+		}
+	}
+
+	pub fn arg<S: AsRef<OsStr>>(&mut self, arg: S) -> &mut Command
+	{
+		self.args.push(arg.as_ref().to_os_string());
+		self
+	}
+
+	pub fn args<I, S>(&mut self, args: I) -> &mut Command
+	where I: IntoIterator<Item = S>, S: AsRef<OsStr>
+	{
+		for arg in args {
+			self.arg(arg);
+		}
+		self
+	}
+
+	pub fn current_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Command
+	{
+		self.current_dir = Some(dir.as_ref().to_path_buf());
+		self
+	}
+
+	pub fn status(&mut self) -> Result<ExitStatus, StdIoError>
+	{
+		let mut command_ctx = CommandContextAccess::default();
+		let emulate_failure = command_ctx.emulate_failure_get();
+
+		command_ctx.executed_push(self.clone());
+
+		Ok(ExitStatus {
+			success: !emulate_failure,
+		})
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ExitStatus
+{
+	success: bool,
+}
+
+impl ExitStatus
+{
+	pub fn success(&self) -> bool
+	{
+		self.success
+	}
+}
+
+impl Display for ExitStatus
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError>
+	{
+		if self.success {
+			write!(f, "exit status: 0")
+		} else {
+			write!(f, "exit status: 1")
+		}
+	}
+}
+
+mod tests {
+
+use super::*;
+
+#[test]
+fn test_struct_command_context()
+{
+	let mut command_ctx = CommandContextAccess::default();
+	COMMAND_CONTEXT_TLS.with(|ctx|
+	{
+		assert_eq!(ctx.borrow().emulate_failure, false);
+	});
+	assert_eq!(command_ctx.emulate_failure_get(), false);
+
+	command_ctx.emulate_failure_set(true);
+	assert_eq!(command_ctx.emulate_failure_get(), true);
+	command_ctx.emulate_failure_set(false);
+	assert_eq!(command_ctx.emulate_failure_get(), false);
+
+	assert_eq!(command_ctx.executed_get().len(), 0);
+	command_ctx.executed_push(Command::new("cc"));
+	assert_eq!(command_ctx.executed_get().len(), 1);
+
+	println!("command_ctx (Access) = {:?}.", command_ctx);
+}
+
+#[test]
+fn test_struct_command()
+{
+	let status = Command::new("cc")
+		.arg("-shared")
+		.args(["-o", "libhello_world.so"])
+		.current_dir("out")
+		.status()
+		.expect("status MUST be available");
+	assert!(status.success());
+	println!("status = {}.", status);
+
+	let executed = CommandContextAccess::default().executed_get();
+	assert_eq!(executed.len(), 1);
+	assert_eq!(executed[0].program, "cc");
+	assert_eq!(executed[0].args, ["-shared", "-o", "libhello_world.so"]);
+	assert_eq!(executed[0].current_dir, Some(PathBuf::from("out")));
+
+	CommandContextAccess::default().emulate_failure_set(true);
+	let status = Command::new("cc")
+		.status()
+		.expect("status MUST be available");
+	assert!(!status.success());
+	println!("status = {}.", status);
+}
+
+}
@@ -0,0 +1,254 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//!
+//! The vendored binding files committed to the source tree, used in place of
+//! or checked against the generated binding files.
+//!
+//! Refer to [`super::Config::vendored_bindings`] for the usage.
+//!
+
+#![deny(missing_docs)]
+
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+use super::ENV_KEY_VENDORED_BINDINGS;
+use super::super::error::Error as MldError;
+
+/// The maximum number of the cells compared by [`diff`], beyond which the
+/// differing lines are reported as a whole.
+static DIFF_MAX_CELLS: usize = 1 << 22;
+
+/// The maximum number of the lines reported by [`diff`].
+static DIFF_MAX_LINES: usize = 200;
+
+///
+/// The usage of the vendored binding files configured by
+/// [`super::Config::vendored_bindings`].
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VendoredBindingsMode
+{
+	/// Generate the binding files, falling back on the vendored ones if
+	/// `libclang` cannot be loaded, the default.
+	#[default]
+	Fallback,
+	/// Use the vendored binding files without generating the binding files.
+	Force,
+	/// Generate the binding files and fail if any of them differs from the
+	/// vendored one, reporting the difference.
+	Verify,
+	/// Generate the binding files and rewrite the vendored ones by them.
+	Update,
+}
+
+impl Display for VendoredBindingsMode
+{
+	fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), FmtError>
+	{
+		match self {
+			VendoredBindingsMode::Fallback => write!(f, "fallback"),
+			VendoredBindingsMode::Force => write!(f, "force"),
+			VendoredBindingsMode::Verify => write!(f, "verify"),
+			VendoredBindingsMode::Update => write!(f, "update"),
+		}
+	}
+}
+
+impl VendoredBindingsMode
+{
+	///
+	/// Parse the value of [`super::ENV_KEY_VENDORED_BINDINGS`].
+	///
+	/// Returns [`None`] for an empty value or `0`, which leaves the
+	/// configured mode as is.  `1` forces the vendored binding files, and
+	/// the names of the modes select them.  The value is case-sensitive, as
+	/// in the manifest, and any other value is an error rather than a guess.
+	///
+	pub(super) fn from_env_value(value: &str)
+		-> Result<Option<VendoredBindingsMode>, MldError>
+	{
+		match value {
+			"" | "0" => Ok(None),
+			"1" | "force" => Ok(Some(VendoredBindingsMode::Force)),
+			"fallback" => Ok(Some(VendoredBindingsMode::Fallback)),
+			"verify" => Ok(Some(VendoredBindingsMode::Verify)),
+			"update" => Ok(Some(VendoredBindingsMode::Update)),
+			_ => Err(MldError::from(format!(
+				"{ENV_KEY_VENDORED_BINDINGS} MUST be one of \"\", \"0\", \"1\", \"fallback\", \"force\", \"verify\" or \"update\", not {value:?}"))),
+		}
+	}
+}
+
+///
+/// Report the line difference from `old` to `new`, in the unified diff
+/// format without the context lines.
+///
+/// The common leading and trailing lines are skipped, and the rest are
+/// compared by the longest common subsequence unless too many.  The report
+/// is truncated after [`DIFF_MAX_LINES`] lines.
+///
+pub(super) fn diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String
+{
+	let old_lines: Vec<&str> = old.lines().collect();
+	let new_lines: Vec<&str> = new.lines().collect();
+	let prefix = old_lines
+		.iter()
+		.zip(&new_lines)
+		.take_while(|(old_line, new_line)| {old_line == new_line})
+		.count();
+	let suffix = old_lines[prefix..]
+		.iter()
+		.rev()
+		.zip(new_lines[prefix..].iter().rev())
+		.take_while(|(old_line, new_line)| {old_line == new_line})
+		.count();
+	let old_middle = &old_lines[prefix..old_lines.len() - suffix];
+	let new_middle = &new_lines[prefix..new_lines.len() - suffix];
+
+	let mut lines = vec![
+		format!("--- {old_name}"),
+		format!("+++ {new_name}"),
+	];
+	let mut old_line_number = prefix + 1;
+	let mut new_line_number = prefix + 1;
+	let mut hunk: Vec<String> = Vec::new();
+	let mut hunk_start = (old_line_number, new_line_number);
+	for edit in edit_script(old_middle, new_middle) {
+		match edit {
+			Edit::Keep => {
+				flush_hunk(&mut lines, &mut hunk, hunk_start);
+				old_line_number += 1;
+				new_line_number += 1;
+			},
+			Edit::Delete(line) => {
+				if hunk.is_empty() {
+					hunk_start = (old_line_number, new_line_number);
+				}
+				hunk.push(format!("-{line}"));
+				old_line_number += 1;
+			},
+			Edit::Insert(line) => {
+				if hunk.is_empty() {
+					hunk_start = (old_line_number, new_line_number);
+				}
+				hunk.push(format!("+{line}"));
+				new_line_number += 1;
+			},
+		}
+	}
+	flush_hunk(&mut lines, &mut hunk, hunk_start);
+
+	if lines.len() > DIFF_MAX_LINES {
+		let truncated = lines.len() - DIFF_MAX_LINES;
+		lines.truncate(DIFF_MAX_LINES);
+		lines.push(format!("... {truncated} more lines"));
+	}
+
+	lines.join("\n")
+}
+
+/// A line edit from the old lines to the new lines.
+enum Edit<'s>
+{
+	/// The line common to both.
+	Keep,
+	/// The line only in the old lines.
+	Delete(&'s str),
+	/// The line only in the new lines.
+	Insert(&'s str),
+}
+
+/// Compute the line edits from `old` to `new`.
+fn edit_script<'s>(old: &[&'s str], new: &[&'s str]) -> Vec<Edit<'s>>
+{
+	if old.len().saturating_mul(new.len()) > DIFF_MAX_CELLS {
+		return old.iter().map(|line| {Edit::Delete(line)})
+			.chain(new.iter().map(|line| {Edit::Insert(line)}))
+			.collect();
+	}
+
+	// lcs[i][j] is the length of the longest common subsequence of
+	// old[i..] and new[j..].
+	let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+	for i in (0..old.len()).rev() {
+		for j in (0..new.len()).rev() {
+			lcs[i][j] = if old[i] == new[j] {
+				lcs[i + 1][j + 1] + 1
+			} else {
+				lcs[i + 1][j].max(lcs[i][j + 1])
+			};
+		}
+	}
+
+	let mut edits = Vec::new();
+	let (mut i, mut j) = (0, 0);
+	while i < old.len() || j < new.len() {
+		if i < old.len() && j < new.len() && old[i] == new[j] {
+			edits.push(Edit::Keep);
+			i += 1;
+			j += 1;
+		} else if i < old.len() && (j == new.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+			edits.push(Edit::Delete(old[i]));
+			i += 1;
+		} else {
+			edits.push(Edit::Insert(new[j]));
+			j += 1;
+		}
+	}
+
+	edits
+}
+
+/// Move the pending `hunk` lines into `lines` with the hunk header.
+fn flush_hunk(lines: &mut Vec<String>, hunk: &mut Vec<String>, hunk_start: (usize, usize))
+{
+	if hunk.is_empty() {
+		return;
+	}
+	let deleted = hunk.iter().filter(|line| {line.starts_with('-')}).count();
+	let inserted = hunk.len() - deleted;
+	lines.push(format!("@@ -{},{deleted} +{},{inserted} @@", hunk_start.0, hunk_start.1));
+	lines.append(hunk);
+}
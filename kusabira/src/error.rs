@@ -47,15 +47,21 @@
 use bindgen::BindgenError;
 use cc::Error as CcError;
 use glob::PatternError;
+use pkg_config::Error as PkgConfigError;
 use std::convert::From;
 use std::error::Error as ErrorTrait;
 use std::fmt::{Display, Error as FmtError, Formatter};
 use std::io::Error as StdIoError;
+use toml::de::Error as TomlError;
 
 ///
 /// The error wrapper covering all backends and [`kusabira`](crate).
 ///
+/// More variants MAY be added as backends are added, so a `match` on this
+/// enum outside [`kusabira`](crate) MUST have a wildcard arm.
+///
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
 	///
 	/// An error data by [`bindgen::Builder::generate`].
@@ -135,6 +141,46 @@ pub enum Error {
 	///
 	PatternError(PatternError),
 	///
+	/// An error data by the [`toml`] parser upon reading the
+	/// [`Cargo`](https://doc.rust-lang.org/cargo/) manifest in
+	/// [`super::builder::Config::from_manifest`].
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::error::Error;
+	///
+	/// let toml_result = "[package".parse::<toml::Table>();
+	/// assert!(toml_result.is_err());
+	/// let err = Error::from(toml_result.err().expect("successful parse"));
+	/// match err {
+	/// 	Error::TomlError(toml_err) => {
+	/// 		();
+	/// 	},
+	/// 	_ => unreachable!("unexpected Error variant"),
+	/// };
+	/// ```
+	///
+	TomlError(TomlError),
+	///
+	/// An error data by [`pkg_config::Config::probe`] upon finding a system
+	/// library configured by [`super::builder::Config::system_library`].
+	///
+	/// # Example
+	/// ```
+	/// use kusabira::error::Error;
+	///
+	/// let err = Error::from(
+	/// 	pkg_config::Error::EnvNoPkgConfig("LIBFOO_NO_PKG_CONFIG".to_string()));
+	/// match err {
+	/// 	Error::PkgConfigError(pkg_config::Error::EnvNoPkgConfig(var)) => {
+	/// 		assert_eq!(var, "LIBFOO_NO_PKG_CONFIG");
+	/// 	},
+	/// 	_ => unreachable!("unexpected Error variant"),
+	/// };
+	/// ```
+	///
+	PkgConfigError(PkgConfigError),
+	///
 	/// An error message by [`super::builder::Config::build`].
 	///
 	/// # Example
@@ -189,6 +235,20 @@ impl From<PatternError> for Error {
 	}
 }
 
+impl From<TomlError> for Error {
+	fn from(err: TomlError) -> Self
+	{
+		Error::TomlError(err)
+	}
+}
+
+impl From<PkgConfigError> for Error {
+	fn from(err: PkgConfigError) -> Self
+	{
+		Error::PkgConfigError(err)
+	}
+}
+
 impl From<String> for Error {
 	fn from(err: String) -> Self
 	{
@@ -211,6 +271,8 @@ impl Display for Error {
 			Error::CcError(err) => write!(f, "CcError: {}", err),
 			Error::StdIoError(err) => write!(f, "StdIoError: {}", err),
 			Error::PatternError(err) => write!(f, "PatternError: {}", err),
+			Error::TomlError(err) => write!(f, "TomlError: {}", err),
+			Error::PkgConfigError(err) => write!(f, "PkgConfigError: {}", err),
 			Error::MessageError(err) => write!(f, "MessageError: {}", err),
 		}
 	}
@@ -274,6 +336,28 @@ fn test_from_pattern_error()
 	println!("err = {:?}.", err);
 }
 
+#[test]
+fn test_from_toml_error()
+{
+	let err = Error::from(
+		"[package".parse::<toml::Table>()
+		.err()
+		.expect("manifest MUST be illegal"));
+
+	println!("err = {}.", err);
+	println!("err = {:?}.", err);
+}
+
+#[test]
+fn test_from_pkg_config_error()
+{
+	let err = Error::from(pkg_config::Error::EnvNoPkgConfig(
+		"LIBFOO_NO_PKG_CONFIG".to_string()));
+
+	println!("err = {}.", err);
+	println!("err = {:?}.", err);
+}
+
 #[test]
 fn test_from_string_error()
 {
@@ -55,3 +55,4 @@
 pub mod bindgen;
 pub mod cc;
 pub mod glob;
+pub mod nasm;
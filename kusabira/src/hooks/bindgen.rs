@@ -54,6 +54,8 @@ use bindgen::Builder;
 #[cfg(test)]
 use super::super::builder::tests::busshi::bindgen_builder::Builder;
 
+use super::super::builder::HeaderBinding;
+
 ///
 /// Reflect the input, ie return the configuration as is.
 ///
@@ -80,3 +82,24 @@ pub fn reflect(builder: Builder) -> Builder
 {
 	builder
 }
+
+///
+/// Reflect the input, ie return the configuration as is regardless of the
+/// header file.
+///
+/// This is the default [`bindgen::Builder`] configuration hook per header
+/// file.
+///
+/// # Example
+/// ```
+/// use kusabira::builder::Config;
+/// use kusabira::hooks::bindgen::reflect_header;
+///
+/// let config = Config::default()
+/// 	.add_header_bindgen_builder_hook(reflect_header);
+/// ```
+///
+pub fn reflect_header(builder: Builder, _header_binding: &HeaderBinding) -> Builder
+{
+	builder
+}
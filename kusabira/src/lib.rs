@@ -49,12 +49,22 @@
 //! [build script](https://doc.rust-lang.org/cargo/reference/build-scripts.html).
 //!
 //! # Features
-//! * Integrated C/C++/assembly building by [`cc`] and
+//! * Integrated C/C++/assembly building by [`cc`] and [`nasm_rs`], and
 //!   [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding generation
 //!   by [`bindgen`] as the backends.
-//! * Multiple source and header files with the glob support by [`glob`].
+//! * Multiple source and header files with the glob support by [`glob`],
+//!   extended by the brace sets, exclusion patterns and ignore files.
 //! * Single-line configuration and build execution.
-//! * Cooperation with [`system_deps`] to integrate the system libraries.
+//! * Declarative configuration in the
+//!   [`Cargo`](https://doc.rust-lang.org/cargo/) manifest.
+//! * System library integration by `pkg-config`, sharing the include
+//!   directories and the macro definitions with the backends.
+//! * Generated Rust module of the binding files, included by
+//!   [`include_bindings`].
+//! * Binding generation on multiple threads while compiling, within the
+//!   [`Cargo`](https://doc.rust-lang.org/cargo/) jobserver limit.
+//! * Generated tests checking the ABI layouts of the bound types against the
+//!   C compiler.
 //! * [`Cargo`](https://doc.rust-lang.org/cargo/) metadata output to
 //!   [`std::io::Stdout`].
 //! * Highly flexible backend configuration via the hooks.
@@ -128,10 +138,7 @@ pub mod builder;
 pub mod error;
 /// The ready-to-go hooks.
 pub mod hooks;
+/// The macros to include the generated bindings.
+mod macros;
 
 pub use error::Error as KusabiraError;
-
-// This `use` is required for the document to link to
-// `system_deps`.
-#[cfg(doc)]
-use system_deps::Config as SystemDepsConfig;
@@ -0,0 +1,104 @@
+//
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+//
+
+//
+// Copyright 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+
+//
+// MIT License
+//
+// Copyright (c) 2023 Seigo Tanimura <seigo.tanimura@gmail.com> and contributors.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in all
+// copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+// SOFTWARE.
+//
+
+//!
+//! The macros for the package using the generated
+//! [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html) binding files.
+//!
+//! Unlike [`crate::builder`], these macros are used by the package itself
+//! rather than its build script.  Hence, [`kusabira`](crate) MUST be a
+//! dependency as well as a build dependency.
+//!
+
+#![deny(missing_docs)]
+
+///
+/// Include the [Rust FFI](https://doc.rust-lang.org/nomicon/ffi.html)
+/// binding files generated by [`crate::builder::Config::build`] in the output
+/// directory `OUT_DIR`.
+///
+/// * `include_bindings!()` includes
+///   [`crate::builder::BINDINGS_MOD_FILENAME`], which declares one `pub mod`
+///   per binding file.  Each submodule is named after the header file, eg
+///   `hello_world_export_to_rust` for `hello_world_export_to_rust.h`, and
+///   allows the lints against the C naming conventions.
+/// * `include_bindings!("header_name")` includes the items of the binding
+///   file of `header_name.h` into the current module, as the hand-written
+///   [`include!`](std::include) does.  The binding file extension defaults to
+///   [`crate::builder::RUST_FFI_BINDING_EXT`], and MAY be given as the second
+///   argument.  The ABI layout tests by
+///   [`crate::builder::Config::abi_layout_tests`] are not included in this
+///   form; use `include_bindings!()` to run them.
+///
+/// # Example
+/// The module including all of the binding files:
+/// ```ignore
+/// kusabira::include_bindings!();
+///
+/// use hello_world_export_to_rust::hello_world_c_1;
+/// ```
+///
+/// The dedicated module for a single binding file, as in `himetake`:
+/// ```ignore
+/// #![allow(non_upper_case_globals)]
+/// #![allow(non_camel_case_types)]
+/// #![allow(non_snake_case)]
+/// #![allow(missing_docs)]
+///
+/// kusabira::include_bindings!("hello_world_export_to_rust");
+/// ```
+///
+#[macro_export]
+macro_rules! include_bindings
+{
+	() => {
+		include!(concat!(env!("OUT_DIR"), "/bindings_mod.rs"));
+	};
+	($header_name:literal) => {
+		$crate::include_bindings!($header_name, "in");
+	};
+	($header_name:literal, $binding_ext:literal) => {
+		include!(concat!(env!("OUT_DIR"), "/", $header_name, ".", $binding_ext));
+	};
+}